@@ -28,29 +28,47 @@ BEGIN
     SELECT id INTO config_id FROM slot_configurations WHERE name = 'Lucky Fruits 5x3';
     
     -- 插入符号
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x)
-    VALUES (config_id, 'Cherry', 'normal', 1, 5, 10, 20) RETURNING id INTO cherry_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (config_id, 'Cherry', 'normal', 1) RETURNING id INTO cherry_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (cherry_id, 3, 5), (cherry_id, 4, 10), (cherry_id, 5, 20);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x)
-    VALUES (config_id, 'Lemon', 'normal', 2, 10, 20, 40) RETURNING id INTO lemon_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (config_id, 'Lemon', 'normal', 2) RETURNING id INTO lemon_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (lemon_id, 3, 10), (lemon_id, 4, 20), (lemon_id, 5, 40);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x)
-    VALUES (config_id, 'Orange', 'normal', 3, 15, 30, 60) RETURNING id INTO orange_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (config_id, 'Orange', 'normal', 3) RETURNING id INTO orange_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (orange_id, 3, 15), (orange_id, 4, 30), (orange_id, 5, 60);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x)
-    VALUES (config_id, 'Plum', 'normal', 4, 20, 40, 80) RETURNING id INTO plum_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (config_id, 'Plum', 'normal', 4) RETURNING id INTO plum_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (plum_id, 3, 20), (plum_id, 4, 40), (plum_id, 5, 80);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x)
-    VALUES (config_id, 'Bell', 'normal', 5, 25, 50, 100) RETURNING id INTO bell_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (config_id, 'Bell', 'normal', 5) RETURNING id INTO bell_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (bell_id, 3, 25), (bell_id, 4, 50), (bell_id, 5, 100);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x)
-    VALUES (config_id, 'Bar', 'normal', 6, 30, 60, 120) RETURNING id INTO bar_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (config_id, 'Bar', 'normal', 6) RETURNING id INTO bar_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (bar_id, 3, 30), (bar_id, 4, 60), (bar_id, 5, 120);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x)
-    VALUES (config_id, 'Seven', 'normal', 7, 50, 100, 200) RETURNING id INTO seven_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (config_id, 'Seven', 'normal', 7) RETURNING id INTO seven_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (seven_id, 3, 50), (seven_id, 4, 100), (seven_id, 5, 200);
     
     INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
     VALUES (config_id, 'Wild', 'wild', 0) RETURNING id INTO wild_id;
+
+    -- Wild参与连线时赢金翻倍
+    INSERT INTO slot_wild_features (slot_config_id, symbol_id, multiplier)
+    VALUES (config_id, wild_id, 2);
     
     INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
     VALUES (config_id, 'Scatter', 'scatter', 0) RETURNING id INTO scatter_id;
@@ -174,23 +192,35 @@ BEGIN
     SELECT id INTO megaway_config_id FROM slot_configurations WHERE name = 'Megaway Fortune';
     
     -- 插入宝石主题符号
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x, payout_6x)
-    VALUES (megaway_config_id, 'Red Gem', 'normal', 1, 2, 5, 10, 20) RETURNING id INTO gem_red_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (megaway_config_id, 'Red Gem', 'normal', 1) RETURNING id INTO gem_red_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (gem_red_id, 3, 2), (gem_red_id, 4, 5), (gem_red_id, 5, 10), (gem_red_id, 6, 20);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x, payout_6x)
-    VALUES (megaway_config_id, 'Blue Gem', 'normal', 2, 3, 8, 15, 30) RETURNING id INTO gem_blue_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (megaway_config_id, 'Blue Gem', 'normal', 2) RETURNING id INTO gem_blue_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (gem_blue_id, 3, 3), (gem_blue_id, 4, 8), (gem_blue_id, 5, 15), (gem_blue_id, 6, 30);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x, payout_6x)
-    VALUES (megaway_config_id, 'Green Gem', 'normal', 3, 5, 10, 20, 40) RETURNING id INTO gem_green_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (megaway_config_id, 'Green Gem', 'normal', 3) RETURNING id INTO gem_green_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (gem_green_id, 3, 5), (gem_green_id, 4, 10), (gem_green_id, 5, 20), (gem_green_id, 6, 40);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x, payout_6x)
-    VALUES (megaway_config_id, 'Purple Gem', 'normal', 4, 8, 15, 30, 60) RETURNING id INTO gem_purple_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (megaway_config_id, 'Purple Gem', 'normal', 4) RETURNING id INTO gem_purple_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (gem_purple_id, 3, 8), (gem_purple_id, 4, 15), (gem_purple_id, 5, 30), (gem_purple_id, 6, 60);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x, payout_6x)
-    VALUES (megaway_config_id, 'Yellow Gem', 'normal', 5, 10, 20, 40, 80) RETURNING id INTO gem_yellow_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (megaway_config_id, 'Yellow Gem', 'normal', 5) RETURNING id INTO gem_yellow_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (gem_yellow_id, 3, 10), (gem_yellow_id, 4, 20), (gem_yellow_id, 5, 40), (gem_yellow_id, 6, 80);
     
-    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, payout_3x, payout_4x, payout_5x, payout_6x)
-    VALUES (megaway_config_id, 'Diamond', 'normal', 6, 20, 50, 100, 200) RETURNING id INTO gem_diamond_id;
+    INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
+    VALUES (megaway_config_id, 'Diamond', 'normal', 6) RETURNING id INTO gem_diamond_id;
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+    (gem_diamond_id, 3, 20), (gem_diamond_id, 4, 50), (gem_diamond_id, 5, 100), (gem_diamond_id, 6, 200);
     
     INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value)
     VALUES (megaway_config_id, 'Wild Star', 'wild', 0) RETURNING id INTO megaway_wild_id;
@@ -0,0 +1,3 @@
+-- 记录每次旋转的随机数种子，用于重放
+-- 适用于保留了旧数据的数据库，可以重复执行
+ALTER TABLE slot_spin_history ADD COLUMN IF NOT EXISTS rng_seed BIGINT;
@@ -0,0 +1,19 @@
+-- 添加可验证公平旋转的种子对，旋转记录中保存使用的种子对和nonce
+-- 适用于保留了旧数据的数据库，可以重复执行
+CREATE TABLE IF NOT EXISTS provably_fair_seeds (
+  id serial PRIMARY KEY,
+  player_id INTEGER REFERENCES players(id) NOT NULL,
+  server_seed VARCHAR(64) NOT NULL,
+  server_seed_hash VARCHAR(64) NOT NULL,
+  client_seed VARCHAR(64) NOT NULL,
+  nonce INTEGER NOT NULL DEFAULT 0,
+  is_active BOOLEAN NOT NULL DEFAULT TRUE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  revealed_at TIMESTAMP WITH TIME ZONE
+);
+
+ALTER TABLE slot_spin_history
+  ADD COLUMN IF NOT EXISTS fair_seed_id INTEGER REFERENCES provably_fair_seeds(id),
+  ADD COLUMN IF NOT EXISTS fair_nonce INTEGER;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_fair_seeds_active ON provably_fair_seeds(player_id) WHERE is_active;
@@ -0,0 +1,27 @@
+-- 添加免费旋转的赢金倍数和免费旋转特性状态
+-- 适用于保留了旧数据的数据库，可以重复执行
+ALTER TABLE slot_configurations
+  ADD COLUMN IF NOT EXISTS free_spins_multiplier INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE slot_spin_history
+  ADD COLUMN IF NOT EXISTS is_free_spin BOOLEAN DEFAULT FALSE,
+  ADD COLUMN IF NOT EXISTS free_spin_session_id INTEGER;
+
+CREATE TABLE IF NOT EXISTS free_spin_sessions (
+  id serial PRIMARY KEY,
+  player_id INTEGER REFERENCES players(id) NOT NULL,
+  session_id INTEGER REFERENCES game_sessions(id),
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  trigger_spin_id INTEGER REFERENCES slot_spin_history(id),
+  bet_per_line INTEGER NOT NULL,
+  total_awarded INTEGER NOT NULL,
+  remaining INTEGER NOT NULL,
+  spins_played INTEGER NOT NULL DEFAULT 0,
+  win_multiplier INTEGER NOT NULL DEFAULT 1,
+  total_win BIGINT NOT NULL DEFAULT 0,
+  status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_free_spins_player ON free_spin_sessions(player_id, slot_config_id, status);
@@ -0,0 +1,9 @@
+-- 添加Scatter触发表，没有记录的配置使用默认触发表
+-- 适用于保留了旧数据的数据库，可以重复执行
+CREATE TABLE IF NOT EXISTS slot_scatter_triggers (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
+  scatter_count INTEGER NOT NULL CHECK (scatter_count > 0),
+  free_spins INTEGER NOT NULL CHECK (free_spins >= 0),
+  UNIQUE(slot_config_id, scatter_count)
+);
@@ -0,0 +1,37 @@
+-- 添加Bonus选宝游戏的配置、奖品表和回合状态
+-- 适用于保留了旧数据的数据库，可以重复执行
+CREATE TABLE IF NOT EXISTS slot_bonus_games (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE UNIQUE,
+  trigger_count INTEGER NOT NULL CHECK (trigger_count > 0),
+  picks INTEGER NOT NULL CHECK (picks > 0),
+  board_size INTEGER NOT NULL CHECK (board_size > 0)
+);
+
+CREATE TABLE IF NOT EXISTS slot_bonus_prizes (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
+  multiplier INTEGER NOT NULL CHECK (multiplier >= 0),
+  weight INTEGER NOT NULL DEFAULT 1 CHECK (weight > 0)
+);
+
+ALTER TABLE slot_spin_history ADD COLUMN IF NOT EXISTS bonus_result JSONB;
+
+CREATE TABLE IF NOT EXISTS bonus_rounds (
+  id serial PRIMARY KEY,
+  player_id INTEGER REFERENCES players(id) NOT NULL,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  spin_id INTEGER REFERENCES slot_spin_history(id) NOT NULL,
+  total_bet BIGINT NOT NULL,
+  board JSONB NOT NULL,
+  picked JSONB NOT NULL DEFAULT '[]'::jsonb,
+  picks_remaining INTEGER NOT NULL,
+  total_win BIGINT NOT NULL DEFAULT 0,
+  board_seed BIGINT NOT NULL,
+  status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_bonus_rounds_player ON bonus_rounds(player_id, slot_config_id, status);
+CREATE INDEX IF NOT EXISTS idx_slot_bonus_prizes_config ON slot_bonus_prizes(slot_config_id);
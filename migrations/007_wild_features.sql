@@ -0,0 +1,14 @@
+-- 添加Wild符号属性，免费旋转保存保留到下一次旋转的Wild
+-- 适用于保留了旧数据的数据库，可以重复执行
+CREATE TABLE IF NOT EXISTS slot_wild_features (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
+  symbol_id INTEGER REFERENCES slot_symbols(id) ON DELETE CASCADE UNIQUE,
+  multiplier INTEGER NOT NULL DEFAULT 1 CHECK (multiplier > 0),
+  cannot_substitute INTEGER[] NOT NULL DEFAULT '{}',
+  expanding BOOLEAN NOT NULL DEFAULT false,
+  sticky BOOLEAN NOT NULL DEFAULT false,
+  walking BOOLEAN NOT NULL DEFAULT false
+);
+
+ALTER TABLE free_spin_sessions ADD COLUMN IF NOT EXISTS held_wilds JSONB NOT NULL DEFAULT '[]'::jsonb;
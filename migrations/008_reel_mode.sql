@@ -0,0 +1,5 @@
+-- 为 slot_configurations 添加卷轴抽样方式，已有配置保持按权重抽样
+-- 适用于保留了旧数据的数据库，可以重复执行
+ALTER TABLE slot_configurations
+  ADD COLUMN IF NOT EXISTS reel_mode VARCHAR(20) NOT NULL DEFAULT 'weighted'
+  CHECK (reel_mode IN ('weighted', 'stepper'));
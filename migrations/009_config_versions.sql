@@ -0,0 +1,34 @@
+-- 添加配置版本：发布时保存不可修改的快照，旋转和免费旋转记录使用的版本
+-- 适用于保留了旧数据的数据库，可以重复执行；已有配置需要发布后才能旋转
+CREATE TABLE IF NOT EXISTS slot_config_versions (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  version_number INTEGER NOT NULL,
+  snapshot JSONB NOT NULL,
+  theoretical_rtp DOUBLE PRECISION,
+  notes TEXT,
+  published_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE(slot_config_id, version_number)
+);
+
+CREATE OR REPLACE FUNCTION reject_slot_config_version_update() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'slot_config_versions rows are immutable';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS slot_config_versions_immutable ON slot_config_versions;
+CREATE TRIGGER slot_config_versions_immutable
+  BEFORE UPDATE ON slot_config_versions
+  FOR EACH ROW EXECUTE FUNCTION reject_slot_config_version_update();
+
+ALTER TABLE slot_configurations
+  ADD COLUMN IF NOT EXISTS live_version_id INTEGER REFERENCES slot_config_versions(id);
+
+ALTER TABLE slot_spin_history
+  ADD COLUMN IF NOT EXISTS config_version_id INTEGER REFERENCES slot_config_versions(id);
+
+ALTER TABLE free_spin_sessions
+  ADD COLUMN IF NOT EXISTS config_version_id INTEGER REFERENCES slot_config_versions(id);
+
+CREATE INDEX IF NOT EXISTS idx_spins_config_version ON slot_spin_history(config_version_id);
@@ -0,0 +1,38 @@
+-- 把 slot_symbols.payout_2x .. payout_6x 迁移到 slot_symbol_payouts
+-- 适用于保留了旧数据的数据库，可以重复执行
+
+CREATE TABLE IF NOT EXISTS slot_symbol_payouts (
+  id serial PRIMARY KEY,
+  symbol_id INTEGER REFERENCES slot_symbols(id) ON DELETE CASCADE NOT NULL,
+  symbol_count INTEGER NOT NULL CHECK (symbol_count > 0),
+  payout INTEGER NOT NULL CHECK (payout > 0),
+  UNIQUE(symbol_id, symbol_count)
+);
+
+DO $$
+BEGIN
+  IF EXISTS (
+    SELECT 1 FROM information_schema.columns
+    WHERE table_name = 'slot_symbols' AND column_name = 'payout_2x'
+  ) THEN
+    INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout)
+    SELECT s.id, p.symbol_count, p.payout
+    FROM slot_symbols s
+    CROSS JOIN LATERAL (VALUES
+      (2, s.payout_2x),
+      (3, s.payout_3x),
+      (4, s.payout_4x),
+      (5, s.payout_5x),
+      (6, s.payout_6x)
+    ) AS p(symbol_count, payout)
+    WHERE p.payout > 0
+    ON CONFLICT (symbol_id, symbol_count) DO NOTHING;
+
+    ALTER TABLE slot_symbols
+      DROP COLUMN payout_2x,
+      DROP COLUMN payout_3x,
+      DROP COLUMN payout_4x,
+      DROP COLUMN payout_5x,
+      DROP COLUMN payout_6x;
+  END IF;
+END $$;
@@ -0,0 +1,5 @@
+-- 为 slot_configurations 添加赢奖计算方式，已有配置保持按支付线计算
+-- 适用于保留了旧数据的数据库，可以重复执行
+ALTER TABLE slot_configurations
+  ADD COLUMN IF NOT EXISTS evaluation_mode VARCHAR(20) NOT NULL DEFAULT 'lines'
+  CHECK (evaluation_mode IN ('lines', 'cluster'));
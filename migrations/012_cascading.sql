@@ -0,0 +1,8 @@
+-- 为 slot_configurations 添加连锁消除设置，已有配置保持关闭
+-- 适用于保留了旧数据的数据库，可以重复执行
+ALTER TABLE slot_configurations
+  ADD COLUMN IF NOT EXISTS cascading BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN IF NOT EXISTS cascade_multipliers INTEGER[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE slot_spin_history
+  ADD COLUMN IF NOT EXISTS cascades JSONB;
@@ -0,0 +1,45 @@
+-- 添加钱币符号类型和Hold and Spin相关的表
+-- 适用于保留了旧数据的数据库，可以重复执行
+
+ALTER TABLE slot_symbols DROP CONSTRAINT IF EXISTS slot_symbols_symbol_type_check;
+ALTER TABLE slot_symbols ADD CONSTRAINT slot_symbols_symbol_type_check
+  CHECK (symbol_type IN ('normal', 'wild', 'scatter', 'bonus', 'money'));
+
+CREATE TABLE IF NOT EXISTS slot_hold_and_spin (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE UNIQUE,
+  trigger_count INTEGER NOT NULL CHECK (trigger_count > 0),
+  respins INTEGER NOT NULL CHECK (respins > 0)
+);
+
+CREATE TABLE IF NOT EXISTS slot_money_values (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
+  multiplier INTEGER NOT NULL CHECK (multiplier >= 0),
+  jackpot VARCHAR(10) CHECK (jackpot IN ('mini', 'minor', 'major', 'grand')),
+  weight INTEGER NOT NULL DEFAULT 1 CHECK (weight > 0)
+);
+
+ALTER TABLE slot_spin_history ADD COLUMN IF NOT EXISTS hold_and_spin_result JSONB;
+
+CREATE TABLE IF NOT EXISTS hold_and_spin_rounds (
+  id serial PRIMARY KEY,
+  player_id INTEGER REFERENCES players(id) NOT NULL,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  spin_id INTEGER REFERENCES slot_spin_history(id) NOT NULL,
+  config_version_id INTEGER REFERENCES slot_config_versions(id),
+  total_bet BIGINT NOT NULL,
+  reel_rows JSONB NOT NULL,
+  coins JSONB NOT NULL,
+  respins_remaining INTEGER NOT NULL,
+  respins_played INTEGER NOT NULL DEFAULT 0,
+  total_win BIGINT NOT NULL DEFAULT 0,
+  seed BIGINT NOT NULL,
+  status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_hold_and_spin_rounds_player
+  ON hold_and_spin_rounds(player_id, slot_config_id, status);
+CREATE INDEX IF NOT EXISTS idx_slot_money_values_config ON slot_money_values(slot_config_id);
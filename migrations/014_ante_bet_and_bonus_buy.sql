@@ -0,0 +1,36 @@
+-- 添加卷轴组、加注、购买免费旋转和司法管辖区设置
+-- 适用于保留了旧数据的数据库，可以重复执行；已有的卷轴位置归入基础卷轴组
+
+ALTER TABLE slot_reel_symbols ADD COLUMN IF NOT EXISTS reel_set VARCHAR(50) NOT NULL DEFAULT 'base';
+ALTER TABLE slot_reel_symbols
+  DROP CONSTRAINT IF EXISTS slot_reel_symbols_slot_config_id_reel_number_position_key;
+ALTER TABLE slot_reel_symbols
+  DROP CONSTRAINT IF EXISTS slot_reel_symbols_slot_config_id_reel_set_reel_number_posit_key;
+ALTER TABLE slot_reel_symbols
+  ADD CONSTRAINT slot_reel_symbols_slot_config_id_reel_set_reel_number_posit_key
+  UNIQUE (slot_config_id, reel_set, reel_number, position);
+
+CREATE TABLE IF NOT EXISTS slot_ante_bets (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE UNIQUE,
+  cost_percent INTEGER NOT NULL CHECK (cost_percent > 0)
+);
+
+CREATE TABLE IF NOT EXISTS slot_bonus_buys (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE UNIQUE,
+  cost_multiplier INTEGER NOT NULL CHECK (cost_multiplier > 0),
+  free_spins INTEGER NOT NULL CHECK (free_spins > 0)
+);
+
+CREATE TABLE IF NOT EXISTS slot_jurisdiction_rules (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  jurisdiction VARCHAR(10) NOT NULL,
+  ante_bet_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  bonus_buy_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  UNIQUE(slot_config_id, jurisdiction)
+);
+
+ALTER TABLE slot_spin_history ADD COLUMN IF NOT EXISTS bet_mode VARCHAR(20) NOT NULL DEFAULT 'standard'
+  CHECK (bet_mode IN ('standard', 'ante', 'bonus_buy'));
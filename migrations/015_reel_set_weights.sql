@@ -0,0 +1,10 @@
+-- 添加基础游戏卷轴组的选择权重
+-- 适用于保留了旧数据的数据库，可以重复执行；没有权重记录的配置继续总是使用基础卷轴
+
+CREATE TABLE IF NOT EXISTS slot_reel_set_weights (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  reel_set VARCHAR(50) NOT NULL,
+  weight INTEGER NOT NULL CHECK (weight > 0),
+  UNIQUE(slot_config_id, reel_set)
+);
@@ -0,0 +1,5 @@
+-- 记录玩家选择的支付线数，旋转记录和免费旋转都保存，免费旋转沿用触发时的支付线数
+-- 适用于保留了旧数据的数据库，可以重复执行；旧记录为 NULL，表示全部支付线
+
+ALTER TABLE slot_spin_history ADD COLUMN IF NOT EXISTS active_lines INTEGER;
+ALTER TABLE free_spin_sessions ADD COLUMN IF NOT EXISTS active_lines INTEGER;
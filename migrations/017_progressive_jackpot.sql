@@ -0,0 +1,13 @@
+-- 经典老虎机的累积奖池保存到数据库，与旋转在同一事务中更新，之前保存在服务器内存中
+-- 适用于保留了旧数据的数据库，可以重复执行
+CREATE TABLE IF NOT EXISTS progressive_jackpot (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  current_amount BIGINT NOT NULL,
+  min_amount BIGINT NOT NULL,
+  contribution_rate DOUBLE PRECISION NOT NULL,
+  last_won TIMESTAMP WITH TIME ZONE
+);
+
+INSERT INTO progressive_jackpot (id, current_amount, min_amount, contribution_rate)
+VALUES (1, 10000, 10000, 0.02)
+ON CONFLICT (id) DO NOTHING;
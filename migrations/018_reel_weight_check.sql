@@ -0,0 +1,20 @@
+-- 卷轴位置权重必须为正：全为0的卷轴会在抽样时 panic
+-- 适用于保留了旧数据的数据库，可以重复执行
+-- weighted 模式从不抽取权重为0的位置，直接删除不影响结果；stepper 模式的窗口会经过这些位置，需要先手动处理
+
+DO $$
+BEGIN
+  IF EXISTS (
+    SELECT 1
+    FROM slot_reel_symbols r
+    JOIN slot_configurations c ON c.id = r.slot_config_id
+    WHERE r.weight <= 0 AND c.reel_mode = 'stepper'
+  ) THEN
+    RAISE EXCEPTION 'stepper reels contain positions with a weight below 1; give them a positive weight or remove them before migrating';
+  END IF;
+END $$;
+
+DELETE FROM slot_reel_symbols WHERE weight <= 0;
+
+ALTER TABLE slot_reel_symbols DROP CONSTRAINT IF EXISTS slot_reel_symbols_weight_check;
+ALTER TABLE slot_reel_symbols ADD CONSTRAINT slot_reel_symbols_weight_check CHECK (weight > 0);
@@ -0,0 +1,4 @@
+-- 可验证公平旋转触发的Hold and Spin不再使用随机种子，重转由该次旋转 nonce 的 HMAC 随机数流推出
+-- 适用于保留了旧数据的数据库，可以重复执行
+
+ALTER TABLE hold_and_spin_rounds ALTER COLUMN seed DROP NOT NULL;
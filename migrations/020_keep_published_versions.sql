@@ -0,0 +1,7 @@
+-- 发布过的版本是旋转记录的依据，删除配置时不能级联删除
+-- 适用于保留了旧数据的数据库，可以重复执行
+
+ALTER TABLE slot_config_versions DROP CONSTRAINT IF EXISTS slot_config_versions_slot_config_id_fkey;
+ALTER TABLE slot_config_versions
+  ADD CONSTRAINT slot_config_versions_slot_config_id_fkey
+  FOREIGN KEY (slot_config_id) REFERENCES slot_configurations(id) ON DELETE RESTRICT;
@@ -0,0 +1,4 @@
+-- 玩家所在地区由服务端保存，旋转时按玩家记录检查加注和购买免费旋转是否可用
+-- 适用于保留了旧数据的数据库，可以重复执行
+
+ALTER TABLE players ADD COLUMN IF NOT EXISTS jurisdiction VARCHAR(10);
@@ -1,12 +1,29 @@
 -- Drop existing tables if they exist
+DROP TABLE IF EXISTS bonus_rounds;
+DROP TABLE IF EXISTS hold_and_spin_rounds;
+DROP TABLE IF EXISTS free_spin_sessions;
 DROP TABLE IF EXISTS slot_spin_history;
+DROP TABLE IF EXISTS provably_fair_seeds;
 DROP TABLE IF EXISTS jackpot_wins;
+DROP TABLE IF EXISTS progressive_jackpot;
 DROP TABLE IF EXISTS game_sessions;
 DROP TABLE IF EXISTS players;
 DROP TABLE IF EXISTS todos;
 DROP TABLE IF EXISTS slot_reel_symbols;
+DROP TABLE IF EXISTS slot_wild_features;
+DROP TABLE IF EXISTS slot_symbol_payouts;
 DROP TABLE IF EXISTS slot_symbols;
 DROP TABLE IF EXISTS slot_paylines;
+DROP TABLE IF EXISTS slot_scatter_triggers;
+DROP TABLE IF EXISTS slot_bonus_prizes;
+DROP TABLE IF EXISTS slot_bonus_games;
+DROP TABLE IF EXISTS slot_money_values;
+DROP TABLE IF EXISTS slot_hold_and_spin;
+DROP TABLE IF EXISTS slot_ante_bets;
+DROP TABLE IF EXISTS slot_reel_set_weights;
+DROP TABLE IF EXISTS slot_bonus_buys;
+DROP TABLE IF EXISTS slot_jurisdiction_rules;
+DROP TABLE IF EXISTS slot_config_versions CASCADE;
 DROP TABLE IF EXISTS slot_configurations;
 
 -- Basic todos table (keeping for compatibility)
@@ -22,7 +39,8 @@ CREATE TABLE IF NOT EXISTS players (
   email VARCHAR(100) UNIQUE NOT NULL,
   balance BIGINT DEFAULT 1000,
   created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
-  last_login TIMESTAMP WITH TIME ZONE
+  last_login TIMESTAMP WITH TIME ZONE,
+  jurisdiction VARCHAR(10) -- 玩家所在地区代码（大写，例如 GB），由注册或KYC写入；为空时不能加注或购买免费旋转
 );
 
 -- Game sessions table
@@ -50,6 +68,11 @@ CREATE TABLE IF NOT EXISTS slot_configurations (
   max_bet INTEGER DEFAULT 1000,
   wild_enabled BOOLEAN DEFAULT TRUE,
   free_spins_enabled BOOLEAN DEFAULT TRUE,
+  free_spins_multiplier INTEGER NOT NULL DEFAULT 1, -- 免费旋转期间的赢金倍数
+  reel_mode VARCHAR(20) NOT NULL DEFAULT 'weighted' CHECK (reel_mode IN ('weighted', 'stepper')), -- 卷轴抽样方式
+  evaluation_mode VARCHAR(20) NOT NULL DEFAULT 'lines' CHECK (evaluation_mode IN ('lines', 'cluster')), -- 赢奖计算方式
+  cascading BOOLEAN NOT NULL DEFAULT FALSE, -- 连锁消除
+  cascade_multipliers INTEGER[] NOT NULL DEFAULT '{}', -- 每次连锁的赢金倍数
   rtp_percentage DECIMAL(5,2) DEFAULT 96.00,
   created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
   updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
@@ -61,26 +84,31 @@ CREATE TABLE IF NOT EXISTS slot_symbols (
   id serial PRIMARY KEY,
   slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
   name VARCHAR(50) NOT NULL,
-  symbol_type VARCHAR(20) NOT NULL CHECK (symbol_type IN ('normal', 'wild', 'scatter', 'bonus')),
+  symbol_type VARCHAR(20) NOT NULL CHECK (symbol_type IN ('normal', 'wild', 'scatter', 'bonus', 'money')),
   value INTEGER NOT NULL,
   image_url VARCHAR(255),
-  payout_2x INTEGER DEFAULT 0,
-  payout_3x INTEGER DEFAULT 0,
-  payout_4x INTEGER DEFAULT 0,
-  payout_5x INTEGER DEFAULT 0,
-  payout_6x INTEGER DEFAULT 0,
   created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
 );
 
+-- Slot symbol payouts (符号赔付表：连线/路数/簇的符号数量 -> 赔付倍数)
+CREATE TABLE IF NOT EXISTS slot_symbol_payouts (
+  id serial PRIMARY KEY,
+  symbol_id INTEGER REFERENCES slot_symbols(id) ON DELETE CASCADE NOT NULL,
+  symbol_count INTEGER NOT NULL CHECK (symbol_count > 0),
+  payout INTEGER NOT NULL CHECK (payout > 0),
+  UNIQUE(symbol_id, symbol_count)
+);
+
 -- Slot reel symbols table (定义每个卷轴上的符号顺序)
 CREATE TABLE IF NOT EXISTS slot_reel_symbols (
   id serial PRIMARY KEY,
   slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
+  reel_set VARCHAR(50) NOT NULL DEFAULT 'base', -- 卷轴组：base 为基础卷轴，free_spins / ante / bonus_buy 为对应玩法的卷轴，其余为基础游戏的备选卷轴
   reel_number INTEGER NOT NULL,
   position INTEGER NOT NULL,
   symbol_id INTEGER REFERENCES slot_symbols(id) ON DELETE CASCADE,
-  weight INTEGER DEFAULT 1,
-  UNIQUE(slot_config_id, reel_number, position)
+  weight INTEGER DEFAULT 1 CHECK (weight > 0), -- 权重必须为正，全为0的卷轴无法抽样
+  UNIQUE(slot_config_id, reel_set, reel_number, position)
 );
 
 -- Slot paylines table (定义支付线)
@@ -93,6 +121,136 @@ CREATE TABLE IF NOT EXISTS slot_paylines (
   UNIQUE(slot_config_id, line_number)
 );
 
+-- Slot scatter triggers (Scatter数量 -> 免费旋转次数，为空时使用默认触发表)
+CREATE TABLE IF NOT EXISTS slot_scatter_triggers (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
+  scatter_count INTEGER NOT NULL CHECK (scatter_count > 0),
+  free_spins INTEGER NOT NULL CHECK (free_spins >= 0),
+  UNIQUE(slot_config_id, scatter_count)
+);
+
+-- Slot wild features (Wild符号属性，每个Wild符号最多一行)
+CREATE TABLE IF NOT EXISTS slot_wild_features (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
+  symbol_id INTEGER REFERENCES slot_symbols(id) ON DELETE CASCADE UNIQUE,
+  multiplier INTEGER NOT NULL DEFAULT 1 CHECK (multiplier > 0), -- 参与连线时的赢金倍数
+  cannot_substitute INTEGER[] NOT NULL DEFAULT '{}', -- 不能替代的符号ID
+  expanding BOOLEAN NOT NULL DEFAULT false, -- 扩展到整个卷轴
+  sticky BOOLEAN NOT NULL DEFAULT false, -- 免费旋转期间保持原位置
+  walking BOOLEAN NOT NULL DEFAULT false -- 免费旋转期间每次向左移动
+);
+
+-- Slot bonus games (Bonus选宝游戏配置，每个配置最多一个)
+CREATE TABLE IF NOT EXISTS slot_bonus_games (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE UNIQUE,
+  trigger_count INTEGER NOT NULL CHECK (trigger_count > 0), -- 触发所需Bonus符号数量
+  picks INTEGER NOT NULL CHECK (picks > 0), -- 玩家可以选择的次数
+  board_size INTEGER NOT NULL CHECK (board_size > 0) -- 隐藏奖品的数量
+);
+
+-- Slot bonus prizes (选宝奖品表：总投注倍数及抽取权重)
+CREATE TABLE IF NOT EXISTS slot_bonus_prizes (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
+  multiplier INTEGER NOT NULL CHECK (multiplier >= 0),
+  weight INTEGER NOT NULL DEFAULT 1 CHECK (weight > 0)
+);
+
+-- Slot hold and spin (Hold and Spin配置，每个配置最多一个)
+CREATE TABLE IF NOT EXISTS slot_hold_and_spin (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE UNIQUE,
+  trigger_count INTEGER NOT NULL CHECK (trigger_count > 0), -- 触发所需钱币符号数量
+  respins INTEGER NOT NULL CHECK (respins > 0) -- 重转次数，出现新钱币时重置
+);
+
+-- Slot money values (钱币金额表：总投注倍数、奖池标签及抽取权重)
+CREATE TABLE IF NOT EXISTS slot_money_values (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE,
+  multiplier INTEGER NOT NULL CHECK (multiplier >= 0),
+  jackpot VARCHAR(10) CHECK (jackpot IN ('mini', 'minor', 'major', 'grand')),
+  weight INTEGER NOT NULL DEFAULT 1 CHECK (weight > 0)
+);
+
+-- Slot reel set weights (基础游戏每次旋转按权重选择卷轴组，base 为基础卷轴；没有记录时总是使用基础卷轴)
+CREATE TABLE IF NOT EXISTS slot_reel_set_weights (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  reel_set VARCHAR(50) NOT NULL,
+  weight INTEGER NOT NULL CHECK (weight > 0),
+  UNIQUE(slot_config_id, reel_set)
+);
+
+-- Slot ante bets (加注：额外支付总投注的百分比，使用 ante 卷轴组)
+CREATE TABLE IF NOT EXISTS slot_ante_bets (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE UNIQUE,
+  cost_percent INTEGER NOT NULL CHECK (cost_percent > 0)
+);
+
+-- Slot bonus buys (购买免费旋转：支付总投注的倍数，使用 bonus_buy 卷轴组并保证触发)
+CREATE TABLE IF NOT EXISTS slot_bonus_buys (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE UNIQUE,
+  cost_multiplier INTEGER NOT NULL CHECK (cost_multiplier > 0),
+  free_spins INTEGER NOT NULL CHECK (free_spins > 0) -- 至少奖励的免费旋转次数
+);
+
+-- Slot jurisdiction rules (按司法管辖区关闭加注或购买免费旋转，没有记录的地区全部可用)
+-- 属于运营设置，修改后立即生效，不需要重新发布
+CREATE TABLE IF NOT EXISTS slot_jurisdiction_rules (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  jurisdiction VARCHAR(10) NOT NULL, -- 地区代码，统一保存为大写，例如 GB、DE
+  ante_bet_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  bonus_buy_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  UNIQUE(slot_config_id, jurisdiction)
+);
+
+-- Slot config versions (发布时的完整配置快照，发布后不可修改)
+-- slot_symbols / slot_reel_symbols 等表为草稿，线上旋转只使用已发布的版本
+CREATE TABLE IF NOT EXISTS slot_config_versions (
+  id serial PRIMARY KEY,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE RESTRICT NOT NULL, -- 发布过的配置不能删除
+  version_number INTEGER NOT NULL,
+  snapshot JSONB NOT NULL, -- SlotConfigBuilder
+  theoretical_rtp DOUBLE PRECISION, -- 发布时计算的理论RTP
+  notes TEXT,
+  published_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE(slot_config_id, version_number)
+);
+
+CREATE OR REPLACE FUNCTION reject_slot_config_version_update() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'slot_config_versions rows are immutable';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER slot_config_versions_immutable
+  BEFORE UPDATE ON slot_config_versions
+  FOR EACH ROW EXECUTE FUNCTION reject_slot_config_version_update();
+
+-- 当前线上使用的版本，回滚时指向之前的版本
+ALTER TABLE slot_configurations
+  ADD COLUMN live_version_id INTEGER REFERENCES slot_config_versions(id);
+
+-- Provably fair seed pairs (可验证公平：服务器种子承诺/公开)
+CREATE TABLE IF NOT EXISTS provably_fair_seeds (
+  id serial PRIMARY KEY,
+  player_id INTEGER REFERENCES players(id) NOT NULL,
+  server_seed VARCHAR(64) NOT NULL,
+  server_seed_hash VARCHAR(64) NOT NULL,
+  client_seed VARCHAR(64) NOT NULL,
+  nonce INTEGER NOT NULL DEFAULT 0, -- 下一次旋转使用的nonce
+  is_active BOOLEAN NOT NULL DEFAULT TRUE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  revealed_at TIMESTAMP WITH TIME ZONE
+);
+
 -- Slot spin history (更新以支持通用配置)
 CREATE TABLE IF NOT EXISTS slot_spin_history (
   id serial PRIMARY KEY,
@@ -105,9 +263,89 @@ CREATE TABLE IF NOT EXISTS slot_spin_history (
   winning_lines JSONB,
   is_megaway_spin BOOLEAN DEFAULT FALSE,
   megaway_rows JSONB, -- 存储每个卷轴的行数（megaway模式）
+  cascades JSONB, -- 连锁消除的每一步（连锁模式）
+  bet_mode VARCHAR(20) NOT NULL DEFAULT 'standard' CHECK (bet_mode IN ('standard', 'ante', 'bonus_buy')), -- 投注方式，bet_amount 为实际收取的金额
+  active_lines INTEGER, -- 玩家选择的支付线数，NULL 表示全部支付线
+  rng_seed BIGINT, -- 随机数种子，用于重放旋转结果
+  fair_seed_id INTEGER REFERENCES provably_fair_seeds(id), -- 可验证公平旋转使用的种子对
+  fair_nonce INTEGER,
+  is_free_spin BOOLEAN DEFAULT FALSE,
+  free_spin_session_id INTEGER, -- 所属的免费旋转特性
+  bonus_result JSONB, -- 触发的选宝回合结果
+  hold_and_spin_result JSONB, -- 触发的Hold and Spin结果
+  config_version_id INTEGER REFERENCES slot_config_versions(id), -- 旋转使用的配置版本
   spin_time TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
 );
 
+-- Free spin features (免费旋转特性状态，按玩家和配置保存)
+CREATE TABLE IF NOT EXISTS free_spin_sessions (
+  id serial PRIMARY KEY,
+  player_id INTEGER REFERENCES players(id) NOT NULL,
+  session_id INTEGER REFERENCES game_sessions(id),
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  trigger_spin_id INTEGER REFERENCES slot_spin_history(id),
+  bet_per_line INTEGER NOT NULL,
+  active_lines INTEGER, -- 触发时选择的支付线数，免费旋转沿用
+  total_awarded INTEGER NOT NULL,
+  remaining INTEGER NOT NULL,
+  spins_played INTEGER NOT NULL DEFAULT 0,
+  win_multiplier INTEGER NOT NULL DEFAULT 1,
+  total_win BIGINT NOT NULL DEFAULT 0,
+  held_wilds JSONB NOT NULL DEFAULT '[]'::jsonb, -- 保留到下一次旋转的粘性/移动Wild
+  config_version_id INTEGER REFERENCES slot_config_versions(id), -- 触发时的配置版本，整个特性使用同一版本
+  status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  completed_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Bonus pick rounds (选宝回合状态，面板由服务器保存)
+CREATE TABLE IF NOT EXISTS bonus_rounds (
+  id serial PRIMARY KEY,
+  player_id INTEGER REFERENCES players(id) NOT NULL,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  spin_id INTEGER REFERENCES slot_spin_history(id) NOT NULL,
+  total_bet BIGINT NOT NULL,
+  board JSONB NOT NULL,
+  picked JSONB NOT NULL DEFAULT '[]'::jsonb,
+  picks_remaining INTEGER NOT NULL,
+  total_win BIGINT NOT NULL DEFAULT 0,
+  board_seed BIGINT NOT NULL,
+  status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  completed_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Hold and spin rounds (Hold and Spin状态，锁定的钱币由服务器保存)
+CREATE TABLE IF NOT EXISTS hold_and_spin_rounds (
+  id serial PRIMARY KEY,
+  player_id INTEGER REFERENCES players(id) NOT NULL,
+  slot_config_id INTEGER REFERENCES slot_configurations(id) ON DELETE CASCADE NOT NULL,
+  spin_id INTEGER REFERENCES slot_spin_history(id) NOT NULL,
+  config_version_id INTEGER REFERENCES slot_config_versions(id), -- 触发时的配置版本，重转使用同一版本
+  total_bet BIGINT NOT NULL,
+  reel_rows JSONB NOT NULL,
+  coins JSONB NOT NULL,
+  respins_remaining INTEGER NOT NULL,
+  respins_played INTEGER NOT NULL DEFAULT 0,
+  total_win BIGINT NOT NULL DEFAULT 0,
+  seed BIGINT, -- 触发旋转的种子，第n次重转使用 seed + n；可验证公平旋转为 NULL，重转使用该次 nonce 的 HMAC 随机数流
+  status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  completed_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Progressive jackpot (经典老虎机的累积奖池，只有一行，与旋转在同一事务中加锁更新)
+CREATE TABLE IF NOT EXISTS progressive_jackpot (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  current_amount BIGINT NOT NULL,
+  min_amount BIGINT NOT NULL, -- 中奖后奖池重置为该金额
+  contribution_rate DOUBLE PRECISION NOT NULL, -- 每次投注计入奖池的比例
+  last_won TIMESTAMP WITH TIME ZONE
+);
+
+INSERT INTO progressive_jackpot (id, current_amount, min_amount, contribution_rate)
+VALUES (1, 10000, 10000, 0.02);
+
 -- Jackpot wins history
 CREATE TABLE IF NOT EXISTS jackpot_wins (
   id serial PRIMARY KEY,
@@ -121,6 +359,13 @@ CREATE INDEX idx_players_username ON players(username);
 CREATE INDEX idx_sessions_player_id ON game_sessions(player_id);
 CREATE INDEX idx_spins_player_id ON slot_spin_history(player_id);
 CREATE INDEX idx_spins_session_id ON slot_spin_history(session_id);
+CREATE INDEX idx_free_spins_player ON free_spin_sessions(player_id, slot_config_id, status);
+CREATE INDEX idx_bonus_rounds_player ON bonus_rounds(player_id, slot_config_id, status);
+CREATE INDEX idx_slot_bonus_prizes_config ON slot_bonus_prizes(slot_config_id);
+CREATE INDEX idx_hold_and_spin_rounds_player ON hold_and_spin_rounds(player_id, slot_config_id, status);
+CREATE INDEX idx_slot_money_values_config ON slot_money_values(slot_config_id);
+CREATE INDEX idx_spins_config_version ON slot_spin_history(config_version_id);
+CREATE UNIQUE INDEX idx_fair_seeds_active ON provably_fair_seeds(player_id) WHERE is_active;
 CREATE INDEX idx_slot_configs_active ON slot_configurations(is_active);
 CREATE INDEX idx_slot_symbols_config ON slot_symbols(slot_config_id);
 CREATE INDEX idx_slot_reels_config ON slot_reel_symbols(slot_config_id);
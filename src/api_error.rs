@@ -0,0 +1,91 @@
+use crate::universal_slots::BetError;
+use crate::wallet::WalletError;
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+
+// API处理函数的统一错误类型，响应体为 {"error": "...", "code": "..."}，code 供客户端区分错误
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Forbidden(String),
+    Conflict(String),
+    PaymentRequired(String),
+    Unprocessable(String),
+    InvalidBet(BetError),
+    Internal(String),
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ApiError::BadRequest(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::Forbidden(msg)
+            | ApiError::Conflict(msg)
+            | ApiError::PaymentRequired(msg)
+            | ApiError::Unprocessable(msg)
+            | ApiError::Internal(msg) => write!(f, "{msg}"),
+            ApiError::InvalidBet(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::PaymentRequired(_) => StatusCode::PAYMENT_REQUIRED,
+            ApiError::Unprocessable(_) | ApiError::InvalidBet(_) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(serde_json::json!({
+            "error": self.to_string(),
+            "code": self.code()
+        }))
+    }
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::PaymentRequired(_) => "insufficient_balance",
+            ApiError::Unprocessable(_) => "unprocessable",
+            ApiError::InvalidBet(e) => e.code(),
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        ApiError::Internal(format!("Database error: {e}"))
+    }
+}
+
+impl From<WalletError> for ApiError {
+    fn from(e: WalletError) -> Self {
+        match e {
+            WalletError::PlayerNotFound => ApiError::NotFound(e.to_string()),
+            WalletError::InsufficientBalance => ApiError::PaymentRequired(e.to_string()),
+            WalletError::Database(e) => e.into(),
+        }
+    }
+}
+
+impl From<BetError> for ApiError {
+    fn from(e: BetError) -> Self {
+        ApiError::InvalidBet(e)
+    }
+}
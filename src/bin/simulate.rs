@@ -0,0 +1,95 @@
+// 独立的蒙特卡洛模拟工具，从JSON或YAML文件读取slot配置（SlotConfigBuilder格式，
+// 即 /api/slot-config/{id}/export 导出的游戏定义）
+//
+// 用法: simulate <config.json|config.yaml> [--spins N] [--threads N] [--seed N]
+//             [--bet-mode standard|ante|bonus_buy]
+use saber::simulation::{simulate, SimulationControl, SimulationParams};
+use saber::universal_slots::{BetMode, SlotConfigBuilder};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: simulate <config.json|config.yaml> [--spins N] [--threads N] [--seed N] [--bet-mode standard|ante|bonus_buy]";
+
+fn parse_args() -> Result<(String, SimulationParams), String> {
+    let mut path = None;
+    let mut params = SimulationParams {
+        spins: 10_000_000,
+        threads: None,
+        seed: None,
+        bet_mode: BetMode::Standard,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .and_then(|v| v.replace('_', "").parse::<u64>().ok())
+                .ok_or_else(|| format!("{name} expects a number"))
+        };
+        match arg.as_str() {
+            "--spins" => params.spins = value("--spins")?,
+            "--threads" => params.threads = Some(value("--threads")? as usize),
+            "--seed" => params.seed = Some(value("--seed")?),
+            "--bet-mode" => {
+                params.bet_mode = args
+                    .next()
+                    .and_then(|mode| serde_json::from_value(serde_json::Value::String(mode)).ok())
+                    .ok_or("--bet-mode expects standard, ante or bonus_buy")?
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+            _ => return Err(format!("unexpected argument '{arg}'\n{USAGE}")),
+        }
+    }
+
+    Ok((path.ok_or(USAGE)?, params))
+}
+
+fn main() -> ExitCode {
+    let (path, params) = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let is_yaml = path.ends_with(".yaml") || path.ends_with(".yml");
+    let builder: SlotConfigBuilder = match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| {
+            if is_yaml {
+                // 经过 serde_json::Value 转换，卷轴编号写成字符串或整数都能解析
+                serde_yaml::from_str::<serde_json::Value>(&text)
+                    .map_err(|e| e.to_string())
+                    .and_then(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+            } else {
+                serde_json::from_str(&text).map_err(|e| e.to_string())
+            }
+        }) {
+        Ok(builder) => builder,
+        Err(e) => {
+            eprintln!("failed to load {path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let machine = builder.build();
+    eprintln!(
+        "simulating {} spins of '{}'...",
+        params.spins, machine.config.name
+    );
+
+    match simulate(&machine, &params, &SimulationControl::default()) {
+        Ok(report) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("report serializes")
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("simulation failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
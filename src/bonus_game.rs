@@ -0,0 +1,393 @@
+use crate::api_error::ApiError;
+use crate::universal_slots::UniversalSlotMachine;
+use crate::wallet;
+use actix_web::{web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Pool, Postgres};
+
+// 选宝回合的状态：面板由服务器保存，玩家每次揭开一个位置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BonusRoundState {
+    pub board: Vec<u32>,
+    pub picked: Vec<usize>,
+    pub picks_remaining: u32,
+}
+
+impl BonusRoundState {
+    pub fn new(board: Vec<u32>, picks: u32) -> Self {
+        let picks_remaining = picks.min(board.len() as u32);
+        Self {
+            board,
+            picked: Vec::new(),
+            picks_remaining,
+        }
+    }
+
+    // 揭开一个位置，返回该位置的奖品倍数
+    pub fn pick(&mut self, index: usize) -> Result<u32, String> {
+        if self.is_complete() {
+            return Err("No picks remaining".to_string());
+        }
+        if index >= self.board.len() {
+            return Err(format!(
+                "Pick index {} is outside the board (size {})",
+                index,
+                self.board.len()
+            ));
+        }
+        if self.picked.contains(&index) {
+            return Err(format!("Position {index} has already been picked"));
+        }
+
+        self.picked.push(index);
+        self.picks_remaining -= 1;
+        Ok(self.board[index])
+    }
+
+    pub fn total_multiplier(&self) -> u32 {
+        self.picked.iter().map(|&i| self.board[i]).sum()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.picks_remaining == 0
+    }
+
+    // 返回给客户端的面板：未揭开的位置为 null，回合结束后全部公开
+    pub fn visible_board(&self) -> Vec<Option<u32>> {
+        self.board
+            .iter()
+            .enumerate()
+            .map(|(i, &prize)| {
+                if self.is_complete() || self.picked.contains(&i) {
+                    Some(prize)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BonusRound {
+    pub id: i32,
+    pub slot_config_id: i32,
+    pub spin_id: i32,
+    pub total_bet: i64,
+    pub board: Vec<Option<u32>>,
+    pub picked: Vec<usize>,
+    pub picks_remaining: u32,
+    pub total_win: i64,
+    pub completed: bool,
+}
+
+impl BonusRound {
+    fn from_state(
+        id: i32,
+        slot_config_id: i32,
+        spin_id: i32,
+        total_bet: i64,
+        state: &BonusRoundState,
+    ) -> Self {
+        Self {
+            id,
+            slot_config_id,
+            spin_id,
+            total_bet,
+            board: state.visible_board(),
+            picked: state.picked.clone(),
+            picks_remaining: state.picks_remaining,
+            total_win: state.total_multiplier() as i64 * total_bet,
+            completed: state.is_complete(),
+        }
+    }
+}
+
+// 查询玩家在某个配置上进行中的选宝回合
+pub async fn has_active_round(
+    conn: &mut PgConnection,
+    player_id: i32,
+    slot_config_id: i32,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM bonus_rounds
+        WHERE player_id = $1 AND slot_config_id = $2 AND status = 'active'
+        "#,
+        player_id,
+        slot_config_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+// 旋转触发Bonus后创建选宝回合，面板由独立的服务器种子生成并随回合保存；
+// 该种子不会返回给客户端，回合结束前无法由旋转结果推算面板
+pub async fn start_round(
+    conn: &mut PgConnection,
+    player_id: i32,
+    machine: &UniversalSlotMachine,
+    spin_id: i32,
+    total_bet: i64,
+    board_seed: u64,
+) -> Result<Option<BonusRound>, sqlx::Error> {
+    let Some(bonus_game) = &machine.bonus_game else {
+        return Ok(None);
+    };
+
+    let state = BonusRoundState::new(bonus_game.generate_board(board_seed), bonus_game.picks);
+
+    let rec = sqlx::query!(
+        r#"
+        INSERT INTO bonus_rounds
+        (player_id, slot_config_id, spin_id, total_bet, board, picked, picks_remaining, board_seed)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id
+        "#,
+        player_id,
+        machine.config.id,
+        spin_id,
+        total_bet,
+        serde_json::to_value(&state.board).unwrap_or_default(),
+        serde_json::to_value(&state.picked).unwrap_or_default(),
+        state.picks_remaining as i32,
+        board_seed as i64
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(Some(BonusRound::from_state(
+        rec.id,
+        machine.config.id,
+        spin_id,
+        total_bet,
+        &state,
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BonusPickRequest {
+    pub player_id: i32,
+    pub round_id: i32,
+    pub index: usize,
+}
+
+// 提交一次选择：奖品按总投注的倍数计入余额，并累计到触发旋转的记录中
+pub async fn pick(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<BonusPickRequest>,
+) -> Result<HttpResponse> {
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+
+    let round = sqlx::query!(
+        r#"
+        SELECT id, slot_config_id, spin_id, total_bet, board, picked, picks_remaining
+        FROM bonus_rounds
+        WHERE id = $1 AND player_id = $2 AND status = 'active'
+        FOR UPDATE
+        "#,
+        req.round_id,
+        req.player_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| ApiError::NotFound("Active bonus round not found".to_string()))?;
+
+    let mut state = BonusRoundState {
+        board: serde_json::from_value(round.board)
+            .map_err(|e| ApiError::Internal(format!("Corrupt bonus board: {e}")))?,
+        picked: serde_json::from_value(round.picked)
+            .map_err(|e| ApiError::Internal(format!("Corrupt bonus picks: {e}")))?,
+        picks_remaining: round.picks_remaining as u32,
+    };
+
+    let multiplier = state.pick(req.index).map_err(ApiError::BadRequest)?;
+    let prize = multiplier as i64 * round.total_bet;
+    let bonus_round = BonusRound::from_state(
+        round.id,
+        round.slot_config_id,
+        round.spin_id,
+        round.total_bet,
+        &state,
+    );
+
+    sqlx::query!(
+        r#"
+        UPDATE bonus_rounds
+        SET picked = $2, picks_remaining = $3, total_win = $4,
+            status = CASE WHEN $3 = 0 THEN 'completed' ELSE 'active' END,
+            completed_at = CASE WHEN $3 = 0 THEN CURRENT_TIMESTAMP ELSE NULL END
+        WHERE id = $1
+        "#,
+        round.id,
+        serde_json::to_value(&state.picked).unwrap_or_default(),
+        state.picks_remaining as i32,
+        bonus_round.total_win
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    // 奖品计入触发旋转的结果
+    sqlx::query!(
+        r#"
+        UPDATE slot_spin_history
+        SET win_amount = win_amount + $2, bonus_result = $3
+        WHERE id = $1
+        "#,
+        round.spin_id,
+        prize,
+        serde_json::to_value(&bonus_round).unwrap_or_default()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    let balance = wallet::settle(&mut tx, req.player_id, 0, prize)
+        .await
+        .map_err(ApiError::from)?;
+
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "round": bonus_round,
+        "prize": prize,
+        "balance": balance
+    })))
+}
+
+// 已结束的选宝回合的完整面板，用于重放；回合进行中返回 None
+pub async fn completed_board(
+    pool: &Pool<Postgres>,
+    spin_id: i32,
+) -> Result<Option<Vec<u32>>, ApiError> {
+    let board = sqlx::query_scalar!(
+        "SELECT board FROM bonus_rounds WHERE spin_id = $1 AND status = 'completed'",
+        spin_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    board
+        .map(|board| {
+            serde_json::from_value(board)
+                .map_err(|e| ApiError::Internal(format!("Corrupt bonus board: {e}")))
+        })
+        .transpose()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BonusRoundQuery {
+    pub player_id: i32,
+}
+
+// 查询选宝回合状态，只能查询自己的回合
+pub async fn get_round(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    query: web::Query<BonusRoundQuery>,
+) -> Result<HttpResponse> {
+    let round_id = path.into_inner();
+
+    let round = sqlx::query!(
+        r#"
+        SELECT id, slot_config_id, spin_id, total_bet, board, picked, picks_remaining
+        FROM bonus_rounds WHERE id = $1 AND player_id = $2
+        "#,
+        round_id,
+        query.player_id
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| ApiError::NotFound("Bonus round not found".to_string()))?;
+
+    let state = BonusRoundState {
+        board: serde_json::from_value(round.board).unwrap_or_default(),
+        picked: serde_json::from_value(round.picked).unwrap_or_default(),
+        picks_remaining: round.picks_remaining as u32,
+    };
+
+    Ok(HttpResponse::Ok().json(BonusRound::from_state(
+        round.id,
+        round.slot_config_id,
+        round.spin_id,
+        round.total_bet,
+        &state,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bonus_round_picks_until_complete() {
+        let mut state = BonusRoundState::new(vec![5, 10, 0, 20], 2);
+
+        assert_eq!(state.pick(1), Ok(10));
+        assert!(state.pick(1).is_err());
+        assert!(state.pick(9).is_err());
+        assert_eq!(state.visible_board(), vec![None, Some(10), None, None]);
+
+        assert_eq!(state.pick(3), Ok(20));
+        assert!(state.is_complete());
+        assert_eq!(state.total_multiplier(), 30);
+        assert!(state.pick(0).is_err());
+        assert_eq!(
+            state.visible_board(),
+            vec![Some(5), Some(10), Some(0), Some(20)]
+        );
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    async fn test_round_is_private_and_board_hidden_until_complete(pool: Pool<Postgres>) {
+        sqlx::query(
+            "INSERT INTO slot_spin_history (id, player_id, slot_config_id, bet_amount, win_amount, symbols)
+             VALUES (1, 1, 1, 10, 0, '[]')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO bonus_rounds (id, player_id, slot_config_id, spin_id, total_bet, board, picked, picks_remaining, board_seed)
+             VALUES (1, 1, 1, 1, 10, '[5, 10, 0]', '[]', 1, 42)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let get = |player_id| {
+            get_round(
+                web::Data::new(pool.clone()),
+                web::Path::from(1),
+                web::Query(BonusRoundQuery { player_id }),
+            )
+        };
+        let err = get(2).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::NOT_FOUND
+        );
+        let resp = get(1).await.unwrap();
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let round: BonusRound = serde_json::from_slice(&body).unwrap();
+        assert_eq!(round.board, vec![None, None, None]);
+
+        // 重放只在回合结束后返回面板
+        assert_eq!(completed_board(&pool, 1).await.unwrap(), None);
+        sqlx::query(
+            "UPDATE bonus_rounds SET picked = '[1]', picks_remaining = 0, status = 'completed'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            completed_board(&pool, 1).await.unwrap(),
+            Some(vec![5, 10, 0])
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use crate::api_error::ApiError;
+use crate::slot_config_api::{build_playable_machine, load_slot_config_builder};
+use crate::slot_math;
+use crate::universal_slots::{SlotConfigBuilder, UniversalSlotMachine};
+use actix_web::{web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+fn machine_from_snapshot(snapshot: serde_json::Value) -> Result<UniversalSlotMachine, ApiError> {
+    let builder: SlotConfigBuilder = serde_json::from_value(snapshot)
+        .map_err(|e| ApiError::Internal(format!("Corrupt config snapshot: {e}")))?;
+    Ok(builder.build())
+}
+
+// 线上旋转使用的机器：当前发布的版本，返回 (机器, 版本ID)
+pub async fn load_live_machine(
+    pool: &Pool<Postgres>,
+    config_id: i32,
+) -> Result<(UniversalSlotMachine, i32), ApiError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT c.live_version_id, c.is_active, v.snapshot as "snapshot?"
+        FROM slot_configurations c
+        LEFT JOIN slot_config_versions v ON v.id = c.live_version_id
+        WHERE c.id = $1
+        "#,
+        config_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| ApiError::NotFound(format!("Slot config {config_id} not found")))?;
+
+    if row.is_active == Some(false) {
+        return Err(ApiError::Conflict(format!(
+            "Slot config {config_id} is inactive"
+        )));
+    }
+
+    match (row.live_version_id, row.snapshot) {
+        (Some(version_id), Some(snapshot)) => Ok((machine_from_snapshot(snapshot)?, version_id)),
+        _ => Err(ApiError::Conflict(format!(
+            "Slot config {config_id} has no published version, publish it via /api/slot-config/{config_id}/publish"
+        ))),
+    }
+}
+
+// 指定版本的机器，版本必须属于该配置
+pub async fn load_version_machine(
+    pool: &Pool<Postgres>,
+    config_id: i32,
+    version_id: i32,
+) -> Result<UniversalSlotMachine, ApiError> {
+    let snapshot = sqlx::query_scalar!(
+        "SELECT snapshot FROM slot_config_versions WHERE id = $1 AND slot_config_id = $2",
+        version_id,
+        config_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        ApiError::NotFound(format!(
+            "Version {version_id} of slot config {config_id} not found"
+        ))
+    })?;
+
+    machine_from_snapshot(snapshot)
+}
+
+// 重放/校验历史旋转：优先使用记录的版本，未指定时使用线上版本，未发布过的配置使用通过校验的草稿
+pub async fn load_machine_at(
+    pool: &Pool<Postgres>,
+    config_id: i32,
+    version_id: Option<i32>,
+) -> Result<UniversalSlotMachine, ApiError> {
+    if let Some(version_id) = version_id {
+        return load_version_machine(pool, config_id, version_id).await;
+    }
+    match load_live_machine(pool, config_id).await {
+        Ok((machine, _)) => Ok(machine),
+        Err(ApiError::Conflict(_)) => build_playable_machine(pool, config_id).await,
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PublishRequest {
+    pub notes: Option<String>,
+}
+
+// 发布草稿：校验通过后把完整配置保存为新的不可修改版本，并设为线上版本
+pub async fn publish(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    req: Option<web::Json<PublishRequest>>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+    let notes = req.and_then(|req| req.into_inner().notes);
+
+    let builder = load_slot_config_builder(pool.get_ref(), config_id).await?;
+    let report = builder.validate();
+    if !report.valid {
+        return Err(ApiError::Unprocessable(format!(
+            "Slot config {} is invalid: {}",
+            config_id,
+            report.error_summary()
+        ))
+        .into());
+    }
+
+    let snapshot = serde_json::to_value(&builder)
+        .map_err(|e| ApiError::Internal(format!("Failed to snapshot config: {e}")))?;
+    // 理论RTP随版本保存，无法计算时（例如免费旋转永不结束）留空
+    let machine = builder.build();
+    let theoretical_rtp = web::block(move || slot_math::analyze(&machine))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok()
+        .map(|report| report.rtp);
+
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+    // 锁定配置行，保证版本号连续
+    sqlx::query!(
+        "SELECT id FROM slot_configurations WHERE id = $1 FOR UPDATE",
+        config_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    let version = sqlx::query!(
+        r#"
+        INSERT INTO slot_config_versions
+        (slot_config_id, version_number, snapshot, theoretical_rtp, notes)
+        SELECT $1, COALESCE(MAX(version_number), 0) + 1, $2, $3, $4
+        FROM slot_config_versions WHERE slot_config_id = $1
+        RETURNING id, version_number, published_at
+        "#,
+        config_id,
+        snapshot,
+        theoretical_rtp,
+        notes
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    sqlx::query!(
+        r#"
+        UPDATE slot_configurations
+        SET live_version_id = $2, updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        "#,
+        config_id,
+        version.id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Slot config published successfully",
+        "version_id": version.id,
+        "version_number": version.version_number,
+        "theoretical_rtp": theoretical_rtp,
+        "published_at": version.published_at.map(|t| t.to_string()),
+        "warnings": report.warnings
+    })))
+}
+
+// 列出配置的所有已发布版本
+pub async fn list_versions(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let versions = sqlx::query!(
+        r#"
+        SELECT v.id, v.version_number, v.theoretical_rtp, v.notes, v.published_at,
+               c.live_version_id = v.id as "is_live!"
+        FROM slot_config_versions v
+        JOIN slot_configurations c ON c.id = v.slot_config_id
+        WHERE v.slot_config_id = $1
+        ORDER BY v.version_number DESC
+        "#,
+        config_id
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match versions {
+        Ok(versions) => {
+            let serializable_versions: Vec<serde_json::Value> = versions
+                .into_iter()
+                .map(|version| {
+                    serde_json::json!({
+                        "id": version.id,
+                        "version_number": version.version_number,
+                        "theoretical_rtp": version.theoretical_rtp,
+                        "notes": version.notes,
+                        "published_at": version.published_at.map(|t| t.to_string()),
+                        "is_live": version.is_live
+                    })
+                })
+                .collect();
+            Ok(HttpResponse::Ok().json(serializable_versions))
+        }
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to fetch versions: {}", e)
+        }))),
+    }
+}
+
+// 查询某个版本的完整快照
+pub async fn get_version(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    let (config_id, version_number) = path.into_inner();
+
+    let version = sqlx::query!(
+        r#"
+        SELECT v.id, v.version_number, v.snapshot, v.theoretical_rtp, v.notes, v.published_at,
+               c.live_version_id = v.id as "is_live!"
+        FROM slot_config_versions v
+        JOIN slot_configurations c ON c.id = v.slot_config_id
+        WHERE v.slot_config_id = $1 AND v.version_number = $2
+        "#,
+        config_id,
+        version_number
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| {
+        ApiError::NotFound(format!(
+            "Version {version_number} of slot config {config_id} not found"
+        ))
+    })?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": version.id,
+        "version_number": version.version_number,
+        "theoretical_rtp": version.theoretical_rtp,
+        "notes": version.notes,
+        "published_at": version.published_at.map(|t| t.to_string()),
+        "is_live": version.is_live,
+        "snapshot": version.snapshot
+    })))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RollbackRequest {
+    pub version_number: i32,
+}
+
+// 回滚：把线上版本切换到之前发布的版本，草稿和版本记录都不变
+pub async fn rollback(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    req: web::Json<RollbackRequest>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+
+    let live_version_id = sqlx::query_scalar!(
+        "SELECT live_version_id FROM slot_configurations WHERE id = $1 FOR UPDATE",
+        config_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| ApiError::NotFound(format!("Slot config {config_id} not found")))?;
+
+    let version_id = sqlx::query_scalar!(
+        "SELECT id FROM slot_config_versions WHERE slot_config_id = $1 AND version_number = $2",
+        config_id,
+        req.version_number
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| {
+        ApiError::NotFound(format!(
+            "Version {} of slot config {} not found",
+            req.version_number, config_id
+        ))
+    })?;
+
+    if live_version_id == Some(version_id) {
+        return Err(
+            ApiError::Conflict(format!("Version {} is already live", req.version_number)).into(),
+        );
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE slot_configurations
+        SET live_version_id = $2, updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        "#,
+        config_id,
+        version_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Slot config rolled back successfully",
+        "version_id": version_id,
+        "version_number": req.version_number
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slot_config_api::delete_slot_config;
+    use actix_web::http::StatusCode;
+
+    async fn publish_version(pool: &Pool<Postgres>) -> i64 {
+        let resp = publish(web::Data::new(pool.clone()), web::Path::from(1), None)
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        json["version_number"].as_i64().unwrap()
+    }
+
+    async fn live_bell_payout(pool: &Pool<Postgres>) -> Option<u32> {
+        let (machine, _) = load_live_machine(pool, 1).await.unwrap();
+        let bell = machine.symbols.iter().find(|s| s.name == "Bell").unwrap();
+        bell.payouts.get(&5).copied()
+    }
+
+    #[sqlx::test(fixtures("playable"))]
+    async fn test_publish_snapshot_round_trips_and_rollback_restores_it(pool: Pool<Postgres>) {
+        let draft = load_slot_config_builder(&pool, 1).await.unwrap();
+        assert_eq!(publish_version(&pool).await, 1);
+
+        // 快照与发布时的草稿一致，反序列化后不丢失任何内容
+        let snapshot: serde_json::Value = sqlx::query_scalar(
+            "SELECT snapshot FROM slot_config_versions WHERE slot_config_id = 1 AND version_number = 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let draft_json = serde_json::to_value(&draft).unwrap();
+        assert_eq!(snapshot, draft_json);
+        let restored: SlotConfigBuilder = serde_json::from_value(snapshot).unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), draft_json);
+
+        // 修改草稿不影响线上版本，重新发布后才生效
+        sqlx::query(
+            "UPDATE slot_symbol_payouts SET payout = 100 WHERE symbol_id = 2 AND symbol_count = 5",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(live_bell_payout(&pool).await, Some(50));
+        assert_eq!(publish_version(&pool).await, 2);
+        assert_eq!(live_bell_payout(&pool).await, Some(100));
+
+        // 回滚只切换线上版本，草稿保持不变
+        let rollback_to = |version_number| {
+            rollback(
+                web::Data::new(pool.clone()),
+                web::Path::from(1),
+                web::Json(RollbackRequest { version_number }),
+            )
+        };
+        assert_eq!(rollback_to(1).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(live_bell_payout(&pool).await, Some(50));
+        let err = rollback_to(1).await.unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), StatusCode::CONFLICT);
+        let draft = load_slot_config_builder(&pool, 1).await.unwrap();
+        let bell = draft.symbols.iter().find(|s| s.name == "Bell").unwrap();
+        assert_eq!(bell.payouts.get(&5), Some(&100));
+
+        // 版本不能修改
+        let updated = sqlx::query("UPDATE slot_config_versions SET notes = 'edited'")
+            .execute(&pool)
+            .await;
+        assert!(updated.is_err());
+    }
+
+    #[sqlx::test(fixtures("playable"))]
+    async fn test_published_config_cannot_be_deleted(pool: Pool<Postgres>) {
+        publish_version(&pool).await;
+
+        let err = delete_slot_config(web::Data::new(pool.clone()), web::Path::from(1))
+            .await
+            .unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), StatusCode::CONFLICT);
+
+        // 绕过接口直接删除同样被外键拒绝
+        let deleted = sqlx::query("DELETE FROM slot_configurations WHERE id = 1")
+            .execute(&pool)
+            .await;
+        assert!(deleted.is_err());
+        let versions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM slot_config_versions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(versions, 1);
+    }
+}
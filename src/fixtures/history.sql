@@ -0,0 +1,3 @@
+INSERT INTO players (id, username, email) VALUES (1, 'alice', 'alice@example.com'), (2, 'bob', 'bob@example.com');
+INSERT INTO game_sessions (id, player_id) VALUES (1, 1);
+INSERT INTO slot_configurations (id, name) VALUES (1, 'First'), (2, 'Second');
@@ -0,0 +1,13 @@
+-- 可以发布的最小配置：2个符号、5个卷轴、1条支付线
+INSERT INTO slot_configurations (id, name, wild_enabled, free_spins_enabled) VALUES (1, 'Classic', false, false);
+INSERT INTO slot_symbols (id, slot_config_id, name, symbol_type, value) VALUES
+  (1, 1, 'Cherry', 'normal', 1),
+  (2, 1, 'Bell', 'normal', 2);
+INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout) VALUES
+  (1, 3, 5), (1, 4, 10), (1, 5, 20),
+  (2, 3, 10), (2, 4, 20), (2, 5, 50);
+INSERT INTO slot_reel_symbols (slot_config_id, reel_number, position, symbol_id, weight)
+SELECT 1, reel, position, position + 1, 3 - position
+FROM generate_series(0, 4) AS reel, generate_series(0, 1) AS position;
+INSERT INTO slot_paylines (slot_config_id, line_number, pattern) VALUES
+  (1, 1, '[[0, 1], [1, 1], [2, 1], [3, 1], [4, 1]]');
@@ -0,0 +1,356 @@
+use crate::api_error::ApiError;
+use crate::config_versions::{load_live_machine, load_version_machine};
+use crate::slot_config_api::SpinResponse;
+use crate::spin_history::{record_spin, SpinRecord};
+use crate::universal_slots::{HeldWild, UniversalSlotMachine, UniversalSpinResult};
+use crate::wallet;
+use actix_web::{web, HttpResponse, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Pool, Postgres};
+
+// 免费旋转特性的进度
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FreeSpinState {
+    pub total_awarded: u32,
+    pub remaining: u32,
+    pub spins_played: u32,
+    pub win_multiplier: u32,
+    pub total_win: u64,
+    #[serde(default)]
+    pub held_wilds: Vec<HeldWild>, // 保留到下一次免费旋转的粘性/移动Wild
+}
+
+impl FreeSpinState {
+    pub fn new(awarded: u32, win_multiplier: u32) -> Self {
+        Self {
+            total_awarded: awarded,
+            remaining: awarded,
+            spins_played: 0,
+            win_multiplier: win_multiplier.max(1),
+            total_win: 0,
+            held_wilds: Vec::new(),
+        }
+    }
+
+    // 结算一次免费旋转：应用赢金倍数、累计赢金，并处理再次触发
+    // （免费旋转不会触发选宝和Hold and Spin，见 UniversalSlotMachine::spin_free_seeded）
+    pub fn play(&mut self, result: &mut UniversalSpinResult) {
+        result.total_win = result.total_win.saturating_mul(self.win_multiplier);
+        self.total_win = self.total_win.saturating_add(result.total_win as u64);
+        self.remaining = self.remaining.saturating_sub(1);
+        self.spins_played += 1;
+        self.held_wilds = result.held_wilds.clone();
+
+        if result.free_spins > 0 {
+            self.remaining = self.remaining.saturating_add(result.free_spins);
+            self.total_awarded = self.total_awarded.saturating_add(result.free_spins);
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreeSpinFeature {
+    pub id: i32,
+    pub slot_config_id: i32,
+    pub session_id: Option<i32>, // 触发时的游戏会话，免费旋转记录到同一会话
+    pub bet_per_line: u32,
+    pub active_lines: Option<u32>, // 触发时选择的支付线数，免费旋转沿用
+    pub config_version_id: Option<i32>, // 免费旋转全程使用触发时的配置版本
+    #[serde(flatten)]
+    pub state: FreeSpinState,
+    pub completed: bool,
+}
+
+// 查询玩家在某个配置上进行中的免费旋转（加行锁）
+pub async fn active_feature(
+    conn: &mut PgConnection,
+    player_id: i32,
+    slot_config_id: i32,
+) -> Result<Option<FreeSpinFeature>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT id, slot_config_id, session_id, bet_per_line, active_lines, total_awarded,
+               remaining, spins_played, win_multiplier, total_win, held_wilds, config_version_id
+        FROM free_spin_sessions
+        WHERE player_id = $1 AND slot_config_id = $2 AND status = 'active'
+        FOR UPDATE
+        "#,
+        player_id,
+        slot_config_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.map(|row| FreeSpinFeature {
+        id: row.id,
+        slot_config_id: row.slot_config_id,
+        session_id: row.session_id,
+        bet_per_line: row.bet_per_line as u32,
+        active_lines: row.active_lines.map(|lines| lines as u32),
+        config_version_id: row.config_version_id,
+        state: FreeSpinState {
+            total_awarded: row.total_awarded as u32,
+            remaining: row.remaining as u32,
+            spins_played: row.spins_played as u32,
+            win_multiplier: row.win_multiplier as u32,
+            total_win: row.total_win as u64,
+            held_wilds: serde_json::from_value(row.held_wilds).unwrap_or_default(),
+        },
+        completed: false,
+    }))
+}
+
+// 基础游戏触发免费旋转时保存奖励，之后以触发时的投注和支付线数进行
+#[allow(clippy::too_many_arguments)]
+pub async fn award(
+    conn: &mut PgConnection,
+    player_id: i32,
+    session_id: Option<i32>,
+    machine: &UniversalSlotMachine,
+    config_version_id: Option<i32>,
+    trigger_spin_id: i32,
+    bet_per_line: u32,
+    active_lines: Option<u32>,
+    free_spins: u32,
+) -> Result<FreeSpinFeature, sqlx::Error> {
+    let state = FreeSpinState::new(free_spins, machine.config.free_spins_multiplier);
+
+    let rec = sqlx::query!(
+        r#"
+        INSERT INTO free_spin_sessions
+        (player_id, session_id, slot_config_id, trigger_spin_id, bet_per_line, active_lines,
+         total_awarded, remaining, win_multiplier, config_version_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id
+        "#,
+        player_id,
+        session_id,
+        machine.config.id,
+        trigger_spin_id,
+        bet_per_line as i32,
+        active_lines.map(|lines| lines as i32),
+        state.total_awarded as i32,
+        state.remaining as i32,
+        state.win_multiplier as i32,
+        config_version_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(FreeSpinFeature {
+        id: rec.id,
+        slot_config_id: machine.config.id,
+        session_id,
+        bet_per_line,
+        active_lines,
+        config_version_id,
+        state,
+        completed: false,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FreeSpinRequest {
+    pub player_id: i32,
+    pub slot_config_id: i32,
+}
+
+// 执行一次免费旋转：不扣除余额，赢金直接计入余额
+pub async fn play_free_spin(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<FreeSpinRequest>,
+) -> Result<HttpResponse> {
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+
+    let mut feature = active_feature(&mut tx, req.player_id, req.slot_config_id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::NotFound("No free spins available".to_string()))?;
+
+    // 发布新版本或回滚不影响进行中的免费旋转
+    let machine = match feature.config_version_id {
+        Some(version_id) => {
+            load_version_machine(pool.get_ref(), req.slot_config_id, version_id).await?
+        }
+        None => {
+            load_live_machine(pool.get_ref(), req.slot_config_id)
+                .await?
+                .0
+        }
+    };
+
+    let machine = machine.with_active_lines(feature.active_lines);
+    let seed = rand::rng().random();
+    let mut result =
+        machine.spin_free_seeded(feature.bet_per_line, seed, &feature.state.held_wilds);
+    feature.state.play(&mut result);
+    feature.completed = feature.state.is_complete();
+
+    let mut record =
+        SpinRecord::universal(&machine, &result, Some(req.player_id), feature.session_id);
+    record.bet_amount = 0;
+    record.is_free_spin = true;
+    record.free_spin_session_id = Some(feature.id);
+    record.config_version_id = feature.config_version_id;
+    record.active_lines = feature.active_lines.map(|lines| lines as i32);
+    let spin_id = record_spin(&mut tx, &record)
+        .await
+        .map_err(ApiError::from)?;
+
+    let balance = wallet::settle(&mut tx, req.player_id, 0, result.total_win as i64)
+        .await
+        .map_err(ApiError::from)?;
+
+    sqlx::query!(
+        r#"
+        UPDATE free_spin_sessions
+        SET total_awarded = $2, remaining = $3, spins_played = $4, total_win = $5,
+            held_wilds = $6,
+            status = CASE WHEN $3 = 0 THEN 'completed' ELSE 'active' END,
+            completed_at = CASE WHEN $3 = 0 THEN CURRENT_TIMESTAMP ELSE NULL END
+        WHERE id = $1
+        "#,
+        feature.id,
+        feature.state.total_awarded as i32,
+        feature.state.remaining as i32,
+        feature.state.spins_played as i32,
+        feature.state.total_win as i64,
+        serde_json::to_value(&feature.state.held_wilds).unwrap_or_default()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(SpinResponse {
+        result,
+        spin_id,
+        balance: Some(balance),
+        fair: None,
+        free_spins_feature: Some(feature),
+        bonus_round: None,
+        hold_and_spin_round: None,
+    }))
+}
+
+// 查询玩家所有进行中的免费旋转
+pub async fn get_player_free_spins(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let player_id = path.into_inner();
+
+    let features = sqlx::query!(
+        r#"
+        SELECT id, slot_config_id, session_id, trigger_spin_id, bet_per_line, active_lines,
+               total_awarded, remaining, spins_played, win_multiplier, total_win, created_at
+        FROM free_spin_sessions
+        WHERE player_id = $1 AND status = 'active'
+        ORDER BY created_at
+        "#,
+        player_id
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match features {
+        Ok(features) => {
+            let serializable_features: Vec<serde_json::Value> = features
+                .into_iter()
+                .map(|feature| {
+                    serde_json::json!({
+                        "id": feature.id,
+                        "slot_config_id": feature.slot_config_id,
+                        "session_id": feature.session_id,
+                        "trigger_spin_id": feature.trigger_spin_id,
+                        "bet_per_line": feature.bet_per_line,
+                        "active_lines": feature.active_lines,
+                        "total_awarded": feature.total_awarded,
+                        "remaining": feature.remaining,
+                        "spins_played": feature.spins_played,
+                        "win_multiplier": feature.win_multiplier,
+                        "total_win": feature.total_win,
+                        "created_at": feature.created_at.map(|t| t.to_string())
+                    })
+                })
+                .collect();
+            Ok(HttpResponse::Ok().json(serializable_features))
+        }
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to fetch free spins: {}", e)
+        }))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::universal_slots::BetMode;
+
+    fn result_with(total_win: u32, free_spins: u32) -> UniversalSpinResult {
+        UniversalSpinResult {
+            grid: Vec::new(),
+            cascades: Vec::new(),
+            megaway_rows: None,
+            winning_lines: Vec::new(),
+            scatter_wins: Vec::new(),
+            total_win,
+            free_spins,
+            bonus_triggered: false,
+            coins: Vec::new(),
+            hold_and_spin_triggered: false,
+            expanded_reels: Vec::new(),
+            held_wilds: Vec::new(),
+            reel_stops: None,
+            bet_per_line: 1,
+            bet_mode: BetMode::Standard,
+            reel_set: None,
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn test_free_spin_feature_runs_to_completion() {
+        let mut state = FreeSpinState::new(2, 3);
+
+        let mut first = result_with(10, 0);
+        state.play(&mut first);
+        assert_eq!(first.total_win, 30);
+        assert!(!state.is_complete());
+
+        let mut second = result_with(0, 0);
+        state.play(&mut second);
+        assert!(state.is_complete());
+        assert_eq!(state.total_win, 30);
+        assert_eq!(state.spins_played, 2);
+    }
+
+    #[test]
+    fn test_free_spin_retrigger_extends_feature() {
+        let mut state = FreeSpinState::new(1, 1);
+
+        let mut retrigger = result_with(5, 10);
+        state.play(&mut retrigger);
+
+        assert_eq!(state.remaining, 10);
+        assert_eq!(state.total_awarded, 11);
+        assert_eq!(state.total_win, 5);
+    }
+
+    #[test]
+    fn test_free_spin_multiplier_saturates() {
+        let mut state = FreeSpinState::new(2, 10);
+
+        let mut huge = result_with(u32::MAX / 2, 0);
+        state.play(&mut huge);
+
+        assert_eq!(huge.total_win, u32::MAX);
+        assert_eq!(state.total_win, u32::MAX as u64);
+    }
+}
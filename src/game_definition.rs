@@ -0,0 +1,457 @@
+use crate::api_error::ApiError;
+use crate::slot_config_api::{load_slot_config_builder, replace_symbol_payouts};
+use crate::universal_slots::{
+    EvaluationMode, JackpotTier, ReelMode, SlotConfigBuilder, SymbolType, BASE_REEL_SET,
+};
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+
+// 游戏定义文档即 SlotConfigBuilder 的JSON/YAML形式，与版本快照和模拟工具使用的格式相同。
+// 文档中的符号ID只是文档内部的引用，导入时重新分配数据库ID并更新卷轴和Wild属性中的引用
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl DocumentFormat {
+    // 根据 Content-Type 判断导入文档的格式，默认JSON
+    fn from_request(req: &HttpRequest) -> Self {
+        let content_type = req
+            .headers()
+            .get(actix_web::http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if content_type.contains("yaml") {
+            DocumentFormat::Yaml
+        } else {
+            DocumentFormat::Json
+        }
+    }
+}
+
+pub fn parse_definition(body: &[u8], format: DocumentFormat) -> Result<SlotConfigBuilder, String> {
+    match format {
+        DocumentFormat::Json => serde_json::from_slice(body).map_err(|e| e.to_string()),
+        // 经过 serde_json::Value 转换，使卷轴编号等数字键无论写成字符串还是整数都能解析
+        DocumentFormat::Yaml => serde_yaml::from_slice::<serde_json::Value>(body)
+            .map_err(|e| e.to_string())
+            .and_then(|value| serde_json::from_value(value).map_err(|e| e.to_string())),
+    }
+}
+
+// 先转换为 serde_json::Value，使对象的键按字母顺序输出，导出结果可以直接纳入版本管理
+pub fn render_definition(
+    builder: &SlotConfigBuilder,
+    format: DocumentFormat,
+) -> Result<String, String> {
+    let value = serde_json::to_value(builder).map_err(|e| e.to_string())?;
+    match format {
+        DocumentFormat::Json => serde_json::to_string_pretty(&value).map_err(|e| e.to_string()),
+        DocumentFormat::Yaml => serde_yaml::to_string(&value).map_err(|e| e.to_string()),
+    }
+}
+
+fn symbol_type_name(symbol_type: &SymbolType) -> &'static str {
+    match symbol_type {
+        SymbolType::Normal => "normal",
+        SymbolType::Wild => "wild",
+        SymbolType::Scatter => "scatter",
+        SymbolType::Bonus => "bonus",
+        SymbolType::Money => "money",
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: DocumentFormat,
+    pub version: Option<i32>, // 导出已发布的版本，未指定时导出草稿
+}
+
+// 导出完整的游戏定义
+pub async fn export_slot_config(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let builder = match query.version {
+        Some(version_number) => {
+            let snapshot = sqlx::query_scalar!(
+                "SELECT snapshot FROM slot_config_versions WHERE slot_config_id = $1 AND version_number = $2",
+                config_id,
+                version_number
+            )
+            .fetch_optional(pool.get_ref())
+            .await
+            .map_err(ApiError::from)?
+            .ok_or_else(|| {
+                ApiError::NotFound(format!(
+                    "Version {version_number} of slot config {config_id} not found"
+                ))
+            })?;
+            serde_json::from_value(snapshot)
+                .map_err(|e| ApiError::Internal(format!("Corrupt config snapshot: {e}")))?
+        }
+        None => load_slot_config_builder(pool.get_ref(), config_id).await?,
+    };
+
+    let body = render_definition(&builder, query.format)
+        .map_err(|e| ApiError::Internal(format!("Failed to export slot config: {e}")))?;
+    let content_type = match query.format {
+        DocumentFormat::Json => "application/json",
+        DocumentFormat::Yaml => "application/yaml",
+    };
+    Ok(HttpResponse::Ok().content_type(content_type).body(body))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportQuery {
+    pub name: Option<String>, // 覆盖文档中的配置名称，用于在同一环境中复制配置
+}
+
+// 在一个事务中导入完整的游戏定义，任何一步失败都不会留下部分数据
+pub async fn import_slot_config(
+    pool: web::Data<Pool<Postgres>>,
+    req: HttpRequest,
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse> {
+    let mut builder = parse_definition(&body, DocumentFormat::from_request(&req))
+        .map_err(|e| ApiError::BadRequest(format!("Invalid game definition: {e}")))?;
+    if let Some(name) = &query.name {
+        builder.config.name = name.clone();
+    }
+
+    let report = builder.validate();
+    if !report.valid {
+        return Err(ApiError::Unprocessable(format!(
+            "Game definition is invalid: {}",
+            report.error_summary()
+        ))
+        .into());
+    }
+    let config = &builder.config;
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+
+    let name_taken = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM slot_configurations WHERE name = $1) as "exists!""#,
+        config.name
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+    if name_taken {
+        return Err(ApiError::Conflict(format!(
+            "Slot config named '{}' already exists",
+            config.name
+        ))
+        .into());
+    }
+
+    let config_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO slot_configurations
+        (name, rows, reels, is_megaway, min_megaway_rows, max_megaway_rows,
+         default_bet, min_bet, max_bet, wild_enabled, free_spins_enabled, rtp_percentage,
+         free_spins_multiplier, reel_mode, evaluation_mode, cascading, cascade_multipliers)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+        RETURNING id
+        "#,
+        config.name,
+        config.rows as i32,
+        config.reels as i32,
+        config.is_megaway,
+        config.min_megaway_rows as i32,
+        config.max_megaway_rows as i32,
+        config.default_bet as i32,
+        config.min_bet as i32,
+        config.max_bet as i32,
+        config.wild_enabled,
+        config.free_spins_enabled,
+        config.rtp_percentage as f64,
+        config.free_spins_multiplier as i32,
+        match config.reel_mode {
+            ReelMode::Weighted => "weighted",
+            ReelMode::Stepper => "stepper",
+        },
+        match config.evaluation_mode {
+            EvaluationMode::Lines => "lines",
+            EvaluationMode::Cluster => "cluster",
+        },
+        config.cascading,
+        &config
+            .cascade_multipliers
+            .iter()
+            .map(|&m| m as i32)
+            .collect::<Vec<_>>()
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    // 文档中的符号ID -> 新的数据库ID
+    let mut symbol_ids = HashMap::new();
+    for symbol in &builder.symbols {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO slot_symbols (slot_config_id, name, symbol_type, value, image_url)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+            config_id,
+            symbol.name,
+            symbol_type_name(&symbol.symbol_type),
+            symbol.value as i32,
+            symbol.image_url
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+        replace_symbol_payouts(&mut tx, id, &symbol.payouts)
+            .await
+            .map_err(ApiError::from)?;
+        symbol_ids.insert(symbol.id, id);
+    }
+
+    // 基础卷轴和各个卷轴组使用同一张表，按 reel_set 区分
+    let reel_sets = std::iter::once((BASE_REEL_SET, &builder.reel_compositions)).chain(
+        builder
+            .reel_sets
+            .iter()
+            .map(|(name, compositions)| (name.as_str(), compositions)),
+    );
+    for (reel_set, compositions) in reel_sets {
+        let mut reel_numbers: Vec<_> = compositions.keys().copied().collect();
+        reel_numbers.sort_unstable();
+        for reel_number in reel_numbers {
+            for (position, (symbol_id, weight)) in compositions[&reel_number].iter().enumerate() {
+                sqlx::query!(
+                    r#"
+                INSERT INTO slot_reel_symbols
+                (slot_config_id, reel_set, reel_number, position, symbol_id, weight)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+                    config_id,
+                    reel_set,
+                    reel_number as i32,
+                    position as i32,
+                    symbol_ids[symbol_id],
+                    *weight as i32
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(ApiError::from)?;
+            }
+        }
+    }
+
+    for (reel_set, weight) in &builder.base_reel_set_weights {
+        sqlx::query!(
+            "INSERT INTO slot_reel_set_weights (slot_config_id, reel_set, weight) VALUES ($1, $2, $3)",
+            config_id,
+            reel_set,
+            *weight as i32
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+    }
+
+    for payline in &builder.paylines {
+        let pattern: Vec<[usize; 2]> = payline
+            .pattern
+            .iter()
+            .map(|&(reel, row)| [reel, row])
+            .collect();
+        sqlx::query!(
+            r#"
+            INSERT INTO slot_paylines (slot_config_id, line_number, pattern, is_active)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            config_id,
+            payline.line_number as i32,
+            serde_json::json!(pattern),
+            payline.is_active
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+    }
+
+    for trigger in &builder.scatter_triggers {
+        sqlx::query!(
+            r#"
+            INSERT INTO slot_scatter_triggers (slot_config_id, scatter_count, free_spins)
+            VALUES ($1, $2, $3)
+            "#,
+            config_id,
+            trigger.scatter_count as i32,
+            trigger.free_spins as i32
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+    }
+
+    for feature in &builder.wild_features {
+        // 不能替代的符号如果不在文档中，不会影响旋转，直接忽略
+        let cannot_substitute: Vec<i32> = feature
+            .cannot_substitute
+            .iter()
+            .filter_map(|id| symbol_ids.get(id).copied())
+            .collect();
+        sqlx::query!(
+            r#"
+            INSERT INTO slot_wild_features
+            (slot_config_id, symbol_id, multiplier, cannot_substitute, expanding, sticky, walking)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            config_id,
+            symbol_ids[&feature.symbol_id],
+            feature.multiplier as i32,
+            &cannot_substitute,
+            feature.expanding,
+            feature.sticky,
+            feature.walking
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+    }
+
+    if let Some(game) = &builder.bonus_game {
+        sqlx::query!(
+            r#"
+            INSERT INTO slot_bonus_games (slot_config_id, trigger_count, picks, board_size)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            config_id,
+            game.trigger_count as i32,
+            game.picks as i32,
+            game.board_size as i32
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+
+        for prize in &game.prizes {
+            sqlx::query!(
+                "INSERT INTO slot_bonus_prizes (slot_config_id, multiplier, weight) VALUES ($1, $2, $3)",
+                config_id,
+                prize.multiplier as i32,
+                prize.weight as i32
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(ApiError::from)?;
+        }
+    }
+
+    if let Some(feature) = &builder.hold_and_spin {
+        sqlx::query!(
+            "INSERT INTO slot_hold_and_spin (slot_config_id, trigger_count, respins) VALUES ($1, $2, $3)",
+            config_id,
+            feature.trigger_count as i32,
+            feature.respins as i32
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+
+        for value in &feature.values {
+            let jackpot = value.jackpot.map(|tier| match tier {
+                JackpotTier::Mini => "mini",
+                JackpotTier::Minor => "minor",
+                JackpotTier::Major => "major",
+                JackpotTier::Grand => "grand",
+            });
+            sqlx::query!(
+                r#"
+                INSERT INTO slot_money_values (slot_config_id, multiplier, jackpot, weight)
+                VALUES ($1, $2, $3, $4)
+                "#,
+                config_id,
+                value.multiplier as i32,
+                jackpot,
+                value.weight as i32
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(ApiError::from)?;
+        }
+    }
+
+    if let Some(ante) = &builder.ante_bet {
+        sqlx::query!(
+            "INSERT INTO slot_ante_bets (slot_config_id, cost_percent) VALUES ($1, $2)",
+            config_id,
+            ante.cost_percent as i32
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+    }
+
+    if let Some(buy) = &builder.bonus_buy {
+        sqlx::query!(
+            "INSERT INTO slot_bonus_buys (slot_config_id, cost_multiplier, free_spins) VALUES ($1, $2, $3)",
+            config_id,
+            buy.cost_multiplier as i32,
+            buy.free_spins as i32
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+    }
+
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Created().json(serde_json::json!({
+        "id": config_id,
+        "message": "Slot configuration imported successfully",
+        "warnings": report.warnings
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_definition_round_trips_through_yaml_and_json() {
+        let json = r#"{
+            "config": {
+                "name": "Doc", "rows": 3, "reels": 3, "is_megaway": false,
+                "min_megaway_rows": 2, "max_megaway_rows": 7, "default_bet": 1,
+                "min_bet": 1, "max_bet": 10, "wild_enabled": true,
+                "free_spins_enabled": false, "free_spins_multiplier": 1,
+                "rtp_percentage": 95.0
+            },
+            "symbols": [
+                {"id": 10, "name": "A", "symbol_type": "Normal", "value": 1,
+                 "image_url": null, "payouts": {"3": 5}}
+            ],
+            "reel_compositions": {"0": [[10, 1]], "1": [[10, 1]], "2": [[10, 1]]},
+            "paylines": [{"line_number": 1, "pattern": [[0, 1], [1, 1], [2, 1]], "is_active": true}]
+        }"#;
+        let builder = parse_definition(json.as_bytes(), DocumentFormat::Json).unwrap();
+        assert_eq!(builder.config.id, 0);
+
+        let yaml = render_definition(&builder, DocumentFormat::Yaml).unwrap();
+        let from_yaml = parse_definition(yaml.as_bytes(), DocumentFormat::Yaml).unwrap();
+        assert_eq!(
+            render_definition(&from_yaml, DocumentFormat::Json).unwrap(),
+            render_definition(&builder, DocumentFormat::Json).unwrap()
+        );
+        assert_eq!(from_yaml.reel_compositions[&2], vec![(10, 1)]);
+        assert_eq!(from_yaml.symbols[0].payouts[&3], 5);
+    }
+}
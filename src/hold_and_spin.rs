@@ -0,0 +1,290 @@
+use crate::api_error::ApiError;
+use crate::config_versions::{load_live_machine, load_version_machine};
+use crate::provably_fair::FairRng;
+use crate::universal_slots::{HoldAndSpinState, UniversalSlotMachine, UniversalSpinResult};
+use crate::wallet;
+use actix_web::{web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Pool, Postgres};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldAndSpinRound {
+    pub id: i32,
+    pub slot_config_id: i32,
+    pub spin_id: i32,
+    pub total_bet: i64,
+    #[serde(flatten)]
+    pub state: HoldAndSpinState,
+    pub total_win: i64, // 结束时派发的奖金：钱币金额之和 × 总投注
+    pub completed: bool,
+}
+
+impl HoldAndSpinRound {
+    fn from_state(
+        id: i32,
+        slot_config_id: i32,
+        spin_id: i32,
+        total_bet: i64,
+        state: HoldAndSpinState,
+    ) -> Self {
+        let completed = state.is_complete();
+        Self {
+            id,
+            slot_config_id,
+            spin_id,
+            total_bet,
+            total_win: if completed {
+                state.total_multiplier() as i64 * total_bet
+            } else {
+                0
+            },
+            state,
+            completed,
+        }
+    }
+}
+
+// 查询玩家在某个配置上进行中的Hold and Spin
+pub async fn has_active_round(
+    conn: &mut PgConnection,
+    player_id: i32,
+    slot_config_id: i32,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM hold_and_spin_rounds
+        WHERE player_id = $1 AND slot_config_id = $2 AND status = 'active'
+        "#,
+        player_id,
+        slot_config_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+// 旋转触发后锁定钱币并保存状态，重转的种子由触发旋转的种子推出；
+// 可验证公平旋转没有种子（seed 为 None），重转使用该次旋转 nonce 的 HMAC 随机数流
+#[allow(clippy::too_many_arguments)]
+pub async fn start_round(
+    conn: &mut PgConnection,
+    player_id: i32,
+    machine: &UniversalSlotMachine,
+    config_version_id: Option<i32>,
+    spin_id: i32,
+    total_bet: i64,
+    result: &UniversalSpinResult,
+    seed: Option<u64>,
+) -> Result<Option<HoldAndSpinRound>, sqlx::Error> {
+    let Some(state) = machine.start_hold_and_spin(result) else {
+        return Ok(None);
+    };
+
+    let rec = sqlx::query!(
+        r#"
+        INSERT INTO hold_and_spin_rounds
+        (player_id, slot_config_id, spin_id, config_version_id, total_bet,
+         reel_rows, coins, respins_remaining, seed)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id
+        "#,
+        player_id,
+        machine.config.id,
+        spin_id,
+        config_version_id,
+        total_bet,
+        serde_json::to_value(&state.reel_rows).unwrap_or_default(),
+        serde_json::to_value(&state.coins).unwrap_or_default(),
+        state.respins_remaining as i32,
+        seed.map(|seed| seed as i64)
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(Some(HoldAndSpinRound::from_state(
+        rec.id,
+        machine.config.id,
+        spin_id,
+        total_bet,
+        state,
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RespinRequest {
+    pub player_id: i32,
+    pub round_id: i32,
+}
+
+// 进行一次重转；结束时钱币金额之和按总投注派发，并累计到触发旋转的记录中
+pub async fn respin(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<RespinRequest>,
+) -> Result<HttpResponse> {
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+
+    let round = sqlx::query!(
+        r#"
+        SELECT h.id, h.slot_config_id, h.spin_id, h.config_version_id, h.total_bet,
+               h.reel_rows, h.coins, h.respins_remaining, h.respins_played, h.seed,
+               f.server_seed AS "server_seed?", f.client_seed AS "client_seed?",
+               s.fair_nonce
+        FROM hold_and_spin_rounds h
+        JOIN slot_spin_history s ON s.id = h.spin_id
+        LEFT JOIN provably_fair_seeds f ON f.id = s.fair_seed_id
+        WHERE h.id = $1 AND h.player_id = $2 AND h.status = 'active'
+        FOR UPDATE OF h
+        "#,
+        req.round_id,
+        req.player_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| ApiError::NotFound("Active hold and spin round not found".to_string()))?;
+
+    // 发布新版本或回滚不影响进行中的重转
+    let machine = match round.config_version_id {
+        Some(version_id) => {
+            load_version_machine(pool.get_ref(), round.slot_config_id, version_id).await?
+        }
+        None => {
+            load_live_machine(pool.get_ref(), round.slot_config_id)
+                .await?
+                .0
+        }
+    };
+
+    let mut state = HoldAndSpinState {
+        reel_rows: serde_json::from_value(round.reel_rows)
+            .map_err(|e| ApiError::Internal(format!("Corrupt hold and spin grid: {e}")))?,
+        coins: serde_json::from_value(round.coins)
+            .map_err(|e| ApiError::Internal(format!("Corrupt hold and spin coins: {e}")))?,
+        respins_remaining: round.respins_remaining as u32,
+        respins_played: round.respins_played as u32,
+    };
+
+    let respin = state.respins_played + 1;
+    let landed = match (
+        round.server_seed,
+        round.client_seed,
+        round.fair_nonce,
+        round.seed,
+    ) {
+        (Some(server_seed), Some(client_seed), Some(nonce), _) => {
+            let mut rng = FairRng::for_respin(&server_seed, &client_seed, nonce as u64, respin);
+            machine.respin(&mut state, &mut rng)
+        }
+        (_, _, _, Some(seed)) => machine.respin(
+            &mut state,
+            &mut HoldAndSpinState::respin_rng(seed as u64, respin),
+        ),
+        _ => {
+            return Err(
+                ApiError::Internal("Hold and spin round has no random source".to_string()).into(),
+            )
+        }
+    };
+    let hold_and_spin_round = HoldAndSpinRound::from_state(
+        round.id,
+        round.slot_config_id,
+        round.spin_id,
+        round.total_bet,
+        state,
+    );
+    let state = &hold_and_spin_round.state;
+
+    sqlx::query!(
+        r#"
+        UPDATE hold_and_spin_rounds
+        SET coins = $2, respins_remaining = $3, respins_played = $4, total_win = $5,
+            status = CASE WHEN $3 = 0 THEN 'completed' ELSE 'active' END,
+            completed_at = CASE WHEN $3 = 0 THEN CURRENT_TIMESTAMP ELSE NULL END
+        WHERE id = $1
+        "#,
+        round.id,
+        serde_json::to_value(&state.coins).unwrap_or_default(),
+        state.respins_remaining as i32,
+        state.respins_played as i32,
+        hold_and_spin_round.total_win
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    let mut balance = None;
+    if hold_and_spin_round.completed {
+        // 奖金计入触发旋转的结果
+        sqlx::query!(
+            r#"
+            UPDATE slot_spin_history
+            SET win_amount = win_amount + $2, hold_and_spin_result = $3
+            WHERE id = $1
+            "#,
+            round.spin_id,
+            hold_and_spin_round.total_win,
+            serde_json::to_value(&hold_and_spin_round).unwrap_or_default()
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+
+        balance = Some(
+            wallet::settle(&mut tx, req.player_id, 0, hold_and_spin_round.total_win)
+                .await
+                .map_err(ApiError::from)?,
+        );
+    }
+
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "round": hold_and_spin_round,
+        "landed": landed,
+        "balance": balance
+    })))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HoldAndSpinRoundQuery {
+    pub player_id: i32,
+}
+
+// 查询Hold and Spin状态，只能查询自己的回合
+pub async fn get_round(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    query: web::Query<HoldAndSpinRoundQuery>,
+) -> Result<HttpResponse> {
+    let round_id = path.into_inner();
+
+    let round = sqlx::query!(
+        r#"
+        SELECT id, slot_config_id, spin_id, total_bet, reel_rows, coins,
+               respins_remaining, respins_played
+        FROM hold_and_spin_rounds WHERE id = $1 AND player_id = $2
+        "#,
+        round_id,
+        query.player_id
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| ApiError::NotFound("Hold and spin round not found".to_string()))?;
+
+    let state = HoldAndSpinState {
+        reel_rows: serde_json::from_value(round.reel_rows).unwrap_or_default(),
+        coins: serde_json::from_value(round.coins).unwrap_or_default(),
+        respins_remaining: round.respins_remaining as u32,
+        respins_played: round.respins_played as u32,
+    };
+
+    Ok(HttpResponse::Ok().json(HoldAndSpinRound::from_state(
+        round.id,
+        round.slot_config_id,
+        round.spin_id,
+        round.total_bet,
+        state,
+    )))
+}
@@ -0,0 +1,71 @@
+use crate::slots::ProgressiveJackpot;
+use sqlx::types::time::OffsetDateTime;
+use sqlx::PgConnection;
+
+struct JackpotRow {
+    current_amount: i64,
+    min_amount: i64,
+    contribution_rate: f64,
+    last_won: Option<OffsetDateTime>,
+}
+
+impl From<JackpotRow> for ProgressiveJackpot {
+    fn from(row: JackpotRow) -> Self {
+        ProgressiveJackpot {
+            current_amount: row.current_amount as u64,
+            contribution_rate: row.contribution_rate,
+            min_amount: row.min_amount as u64,
+            last_won: row
+                .last_won
+                .and_then(|t| chrono::DateTime::from_timestamp(t.unix_timestamp(), t.nanosecond())),
+        }
+    }
+}
+
+// 经典老虎机的累积奖池保存在 progressive_jackpot 中：旋转时在事务内加锁读取，
+// 计入投注和派奖后随事务一起写回，事务失败时奖池保持不变
+pub async fn lock_jackpot(conn: &mut PgConnection) -> Result<ProgressiveJackpot, sqlx::Error> {
+    let row = sqlx::query_as!(
+        JackpotRow,
+        r#"
+        SELECT current_amount, min_amount, contribution_rate, last_won
+        FROM progressive_jackpot
+        WHERE id = 1
+        FOR UPDATE
+        "#
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(row.into())
+}
+
+pub async fn current_jackpot(conn: &mut PgConnection) -> Result<ProgressiveJackpot, sqlx::Error> {
+    let row = sqlx::query_as!(
+        JackpotRow,
+        r#"
+        SELECT current_amount, min_amount, contribution_rate, last_won
+        FROM progressive_jackpot
+        WHERE id = 1
+        "#
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(row.into())
+}
+
+pub async fn save_jackpot(
+    conn: &mut PgConnection,
+    jackpot: &ProgressiveJackpot,
+) -> Result<(), sqlx::Error> {
+    let last_won = jackpot.last_won.and_then(|t| {
+        OffsetDateTime::from_unix_timestamp_nanos(t.timestamp_nanos_opt()? as i128).ok()
+    });
+    sqlx::query!(
+        "UPDATE progressive_jackpot SET current_amount = $1, last_won = $2 WHERE id = 1",
+        jackpot.current_amount as i64,
+        last_won
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,6 @@
+// 与数据库无关的slot引擎，供服务器和独立的模拟工具共用
+pub mod rtp_solver;
+pub mod simulation;
+pub mod slot_math;
+pub mod slot_validation;
+pub mod universal_slots;
@@ -12,10 +12,22 @@ use sqlx::{Executor, FromRow, PgPool, Pool, Postgres};
 use std::env;
 use std::sync::Mutex;
 
+mod api_error;
+mod bonus_game;
+mod config_versions;
+mod free_spins;
+mod game_definition;
+mod hold_and_spin;
+mod jackpot;
+mod provably_fair;
+mod simulation_jobs;
 mod slot_config_api;
 mod slots;
-mod universal_slots;
-use slots::{ProgressiveJackpot, SlotMachine};
+mod spin_history;
+mod wallet;
+use api_error::ApiError;
+use saber::{rtp_solver, slot_math, universal_slots};
+use slots::SlotMachine;
 
 #[get("/{id}")]
 async fn retrieve(path: web::Path<i32>, state: web::Data<AppState>) -> Result<Json<Todo>> {
@@ -86,41 +98,10 @@ async fn delete_todo(
     ))
 }
 
-// custom error
-#[derive(Debug)]
-enum MyError {
-    SqlxError(sqlx::Error),
-    IoError(std::io::Error),
-}
-
-impl std::fmt::Display for MyError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            MyError::SqlxError(e) => write!(f, "sqlx error: {e}"),
-            MyError::IoError(e) => write!(f, "io error: {e}"),
-        }
-    }
-}
-
-impl std::error::Error for MyError {}
-
-impl From<sqlx::Error> for MyError {
-    fn from(e: sqlx::Error) -> Self {
-        MyError::SqlxError(e)
-    }
-}
-
-impl From<std::io::Error> for MyError {
-    fn from(e: std::io::Error) -> Self {
-        MyError::IoError(e)
-    }
-}
-
 #[derive(Clone)]
 struct AppState {
     pool: PgPool,
     slot_machine: web::Data<Mutex<SlotMachine>>,
-    jackpot: web::Data<Mutex<ProgressiveJackpot>>,
 }
 
 #[derive(Deserialize)]
@@ -167,53 +148,118 @@ async fn hello(name: web::Path<String>) -> impl Responder {
 }
 
 // Slot machine endpoints
-#[post("/slots/spin")]
+#[post("/spin")]
 async fn spin_slots(
     bet: web::Json<SlotBet>,
     state: web::Data<AppState>,
-) -> Result<Json<slots::SpinResult>> {
-    let mut machine = state.slot_machine.lock().unwrap();
-    let mut jackpot = state.jackpot.lock().unwrap();
+) -> Result<Json<SpinResultWithJackpot>> {
+    universal_slots::check_bet_limits(bet.amount, slots::MIN_BET, slots::MAX_BET)
+        .map_err(ApiError::from)?;
+    let bet_amount = bet.amount as i64;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(|e| error::ErrorInternalServerError(e.to_string()))?;
 
-    // Add bet to progressive jackpot
-    jackpot.add_contribution(bet.amount);
+    // Lock the player's row so concurrent spins cannot overdraw the balance
+    wallet::lock_balance(&mut tx, bet.player_id, bet_amount)
+        .await
+        .map_err(ApiError::from)?;
+
+    // The jackpot row stays locked until commit, so a failed spin leaves the pool untouched
+    let mut progressive = jackpot::lock_jackpot(&mut tx)
+        .await
+        .map_err(|e| error::ErrorInternalServerError(e.to_string()))?;
+    progressive.add_contribution(bet.amount);
 
-    // Spin the slot machine
-    let result = machine.spin();
+    let result = state.slot_machine.lock().unwrap().spin();
 
     // Check for jackpot win (three diamonds on center line)
     let is_jackpot = result
         .winning_lines
         .iter()
         .any(|line| matches!(&line.win_type, slots::WinType::ThreeDiamonds));
+    let jackpot_win = progressive.check_and_award(is_jackpot);
+
+    // total_win is a multiplier of the bet; the jackpot is paid as a flat amount
+    let win_amount = result.total_win as u64 * bet.amount + jackpot_win.unwrap_or(0);
+    let win_amount = i64::try_from(win_amount)
+        .map_err(|_| error::ErrorInternalServerError("Win amount overflow"))?;
 
-    let jackpot_win = jackpot.check_and_award(is_jackpot);
+    let new_balance = wallet::settle(&mut tx, bet.player_id, bet_amount, win_amount)
+        .await
+        .map_err(ApiError::from)?;
+
+    let record = spin_history::SpinRecord {
+        player_id: Some(bet.player_id),
+        session_id: bet.session_id,
+        slot_config_id: None,
+        bet_amount,
+        win_amount,
+        symbols: serde_json::to_value(&result.grid).unwrap_or_default(),
+        winning_lines: serde_json::to_value(&result.winning_lines).unwrap_or_default(),
+        is_megaway_spin: false,
+        megaway_rows: None,
+        cascades: None,
+        rng_seed: result.seed,
+        fair_seed_id: None,
+        fair_nonce: None,
+        is_free_spin: false,
+        free_spin_session_id: None,
+        config_version_id: None,
+        bet_mode: "standard".to_string(),
+        active_lines: None,
+    };
+    spin_history::record_spin(&mut tx, &record)
+        .await
+        .map_err(|e| error::ErrorInternalServerError(e.to_string()))?;
+
+    if let Some(amount) = jackpot_win {
+        sqlx::query("INSERT INTO jackpot_wins (player_id, win_amount) VALUES ($1, $2)")
+            .bind(bet.player_id)
+            .bind(amount as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| error::ErrorInternalServerError(e.to_string()))?;
+    }
+
+    jackpot::save_jackpot(&mut tx, &progressive)
+        .await
+        .map_err(|e| error::ErrorInternalServerError(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| error::ErrorInternalServerError(e.to_string()))?;
 
-    // Create enhanced result with jackpot info
-    let enhanced_result = SpinResultWithJackpot {
+    Ok(Json(SpinResultWithJackpot {
         grid: result.grid,
         winning_lines: result.winning_lines,
-        total_win: result.total_win + jackpot_win.unwrap_or(0) as u32,
+        total_win: result.total_win,
         jackpot_win,
-    };
-
-    Ok(Json(slots::SpinResult {
-        grid: enhanced_result.grid,
-        winning_lines: enhanced_result.winning_lines,
-        total_win: enhanced_result.total_win,
+        win_amount: win_amount as u64,
+        balance: new_balance,
     }))
 }
 
-#[get("/slots/jackpot")]
+#[get("/jackpot")]
 async fn get_jackpot(state: web::Data<AppState>) -> Result<Json<JackpotInfo>> {
-    let jackpot = state.jackpot.lock().unwrap();
+    let mut conn = state
+        .pool
+        .acquire()
+        .await
+        .map_err(|e| error::ErrorInternalServerError(e.to_string()))?;
+    let progressive = jackpot::current_jackpot(&mut conn)
+        .await
+        .map_err(|e| error::ErrorInternalServerError(e.to_string()))?;
     Ok(Json(JackpotInfo {
-        current_amount: jackpot.current_amount,
-        last_won: jackpot.last_won,
+        current_amount: progressive.current_amount,
+        last_won: progressive.last_won,
     }))
 }
 
-#[get("/slots/rtp")]
+#[get("/rtp")]
 async fn calculate_slot_rtp() -> Result<Json<RtpInfo>> {
     let rtp = slots::calculate_rtp(10000);
     Ok(Json(RtpInfo {
@@ -224,6 +270,8 @@ async fn calculate_slot_rtp() -> Result<Json<RtpInfo>> {
 
 #[derive(Deserialize)]
 struct SlotBet {
+    player_id: i32,
+    session_id: Option<i32>,
     amount: u64,
 }
 
@@ -233,6 +281,8 @@ struct SpinResultWithJackpot {
     winning_lines: Vec<slots::WinningLine>,
     total_win: u32,
     jackpot_win: Option<u64>,
+    win_amount: u64,
+    balance: i64,
 }
 
 #[derive(Serialize)]
@@ -268,14 +318,14 @@ async fn main() -> std::io::Result<()> {
     env_logger::init();
     let pool = establish_connection().await.unwrap();
 
-    // Initialize slot machine and jackpot
+    // Initialize slot machine; the progressive jackpot lives in the database
     let slot_machine = web::Data::new(Mutex::new(SlotMachine::new(3, 3)));
-    let jackpot = web::Data::new(Mutex::new(ProgressiveJackpot::new(10000, 0.02)));
 
+    let db_pool = web::Data::new(pool.clone());
+    let simulation_jobs = web::Data::new(simulation_jobs::SimulationJobs::default());
     let state = web::Data::new(AppState {
         pool,
         slot_machine: slot_machine.clone(),
-        jackpot: jackpot.clone(),
     });
 
     // Get host and port from environment variables
@@ -311,13 +361,137 @@ async fn main() -> std::io::Result<()> {
                 web::scope("/api/slot-config")
                     .route("", web::post().to(slot_config_api::create_slot_config))
                     .route("", web::get().to(slot_config_api::list_slot_configs))
+                    .route(
+                        "/import",
+                        web::post().to(game_definition::import_slot_config),
+                    )
+                    .route(
+                        "/simulations",
+                        web::get().to(simulation_jobs::list_simulations),
+                    )
+                    .route(
+                        "/simulations/{job_id}",
+                        web::get().to(simulation_jobs::get_simulation),
+                    )
+                    .route(
+                        "/simulations/{job_id}",
+                        web::delete().to(simulation_jobs::cancel_simulation),
+                    )
                     .route("/{id}", web::get().to(slot_config_api::get_slot_config))
+                    .route(
+                        "/{id}",
+                        web::patch().to(slot_config_api::update_slot_config),
+                    )
+                    .route(
+                        "/{id}",
+                        web::delete().to(slot_config_api::delete_slot_config),
+                    )
                     .route("/symbol", web::post().to(slot_config_api::add_symbol))
+                    .route(
+                        "/symbol/{symbol_id}",
+                        web::patch().to(slot_config_api::update_symbol),
+                    )
+                    .route(
+                        "/symbol/{symbol_id}",
+                        web::delete().to(slot_config_api::delete_symbol),
+                    )
                     .route(
                         "/reel-symbol",
                         web::post().to(slot_config_api::add_reel_symbol),
                     )
+                    .route(
+                        "/reel-symbol/{id}/{reel_number}/{position}",
+                        web::patch().to(slot_config_api::update_reel_symbol),
+                    )
+                    .route(
+                        "/reel-symbol/{id}/{reel_number}/{position}",
+                        web::delete().to(slot_config_api::delete_reel_symbol),
+                    )
                     .route("/payline", web::post().to(slot_config_api::add_payline))
+                    .route(
+                        "/payline/{id}/{line_number}",
+                        web::patch().to(slot_config_api::update_payline),
+                    )
+                    .route(
+                        "/payline/{id}/{line_number}",
+                        web::delete().to(slot_config_api::delete_payline),
+                    )
+                    .route(
+                        "/wild-feature",
+                        web::post().to(slot_config_api::set_wild_feature),
+                    )
+                    .route(
+                        "/wild-feature/{symbol_id}",
+                        web::delete().to(slot_config_api::delete_wild_feature),
+                    )
+                    .route(
+                        "/bonus-game",
+                        web::post().to(slot_config_api::set_bonus_game),
+                    )
+                    .route(
+                        "/bonus-prize",
+                        web::post().to(slot_config_api::add_bonus_prize),
+                    )
+                    .route(
+                        "/bonus-prize/{id}",
+                        web::delete().to(slot_config_api::delete_bonus_prize),
+                    )
+                    .route("/bonus/pick", web::post().to(bonus_game::pick))
+                    .route("/bonus/{round_id}", web::get().to(bonus_game::get_round))
+                    .route(
+                        "/hold-and-spin",
+                        web::post().to(slot_config_api::set_hold_and_spin),
+                    )
+                    .route(
+                        "/money-value",
+                        web::post().to(slot_config_api::add_money_value),
+                    )
+                    .route(
+                        "/money-value/{id}",
+                        web::delete().to(slot_config_api::delete_money_value),
+                    )
+                    .route(
+                        "/hold-and-spin/respin",
+                        web::post().to(hold_and_spin::respin),
+                    )
+                    .route(
+                        "/hold-and-spin/{round_id}",
+                        web::get().to(hold_and_spin::get_round),
+                    )
+                    .route(
+                        "/reel-set-weight",
+                        web::post().to(slot_config_api::set_reel_set_weight),
+                    )
+                    .route(
+                        "/reel-set-weight/{id}/{reel_set}",
+                        web::delete().to(slot_config_api::delete_reel_set_weight),
+                    )
+                    .route("/ante-bet", web::post().to(slot_config_api::set_ante_bet))
+                    .route(
+                        "/ante-bet/{id}",
+                        web::delete().to(slot_config_api::delete_ante_bet),
+                    )
+                    .route("/bonus-buy", web::post().to(slot_config_api::set_bonus_buy))
+                    .route(
+                        "/bonus-buy/{id}",
+                        web::delete().to(slot_config_api::delete_bonus_buy),
+                    )
+                    .route(
+                        "/jurisdiction-rule",
+                        web::post().to(slot_config_api::set_jurisdiction_rule),
+                    )
+                    .route(
+                        "/jurisdiction-rule/{id}/{jurisdiction}",
+                        web::delete().to(slot_config_api::delete_jurisdiction_rule),
+                    )
+                    .route(
+                        "/scatter-trigger",
+                        web::post().to(slot_config_api::add_scatter_trigger),
+                    )
+                    .route(
+                        "/scatter-trigger/{id}/{scatter_count}",
+                        web::delete().to(slot_config_api::delete_scatter_trigger),
+                    )
                     .route(
                         "/{id}/symbols",
                         web::get().to(slot_config_api::get_slot_symbols),
@@ -326,16 +500,96 @@ async fn main() -> std::io::Result<()> {
                         "/{id}/reels",
                         web::get().to(slot_config_api::get_slot_reels),
                     )
+                    .route(
+                        "/{id}/reel-sets",
+                        web::get().to(slot_config_api::get_reel_sets),
+                    )
+                    .route(
+                        "/{id}/reel-sets/{reel_set}",
+                        web::delete().to(slot_config_api::delete_reel_set),
+                    )
                     .route(
                         "/{id}/paylines",
                         web::get().to(slot_config_api::get_slot_paylines),
                     )
-                    .route("/spin", web::post().to(slot_config_api::test_spin)),
+                    .route("/{id}/math", web::get().to(slot_config_api::get_slot_math))
+                    .route(
+                        "/{id}/validate",
+                        web::get().to(slot_config_api::validate_slot_config),
+                    )
+                    .route(
+                        "/{id}/rtp-solver/preview",
+                        web::post().to(slot_config_api::preview_rtp_solver),
+                    )
+                    .route(
+                        "/{id}/rtp-solver/apply",
+                        web::post().to(slot_config_api::apply_rtp_solver),
+                    )
+                    .route(
+                        "/{id}/simulate",
+                        web::post().to(simulation_jobs::start_simulation),
+                    )
+                    .route(
+                        "/{id}/export",
+                        web::get().to(game_definition::export_slot_config),
+                    )
+                    .route("/{id}/publish", web::post().to(config_versions::publish))
+                    .route(
+                        "/{id}/versions",
+                        web::get().to(config_versions::list_versions),
+                    )
+                    .route(
+                        "/{id}/versions/{version_number}",
+                        web::get().to(config_versions::get_version),
+                    )
+                    .route("/{id}/rollback", web::post().to(config_versions::rollback))
+                    .route(
+                        "/{id}/wild-features",
+                        web::get().to(slot_config_api::get_wild_features),
+                    )
+                    .route(
+                        "/{id}/bonus-game",
+                        web::get().to(slot_config_api::get_bonus_game),
+                    )
+                    .route(
+                        "/{id}/hold-and-spin",
+                        web::get().to(slot_config_api::get_hold_and_spin),
+                    )
+                    .route(
+                        "/{id}/bet-options",
+                        web::get().to(slot_config_api::get_bet_options),
+                    )
+                    .route(
+                        "/{id}/scatter-triggers",
+                        web::get().to(slot_config_api::get_scatter_triggers),
+                    )
+                    .route("/spin", web::post().to(slot_config_api::test_spin))
+                    .route("/replay", web::post().to(slot_config_api::replay_spin))
+                    .route("/free-spin", web::post().to(free_spins::play_free_spin)),
+            )
+            .service(
+                web::scope("/api/provably-fair")
+                    .route("/seed", web::post().to(provably_fair::create_seed))
+                    .route(
+                        "/seed/{player_id}",
+                        web::get().to(provably_fair::get_active_seed),
+                    )
+                    .route("/reveal", web::post().to(provably_fair::reveal_seed))
+                    .route("/verify", web::post().to(provably_fair::verify_spin)),
+            )
+            .service(
+                web::scope("/api/players")
+                    .route("/{id}/spins", web::get().to(spin_history::get_player_spins))
+                    .route(
+                        "/{id}/free-spins",
+                        web::get().to(free_spins::get_player_free_spins),
+                    ),
             )
             .service(fs::Files::new("/admin", "./admin").index_file("index.html"))
             .app_data(state.clone())
+            .app_data(db_pool.clone())
+            .app_data(simulation_jobs.clone())
             .app_data(slot_machine.clone())
-            .app_data(jackpot.clone())
     })
     .bind((host.as_str(), port))?
     .run()
@@ -343,3 +597,106 @@ async fn main() -> std::io::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, App};
+
+    async fn seed_player(pool: &PgPool, username: &str, balance: i64) -> i32 {
+        sqlx::query_scalar(
+            "INSERT INTO players (username, email, balance) VALUES ($1, $1 || '@example.com', $2)
+             RETURNING id",
+        )
+        .bind(username)
+        .bind(balance)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn balance_and_jackpot(pool: &PgPool, player_id: i32) -> (i64, i64, i64) {
+        sqlx::query_as(
+            "SELECT (SELECT balance FROM players WHERE id = $1),
+                    (SELECT current_amount FROM progressive_jackpot),
+                    (SELECT COUNT(*) FROM slot_spin_history)",
+        )
+        .bind(player_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    macro_rules! spin_app {
+        ($pool:expr) => {
+            test::init_service(
+                App::new()
+                    .app_data(web::Data::new(AppState {
+                        pool: $pool.clone(),
+                        slot_machine: web::Data::new(Mutex::new(SlotMachine::new(3, 3))),
+                    }))
+                    .service(web::scope("/slots").service(spin_slots)),
+            )
+            .await
+        };
+    }
+
+    #[sqlx::test]
+    async fn test_spin_settles_bet_win_and_jackpot_atomically(pool: PgPool) {
+        let player_id = seed_player(&pool, "alice", 1000).await;
+        let app = spin_app!(pool);
+
+        let req = test::TestRequest::post()
+            .uri("/slots/spin")
+            .set_json(serde_json::json!({"player_id": player_id, "amount": 100}))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        let win_amount = body["win_amount"].as_i64().unwrap();
+        let (balance, pool_amount, spins) = balance_and_jackpot(&pool, player_id).await;
+        assert_eq!(body["balance"].as_i64(), Some(balance));
+        assert_eq!(balance, 1000 - 100 + win_amount);
+        assert_eq!(spins, 1);
+        // 2% of the bet goes into the pool unless this spin won it
+        match body["jackpot_win"].as_u64() {
+            Some(won) => assert_eq!((won, pool_amount), (10002, 10000)),
+            None => assert_eq!(pool_amount, 10002),
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_failed_spin_leaves_balance_and_jackpot_unchanged(pool: PgPool) {
+        let player_id = seed_player(&pool, "alice", 1000).await;
+        let app = spin_app!(pool);
+
+        // Recording the spin fails on the unknown session, after the jackpot was updated
+        let req = test::TestRequest::post()
+            .uri("/slots/spin")
+            .set_json(serde_json::json!({"player_id": player_id, "session_id": 999, "amount": 100}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            balance_and_jackpot(&pool, player_id).await,
+            (1000, 10000, 0)
+        );
+
+        let poor_id = seed_player(&pool, "bob", 50).await;
+        let req = test::TestRequest::post()
+            .uri("/slots/spin")
+            .set_json(serde_json::json!({"player_id": poor_id, "amount": 100}))
+            .to_request();
+        let body: serde_json::Value =
+            test::read_body_json(test::call_service(&app, req).await).await;
+        assert_eq!(body["code"], "insufficient_balance");
+        assert_eq!(balance_and_jackpot(&pool, poor_id).await, (50, 10000, 0));
+
+        let req = test::TestRequest::post()
+            .uri("/slots/spin")
+            .set_json(serde_json::json!({"player_id": player_id, "amount": 0}))
+            .to_request();
+        let body: serde_json::Value =
+            test::read_body_json(test::call_service(&app, req).await).await;
+        assert_eq!(body["code"], "zero_bet");
+    }
+}
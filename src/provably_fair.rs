@@ -0,0 +1,510 @@
+use crate::api_error::ApiError;
+use crate::config_versions::load_machine_at;
+use crate::universal_slots::{
+    BetMode, HoldAndSpinState, UniversalSlotMachine, UniversalSpinResult,
+};
+use actix_web::{web, HttpResponse, Result};
+use hmac::{Hmac, Mac};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, Pool, Postgres};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// 可验证公平的随机数流：
+// 第 n 个32字节块 = HMAC-SHA256(key = server_seed, message = "{client_seed}:{nonce}:{n}")
+// 第 r 次Hold and Spin重转使用 message = "{client_seed}:{nonce}:hold_and_spin:{r}:{n}" 的独立流
+// 按块顺序依次取字节，任何人拿到公开后的 server_seed 都可以重新计算
+pub struct FairRng {
+    mac: HmacSha256,
+    prefix: String,
+    round: u64,
+    block: [u8; 32],
+    offset: usize,
+}
+
+impl FairRng {
+    pub fn new(server_seed: &str, client_seed: &str, nonce: u64) -> Self {
+        Self::with_prefix(server_seed, format!("{client_seed}:{nonce}"))
+    }
+
+    // 第 respin 次重转的随机数流（从1开始）
+    pub fn for_respin(server_seed: &str, client_seed: &str, nonce: u64, respin: u32) -> Self {
+        Self::with_prefix(
+            server_seed,
+            format!("{client_seed}:{nonce}:hold_and_spin:{respin}"),
+        )
+    }
+
+    fn with_prefix(server_seed: &str, prefix: String) -> Self {
+        let mac = HmacSha256::new_from_slice(server_seed.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        let mut rng = Self {
+            mac,
+            prefix,
+            round: 0,
+            block: [0; 32],
+            offset: 0,
+        };
+        rng.refill();
+        rng
+    }
+
+    fn refill(&mut self) {
+        let mut mac = self.mac.clone();
+        mac.update(format!("{}:{}", self.prefix, self.round).as_bytes());
+        self.block.copy_from_slice(&mac.finalize().into_bytes());
+        self.round += 1;
+        self.offset = 0;
+    }
+}
+
+impl RngCore for FairRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        for byte in dst {
+            if self.offset == self.block.len() {
+                self.refill();
+            }
+            *byte = self.block[self.offset];
+            self.offset += 1;
+        }
+    }
+}
+
+pub fn generate_server_seed() -> String {
+    let bytes: [u8; 32] = rand::rng().random();
+    hex::encode(bytes)
+}
+
+pub fn hash_server_seed(server_seed: &str) -> String {
+    hex::encode(Sha256::digest(server_seed.as_bytes()))
+}
+
+pub fn fair_spin(
+    machine: &UniversalSlotMachine,
+    bet_per_line: u32,
+    bet_mode: BetMode,
+    server_seed: &str,
+    client_seed: &str,
+    nonce: u64,
+) -> UniversalSpinResult {
+    let mut rng = FairRng::new(server_seed, client_seed, nonce);
+    machine.spin_with_rng_mode(bet_per_line, bet_mode, &mut rng)
+}
+
+// 校验结果：旋转结果，以及触发时按重转随机数流进行到结束的Hold and Spin
+#[derive(Debug, Serialize)]
+pub struct VerifiedSpin {
+    #[serde(flatten)]
+    pub result: UniversalSpinResult,
+    pub hold_and_spin: Option<HoldAndSpinState>,
+}
+
+// 校验公开的 server_seed 与之前承诺的哈希一致，并重新计算该次旋转
+pub fn verify(
+    machine: &UniversalSlotMachine,
+    bet_per_line: u32,
+    bet_mode: BetMode,
+    server_seed: &str,
+    server_seed_hash: &str,
+    client_seed: &str,
+    nonce: u64,
+) -> Result<VerifiedSpin, String> {
+    if !hash_server_seed(server_seed).eq_ignore_ascii_case(server_seed_hash) {
+        return Err("Server seed does not match the committed hash".to_string());
+    }
+    let result = fair_spin(
+        machine,
+        bet_per_line,
+        bet_mode,
+        server_seed,
+        client_seed,
+        nonce,
+    );
+    let hold_and_spin = machine.play_hold_and_spin_with(&result, |respin| {
+        FairRng::for_respin(server_seed, client_seed, nonce, respin)
+    });
+    Ok(VerifiedSpin {
+        result,
+        hold_and_spin,
+    })
+}
+
+// 可验证公平旋转触发的Hold and Spin结束前不能公开 server_seed，否则剩余的重转可以被预测
+async fn has_pending_fair_respins(
+    pool: &Pool<Postgres>,
+    player_id: i32,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM hold_and_spin_rounds h
+            JOIN slot_spin_history s ON s.id = h.spin_id
+            WHERE h.player_id = $1 AND h.status = 'active' AND s.fair_seed_id IS NOT NULL
+        ) AS "pending!"
+        "#,
+        player_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+fn pending_fair_respins_error() -> ApiError {
+    ApiError::Conflict(
+        "Finish the provably fair hold and spin round before revealing the server seed".to_string(),
+    )
+}
+
+// 随旋转结果返回给玩家的公平性证明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FairProof {
+    pub seed_id: i32,
+    pub server_seed_hash: String,
+    pub client_seed: String,
+    pub nonce: u64,
+}
+
+// 使用玩家当前的种子对执行一次可验证公平旋转，nonce 自增（需在事务内调用）
+pub async fn spin_with_active_seed(
+    conn: &mut PgConnection,
+    machine: &UniversalSlotMachine,
+    player_id: i32,
+    bet_per_line: u32,
+    bet_mode: BetMode,
+) -> Result<(UniversalSpinResult, FairProof), ApiError> {
+    let seed = sqlx::query!(
+        r#"
+        SELECT id, server_seed, server_seed_hash, client_seed, nonce
+        FROM provably_fair_seeds
+        WHERE player_id = $1 AND is_active = true
+        FOR UPDATE
+        "#,
+        player_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| {
+        ApiError::NotFound(
+            "No active seed pair, create one via /api/provably-fair/seed".to_string(),
+        )
+    })?;
+
+    let nonce = seed.nonce as u64;
+    let result = fair_spin(
+        machine,
+        bet_per_line,
+        bet_mode,
+        &seed.server_seed,
+        &seed.client_seed,
+        nonce,
+    );
+
+    sqlx::query!(
+        "UPDATE provably_fair_seeds SET nonce = nonce + 1 WHERE id = $1",
+        seed.id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok((
+        result,
+        FairProof {
+            seed_id: seed.id,
+            server_seed_hash: seed.server_seed_hash,
+            client_seed: seed.client_seed,
+            nonce,
+        },
+    ))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSeedRequest {
+    pub player_id: i32,
+    pub client_seed: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevealRequest {
+    pub player_id: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyRequest {
+    pub slot_config_id: i32,
+    pub bet_per_line: u32,
+    pub server_seed: String,
+    pub server_seed_hash: String,
+    pub client_seed: String,
+    pub nonce: u64,
+    // 旋转记录中的 config_version_id，未指定时使用线上版本
+    #[serde(default)]
+    pub version_id: Option<i32>,
+    // 旋转记录中的 bet_mode，默认标准旋转
+    #[serde(default)]
+    pub bet_mode: BetMode,
+    // 旋转记录中的 active_lines，未指定时使用全部支付线
+    #[serde(default)]
+    pub lines: Option<u32>,
+}
+
+// 创建新的种子对：公布 server_seed 的哈希，旧的种子对会被公开
+pub async fn create_seed(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<CreateSeedRequest>,
+) -> Result<HttpResponse> {
+    if has_pending_fair_respins(pool.get_ref(), req.player_id)
+        .await
+        .map_err(ApiError::from)?
+    {
+        return Err(pending_fair_respins_error().into());
+    }
+    let server_seed = generate_server_seed();
+    let server_seed_hash = hash_server_seed(&server_seed);
+    let client_seed = req
+        .client_seed
+        .clone()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| hex::encode(rand::rng().random::<[u8; 16]>()));
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE provably_fair_seeds
+            SET is_active = false, revealed_at = CURRENT_TIMESTAMP
+            WHERE player_id = $1 AND is_active = true
+            "#,
+            req.player_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO provably_fair_seeds (player_id, server_seed, server_seed_hash, client_seed)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+            req.player_id,
+            server_seed,
+            server_seed_hash,
+            client_seed
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(rec.id)
+    }
+    .await;
+
+    match result {
+        Ok(id) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "seed_id": id,
+            "server_seed_hash": server_seed_hash,
+            "client_seed": client_seed,
+            "nonce": 0
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to create seed pair: {}", e)
+        }))),
+    }
+}
+
+// 查询玩家当前的种子承诺（不包含 server_seed）
+pub async fn get_active_seed(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let player_id = path.into_inner();
+
+    let seed = sqlx::query!(
+        r#"
+        SELECT id, server_seed_hash, client_seed, nonce
+        FROM provably_fair_seeds
+        WHERE player_id = $1 AND is_active = true
+        "#,
+        player_id
+    )
+    .fetch_optional(pool.get_ref())
+    .await;
+
+    match seed {
+        Ok(Some(seed)) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "seed_id": seed.id,
+            "server_seed_hash": seed.server_seed_hash,
+            "client_seed": seed.client_seed,
+            "nonce": seed.nonce
+        }))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No active seed pair"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to fetch seed pair: {}", e)
+        }))),
+    }
+}
+
+// 公开当前的 server_seed，之后该种子对不能再用于旋转
+pub async fn reveal_seed(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<RevealRequest>,
+) -> Result<HttpResponse> {
+    if has_pending_fair_respins(pool.get_ref(), req.player_id)
+        .await
+        .map_err(ApiError::from)?
+    {
+        return Err(pending_fair_respins_error().into());
+    }
+    let seed = sqlx::query!(
+        r#"
+        UPDATE provably_fair_seeds
+        SET is_active = false, revealed_at = CURRENT_TIMESTAMP
+        WHERE player_id = $1 AND is_active = true
+        RETURNING id, server_seed, server_seed_hash, client_seed, nonce
+        "#,
+        req.player_id
+    )
+    .fetch_optional(pool.get_ref())
+    .await;
+
+    match seed {
+        Ok(Some(seed)) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "seed_id": seed.id,
+            "server_seed": seed.server_seed,
+            "server_seed_hash": seed.server_seed_hash,
+            "client_seed": seed.client_seed,
+            "spins_played": seed.nonce
+        }))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No active seed pair"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to reveal seed pair: {}", e)
+        }))),
+    }
+}
+
+// 根据公开的种子重新计算一次历史旋转
+pub async fn verify_spin(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<VerifyRequest>,
+) -> Result<HttpResponse> {
+    let machine = load_machine_at(pool.get_ref(), req.slot_config_id, req.version_id).await?;
+
+    match verify(
+        &machine.with_active_lines(req.lines),
+        req.bet_per_line,
+        req.bet_mode,
+        &req.server_seed,
+        &req.server_seed_hash,
+        &req.client_seed,
+        req.nonce,
+    ) {
+        Ok(result) => Ok(HttpResponse::Ok().json(result)),
+        Err(e) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e
+        }))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fair_rng_is_deterministic() {
+        let mut a = FairRng::new("server", "client", 7);
+        let mut b = FairRng::new("server", "client", 7);
+        let mut c = FairRng::new("server", "client", 8);
+
+        let first: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        let second: Vec<u64> = (0..10).map(|_| b.next_u64()).collect();
+        let other: Vec<u64> = (0..10).map(|_| c.next_u64()).collect();
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn test_respin_streams_are_separate() {
+        let spin: Vec<u64> = {
+            let mut rng = FairRng::new("server", "client", 7);
+            (0..4).map(|_| rng.next_u64()).collect()
+        };
+        let respin = |n| -> Vec<u64> {
+            let mut rng = FairRng::for_respin("server", "client", 7, n);
+            (0..4).map(|_| rng.next_u64()).collect()
+        };
+
+        assert_eq!(respin(1), respin(1));
+        assert_ne!(respin(1), respin(2));
+        assert_ne!(respin(1), spin);
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    async fn test_seed_stays_secret_until_fair_respins_finish(pool: Pool<Postgres>) {
+        sqlx::query(
+            "INSERT INTO provably_fair_seeds (id, player_id, server_seed, server_seed_hash, client_seed)
+             VALUES (1, 1, 'server', 'hash', 'client')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO slot_spin_history (id, player_id, slot_config_id, bet_amount, win_amount, symbols, fair_seed_id, fair_nonce)
+             VALUES (1, 1, 1, 10, 0, '[]', 1, 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO hold_and_spin_rounds (player_id, slot_config_id, spin_id, total_bet, reel_rows, coins, respins_remaining)
+             VALUES (1, 1, 1, 10, '[3, 3, 3]', '[]', 3)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let reveal = || {
+            reveal_seed(
+                web::Data::new(pool.clone()),
+                web::Json(RevealRequest { player_id: 1 }),
+            )
+        };
+        let err = reveal().await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::CONFLICT
+        );
+
+        sqlx::query("UPDATE hold_and_spin_rounds SET status = 'completed'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let resp = reveal().await.unwrap();
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    }
+
+    #[test]
+    fn test_server_seed_hash_commitment() {
+        let server_seed = generate_server_seed();
+        let hash = hash_server_seed(&server_seed);
+
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_server_seed(&server_seed));
+        assert_ne!(hash, hash_server_seed("tampered"));
+    }
+}
@@ -0,0 +1,385 @@
+use crate::slot_math::{self, MathReport};
+use crate::universal_slots::UniversalSlotMachine;
+use serde::{Deserialize, Serialize};
+
+// 默认允许每个权重在原值的 ±50% 范围内调整
+const DEFAULT_MAX_CHANGE_PERCENT: u32 = 50;
+// 默认RTP容差（绝对值），0.001 即 ±0.1%
+const DEFAULT_RTP_TOLERANCE: f64 = 0.001;
+// 默认最多计算的数学模型次数
+const DEFAULT_MAX_EVALUATIONS: u32 = 2_000;
+
+// 单个卷轴位置的权重范围，按卷轴条中的顺序索引
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct WeightBound {
+    pub reel_number: usize,
+    pub index: usize,
+    pub min_weight: u32,
+    pub max_weight: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SolverParams {
+    pub target_rtp: Option<f64>, // 默认使用配置的 rtp_percentage
+    pub volatility_min: Option<f64>,
+    pub volatility_max: Option<f64>,
+    pub rtp_tolerance: Option<f64>,
+    pub max_change_percent: Option<u32>, // 未单独指定范围的权重允许的变化幅度
+    #[serde(default)]
+    pub bounds: Vec<WeightBound>,
+    pub max_evaluations: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WeightChange {
+    pub reel_number: usize,
+    pub index: usize,
+    pub symbol_id: i32,
+    pub symbol_name: String,
+    pub old_weight: u32,
+    pub new_weight: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MathSummary {
+    pub rtp: f64,
+    pub hit_frequency: f64,
+    pub volatility_index: f64,
+}
+
+impl From<&MathReport> for MathSummary {
+    fn from(report: &MathReport) -> Self {
+        Self {
+            rtp: report.rtp,
+            hit_frequency: report.hit_frequency,
+            volatility_index: report.volatility_index,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SolverResult {
+    pub target_rtp: f64,
+    pub volatility_min: Option<f64>,
+    pub volatility_max: Option<f64>,
+    pub converged: bool, // RTP在容差内且波动指数在范围内
+    pub evaluations: u32,
+    pub before: MathSummary,
+    pub after: MathSummary,
+    pub changes: Vec<WeightChange>, // 只包含权重有变化的位置
+    pub weights: Vec<Vec<u32>>,     // 建议的完整权重表，按卷轴和位置顺序
+    pub report: MathReport,
+}
+
+// 可调整的权重及其范围
+struct Variable {
+    reel: usize,
+    index: usize,
+    min: u32,
+    max: u32,
+}
+
+struct Objective {
+    target_rtp: f64,
+    volatility_min: Option<f64>,
+    volatility_max: Option<f64>,
+    rtp_tolerance: f64,
+}
+
+impl Objective {
+    // 误差：RTP的相对偏差加上波动指数超出范围的相对距离
+    fn error(&self, report: &MathReport) -> f64 {
+        let rtp_error = (report.rtp - self.target_rtp).abs() / self.target_rtp;
+        let volatility = report.volatility_index;
+        let volatility_error = match (self.volatility_min, self.volatility_max) {
+            (Some(min), _) if volatility < min => (min - volatility) / min.max(f64::EPSILON),
+            (_, Some(max)) if volatility > max => (volatility - max) / max.max(f64::EPSILON),
+            _ => 0.0,
+        };
+        rtp_error + volatility_error
+    }
+
+    fn is_satisfied(&self, report: &MathReport) -> bool {
+        (report.rtp - self.target_rtp).abs() <= self.rtp_tolerance
+            && self
+                .volatility_min
+                .filter(|&min| report.volatility_index < min)
+                .is_none()
+            && self
+                .volatility_max
+                .filter(|&max| report.volatility_index > max)
+                .is_none()
+    }
+}
+
+fn variables(
+    machine: &UniversalSlotMachine,
+    params: &SolverParams,
+) -> Result<Vec<Variable>, String> {
+    for bound in &params.bounds {
+        let exists = machine
+            .reel_strips
+            .get(bound.reel_number)
+            .is_some_and(|strip| bound.index < strip.symbols.len());
+        if !exists {
+            return Err(format!(
+                "Bound refers to reel {} position {} which does not exist",
+                bound.reel_number, bound.index
+            ));
+        }
+        if bound.min_weight == 0 {
+            return Err(format!(
+                "Bound for reel {} position {} needs a min_weight of at least 1",
+                bound.reel_number, bound.index
+            ));
+        }
+        if bound.min_weight > bound.max_weight {
+            return Err(format!(
+                "Bound for reel {} position {} has min_weight above max_weight",
+                bound.reel_number, bound.index
+            ));
+        }
+    }
+
+    let change = params
+        .max_change_percent
+        .unwrap_or(DEFAULT_MAX_CHANGE_PERCENT) as u64;
+    let mut variables = Vec::new();
+    for (reel, strip) in machine.reel_strips.iter().enumerate() {
+        for (index, (_, weight)) in strip.symbols.iter().enumerate() {
+            let bound = params
+                .bounds
+                .iter()
+                .find(|b| b.reel_number == reel && b.index == index);
+            let (min, max) = match bound {
+                Some(bound) => (bound.min_weight, bound.max_weight),
+                None => {
+                    let delta = (*weight as u64 * change / 100) as u32;
+                    (
+                        weight.saturating_sub(delta).max(1),
+                        weight.saturating_add(delta),
+                    )
+                }
+            };
+            if min < max {
+                variables.push(Variable {
+                    reel,
+                    index,
+                    min,
+                    max,
+                });
+            }
+        }
+    }
+    Ok(variables)
+}
+
+fn set_weight(machine: &mut UniversalSlotMachine, reel: usize, index: usize, weight: u32) {
+    machine.reel_strips[reel].symbols[index].1 = weight;
+}
+
+fn weight(machine: &UniversalSlotMachine, reel: usize, index: usize) -> u32 {
+    machine.reel_strips[reel].symbols[index].1
+}
+
+// 模式搜索：依次尝试把每个权重增减一个步长，保留使误差下降的调整；
+// 一轮没有改进时步长减半，直到满足目标、步长降到1以下或计算次数用尽
+pub fn solve(
+    machine: &UniversalSlotMachine,
+    params: &SolverParams,
+) -> Result<SolverResult, String> {
+    let objective = Objective {
+        target_rtp: params
+            .target_rtp
+            .unwrap_or(machine.config.rtp_percentage / 100.0),
+        volatility_min: params.volatility_min,
+        volatility_max: params.volatility_max,
+        rtp_tolerance: params.rtp_tolerance.unwrap_or(DEFAULT_RTP_TOLERANCE),
+    };
+    if objective.target_rtp <= 0.0 {
+        return Err("target_rtp must be greater than 0".to_string());
+    }
+    let max_evaluations = params.max_evaluations.unwrap_or(DEFAULT_MAX_EVALUATIONS);
+
+    let variables = variables(machine, params)?;
+    let original = slot_math::analyze(machine)?;
+    let mut evaluations = 1;
+
+    // 先把超出指定范围的权重移到范围内
+    let mut candidate = machine.clone();
+    let mut clamped = false;
+    for bound in &params.bounds {
+        let current = weight(&candidate, bound.reel_number, bound.index);
+        let next = current.clamp(bound.min_weight, bound.max_weight);
+        set_weight(&mut candidate, bound.reel_number, bound.index, next);
+        clamped |= next != current;
+    }
+    let mut best = if !clamped {
+        original.clone()
+    } else {
+        evaluations += 1;
+        slot_math::analyze(&candidate)?
+    };
+    let mut best_error = objective.error(&best);
+
+    // 初始步长为最大可调范围的四分之一
+    let mut step = variables
+        .iter()
+        .map(|v| (v.max - v.min) / 4)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    'search: while !objective.is_satisfied(&best) && !variables.is_empty() {
+        let mut improved = false;
+        for variable in &variables {
+            let current = weight(&candidate, variable.reel, variable.index);
+            let moves = [
+                current.saturating_add(step).min(variable.max),
+                current.saturating_sub(step).max(variable.min),
+            ];
+            for next in moves.into_iter().filter(|&next| next != current) {
+                if evaluations >= max_evaluations {
+                    break 'search;
+                }
+                set_weight(&mut candidate, variable.reel, variable.index, next);
+                evaluations += 1;
+                // 免费旋转永不结束等无效组合直接跳过
+                if let Ok(report) = slot_math::analyze(&candidate) {
+                    let error = objective.error(&report);
+                    if error < best_error {
+                        best = report;
+                        best_error = error;
+                        improved = true;
+                        break;
+                    }
+                }
+                set_weight(&mut candidate, variable.reel, variable.index, current);
+            }
+            if objective.is_satisfied(&best) {
+                break 'search;
+            }
+        }
+        if !improved {
+            if step == 1 {
+                break;
+            }
+            step /= 2;
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (reel, strip) in machine.reel_strips.iter().enumerate() {
+        for (index, (symbol, old_weight)) in strip.symbols.iter().enumerate() {
+            let new_weight = weight(&candidate, reel, index);
+            if new_weight != *old_weight {
+                changes.push(WeightChange {
+                    reel_number: reel,
+                    index,
+                    symbol_id: symbol.id,
+                    symbol_name: symbol.name.clone(),
+                    old_weight: *old_weight,
+                    new_weight,
+                });
+            }
+        }
+    }
+
+    Ok(SolverResult {
+        target_rtp: objective.target_rtp,
+        volatility_min: objective.volatility_min,
+        volatility_max: objective.volatility_max,
+        converged: objective.is_satisfied(&best),
+        evaluations,
+        before: MathSummary::from(&original),
+        after: MathSummary::from(&best),
+        changes,
+        weights: candidate
+            .reel_strips
+            .iter()
+            .map(|strip| strip.symbols.iter().map(|(_, weight)| *weight).collect())
+            .collect(),
+        report: best,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::universal_slots::{Payline, ReelStrip, SlotConfig, SlotSymbol, SymbolType};
+
+    // 3x3，单条中线；A 3连赔10，B 3连赔5，C不赔付
+    fn machine() -> UniversalSlotMachine {
+        let symbol = |id, name: &str, payout| SlotSymbol {
+            id,
+            name: name.to_string(),
+            symbol_type: SymbolType::Normal,
+            value: id as u32,
+            image_url: None,
+            payouts: [(3, payout)].into_iter().collect(),
+        };
+        let symbols = vec![symbol(1, "A", 10), symbol(2, "B", 5), symbol(3, "C", 0)];
+        let reel_strips = (0..3)
+            .map(|reel_number| ReelStrip {
+                reel_number,
+                symbols: vec![
+                    (symbols[0].clone(), 20),
+                    (symbols[1].clone(), 20),
+                    (symbols[2].clone(), 20),
+                ],
+            })
+            .collect();
+        let config = SlotConfig {
+            id: 1,
+            name: "Solver".to_string(),
+            rows: 3,
+            reels: 3,
+            is_megaway: false,
+            min_megaway_rows: 2,
+            max_megaway_rows: 7,
+            default_bet: 1,
+            min_bet: 1,
+            max_bet: 100,
+            wild_enabled: true,
+            free_spins_enabled: false,
+            free_spins_multiplier: 1,
+            reel_mode: Default::default(),
+            evaluation_mode: Default::default(),
+            cascading: false,
+            cascade_multipliers: Vec::new(),
+            rtp_percentage: 40.0,
+        };
+        let payline = Payline {
+            line_number: 1,
+            pattern: vec![(0, 1), (1, 1), (2, 1)],
+            is_active: true,
+        };
+        UniversalSlotMachine::new(config, symbols, reel_strips, vec![payline])
+    }
+
+    #[test]
+    fn test_solver_hits_configured_rtp_within_bounds() {
+        let machine = machine();
+        // 初始RTP = (1/3)^3 x 15 ≈ 0.556，目标为配置的40%
+        let params = SolverParams {
+            bounds: vec![WeightBound {
+                reel_number: 0,
+                index: 2,
+                min_weight: 20,
+                max_weight: 20,
+            }],
+            ..Default::default()
+        };
+        let result = solve(&machine, &params).unwrap();
+
+        assert!(result.before.rtp > 0.55);
+        assert!(result.converged);
+        assert!((result.after.rtp - 0.4).abs() <= DEFAULT_RTP_TOLERANCE);
+        assert!(result.evaluations <= DEFAULT_MAX_EVALUATIONS);
+        for change in &result.changes {
+            assert_ne!((change.reel_number, change.index), (0, 2));
+            assert!((10..=30).contains(&change.new_weight));
+        }
+        assert_eq!(result.weights[0][2], 20, "bounded position must stay fixed");
+    }
+}
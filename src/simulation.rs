@@ -0,0 +1,587 @@
+use crate::universal_slots::{BetMode, HeldWild, UniversalSlotMachine};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+
+// 单次免费旋转特性的最大旋转次数，防止再次触发率过高的配置无限循环
+const MAX_FEATURE_SPINS: u32 = 10_000;
+// 每个线程每完成这么多次旋转更新一次进度并检查是否取消
+const PROGRESS_INTERVAL: u64 = 10_000;
+// RTP置信区间使用的z值（95%）
+const CONFIDENCE_Z: f64 = 1.96;
+// 赢金分布直方图的区间下界（总投注的倍数），0单独统计未中奖的旋转
+const HISTOGRAM_BOUNDS: [f64; 11] = [
+    0.0, 0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 500.0, 1000.0,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationParams {
+    pub spins: u64,
+    pub threads: Option<usize>, // 默认使用全部CPU核心
+    pub seed: Option<u64>,      // 相同种子和线程数得到相同结果
+    #[serde(default)]
+    pub bet_mode: BetMode, // 模拟加注或购买免费旋转时，RTP按实际收取的金额计算
+}
+
+// 运行中的模拟：进度计数和取消标记，可跨线程共享
+#[derive(Debug, Default)]
+pub struct SimulationControl {
+    pub spins_done: AtomicU64,
+    pub cancelled: AtomicBool,
+}
+
+impl SimulationControl {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn spins_done(&self) -> u64 {
+        self.spins_done.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBucket {
+    pub min_multiplier: f64,
+    pub max_multiplier: Option<f64>, // 不含上界，最后一个区间为空
+    pub count: u64,
+    pub frequency: f64,
+}
+
+// 所有比例均相对于每次旋转收取的金额（标准旋转即总投注）；
+// 一次旋转的赢金包含其触发的免费旋转、选宝游戏和Hold and Spin
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationReport {
+    pub slot_config_id: i32,
+    pub bet_mode: BetMode,
+    pub bet_cost: f64, // 每次旋转收取的金额，总投注的倍数
+    pub spins: u64,
+    pub threads: usize,
+    pub seed: u64,
+    pub elapsed_ms: u128,
+    pub rtp: f64,
+    pub rtp_confidence_low: f64,
+    pub rtp_confidence_high: f64,
+    pub base_game_rtp: f64,
+    pub free_spins_rtp: f64,
+    pub bonus_rtp: f64,
+    pub hold_and_spin_rtp: f64,
+    pub hit_frequency: f64,
+    pub standard_deviation: f64,
+    pub max_win_multiplier: f64,
+    pub free_spins_trigger_rate: f64,
+    pub bonus_trigger_rate: f64,
+    pub hold_and_spin_trigger_rate: f64,
+    pub average_free_spins: f64, // 每次触发平均进行的免费旋转次数（含再次触发）
+    pub longest_losing_streak: u64,
+    pub histogram: Vec<HistogramBucket>,
+    pub configured_rtp: f64,
+}
+
+// 单个线程的统计，按线程顺序合并（连续未中奖需要跨线程拼接）
+#[derive(Debug, Clone, Default)]
+struct Tally {
+    spins: u64,
+    total_win: u64,
+    base_win: u64,
+    free_spins_win: u64,
+    bonus_win: u64,
+    hold_and_spin_win: u64,
+    sum_squares: u128,
+    hits: u64,
+    max_win: u64,
+    free_spins_triggers: u64,
+    free_spins_played: u64,
+    bonus_triggers: u64,
+    hold_and_spin_triggers: u64,
+    histogram: [u64; HISTOGRAM_BOUNDS.len()],
+    leading_losses: u64,
+    trailing_losses: u64,
+    longest_losses: u64,
+}
+
+impl Tally {
+    fn record(&mut self, round: &Round, total_bet: u64) {
+        let win = round.base_win + round.free_spins_win + round.bonus_win + round.hold_and_spin_win;
+        self.spins += 1;
+        self.total_win += win;
+        self.base_win += round.base_win;
+        self.free_spins_win += round.free_spins_win;
+        self.bonus_win += round.bonus_win;
+        self.hold_and_spin_win += round.hold_and_spin_win;
+        self.sum_squares += win as u128 * win as u128;
+        self.max_win = self.max_win.max(win);
+        self.histogram[histogram_bucket(win, total_bet)] += 1;
+
+        if round.free_spins_played > 0 {
+            self.free_spins_triggers += 1;
+            self.free_spins_played += round.free_spins_played as u64;
+        }
+        if round.bonus_triggered {
+            self.bonus_triggers += 1;
+        }
+        if round.hold_and_spin_triggered {
+            self.hold_and_spin_triggers += 1;
+        }
+
+        if win > 0 {
+            self.hits += 1;
+            self.trailing_losses = 0;
+        } else {
+            if self.hits == 0 {
+                self.leading_losses += 1;
+            }
+            self.trailing_losses += 1;
+            self.longest_losses = self.longest_losses.max(self.trailing_losses);
+        }
+    }
+
+    fn merge(mut self, next: Tally) -> Tally {
+        let bridged = self.trailing_losses + next.leading_losses;
+        self.longest_losses = self.longest_losses.max(next.longest_losses).max(bridged);
+        if self.hits == 0 {
+            self.leading_losses += next.leading_losses;
+        }
+        self.trailing_losses = if next.hits == 0 {
+            bridged
+        } else {
+            next.trailing_losses
+        };
+
+        self.spins += next.spins;
+        self.total_win += next.total_win;
+        self.base_win += next.base_win;
+        self.free_spins_win += next.free_spins_win;
+        self.bonus_win += next.bonus_win;
+        self.hold_and_spin_win += next.hold_and_spin_win;
+        self.sum_squares += next.sum_squares;
+        self.hits += next.hits;
+        self.max_win = self.max_win.max(next.max_win);
+        self.free_spins_triggers += next.free_spins_triggers;
+        self.free_spins_played += next.free_spins_played;
+        self.bonus_triggers += next.bonus_triggers;
+        self.hold_and_spin_triggers += next.hold_and_spin_triggers;
+        for (count, other) in self.histogram.iter_mut().zip(next.histogram) {
+            *count += other;
+        }
+        self
+    }
+}
+
+// 一次付费旋转及其触发的全部特性
+struct Round {
+    base_win: u64,
+    free_spins_win: u64,
+    free_spins_played: u32,
+    bonus_win: u64,
+    bonus_triggered: bool,
+    hold_and_spin_win: u64,
+    hold_and_spin_triggered: bool,
+}
+
+fn histogram_bucket(win: u64, total_bet: u64) -> usize {
+    if win == 0 {
+        return 0;
+    }
+    let multiplier = win as f64 / total_bet as f64;
+    HISTOGRAM_BOUNDS
+        .iter()
+        .rposition(|&bound| multiplier >= bound)
+        .unwrap_or(1)
+        .max(1)
+}
+
+// 与线上流程一致：免费旋转使用触发时的投注，应用配置的赢金倍数并保留粘性/移动Wild；
+// 基础旋转触发的选宝游戏随机生成面板并揭开 picks 个位置，Hold and Spin进行到结束
+fn play_round<R: Rng + ?Sized>(
+    machine: &UniversalSlotMachine,
+    bet_per_line: u32,
+    total_bet: u64,
+    bet_mode: BetMode,
+    rng: &mut R,
+) -> Round {
+    let result = machine.spin_with_rng_mode(bet_per_line, bet_mode, rng);
+    let mut round = Round {
+        base_win: result.total_win as u64,
+        free_spins_win: 0,
+        free_spins_played: 0,
+        bonus_win: 0,
+        bonus_triggered: false,
+        hold_and_spin_win: 0,
+        hold_and_spin_triggered: false,
+    };
+
+    let mut remaining = result.free_spins;
+    let mut held_wilds: Vec<HeldWild> = Vec::new();
+    let win_multiplier = machine.config.free_spins_multiplier.max(1) as u64;
+    while remaining > 0 && round.free_spins_played < MAX_FEATURE_SPINS {
+        let free = machine.spin_free_seeded(bet_per_line, rng.random(), &held_wilds);
+        round.free_spins_win += free.total_win as u64 * win_multiplier;
+        round.free_spins_played += 1;
+        remaining = remaining - 1 + free.free_spins;
+        held_wilds = free.held_wilds;
+    }
+
+    if let (true, Some(bonus_game)) = (result.bonus_triggered, &machine.bonus_game) {
+        let board = bonus_game.generate_board(rng.random());
+        let picks = (bonus_game.picks as usize).min(board.len());
+        let multiplier: u64 = board[..picks].iter().map(|&m| m as u64).sum();
+        round.bonus_triggered = true;
+        round.bonus_win = multiplier * total_bet;
+    }
+
+    if let Some(mut state) = machine.start_hold_and_spin(&result) {
+        while !state.is_complete() {
+            machine.respin_seeded(&mut state, rng.random());
+        }
+        round.hold_and_spin_triggered = true;
+        round.hold_and_spin_win = state.total_multiplier() * total_bet;
+    }
+
+    round
+}
+
+fn run_chunk(
+    machine: &UniversalSlotMachine,
+    bet_mode: BetMode,
+    spins: u64,
+    seed: u64,
+    control: &SimulationControl,
+) -> Tally {
+    let bet_per_line = 1;
+    let total_bet = machine.total_bet(bet_per_line);
+    let stake = machine
+        .bet_cost(bet_per_line, bet_mode)
+        .unwrap_or(total_bet);
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let mut tally = Tally::default();
+
+    let mut reported = 0;
+    for i in 0..spins {
+        if i - reported == PROGRESS_INTERVAL {
+            control
+                .spins_done
+                .fetch_add(i - reported, Ordering::Relaxed);
+            reported = i;
+            if control.is_cancelled() {
+                break;
+            }
+        }
+        let round = play_round(machine, bet_per_line, total_bet, bet_mode, &mut rng);
+        tally.record(&round, stake);
+    }
+    control
+        .spins_done
+        .fetch_add(tally.spins - reported, Ordering::Relaxed);
+
+    tally
+}
+
+// 多线程蒙特卡洛模拟：总旋转次数平均分配到各线程，每个线程使用由种子派生的独立随机数流
+pub fn simulate(
+    machine: &UniversalSlotMachine,
+    params: &SimulationParams,
+    control: &SimulationControl,
+) -> Result<SimulationReport, String> {
+    if params.spins == 0 {
+        return Err("spins must be greater than 0".to_string());
+    }
+    // 基础卷轴和所有卷轴组都可能被抽到（基础游戏的备选卷轴、免费旋转、投注方式）
+    let reel_sets = std::iter::once(&machine.reel_strips).chain(
+        machine
+            .reel_sets
+            .values()
+            .map(|reel_set| &reel_set.reel_strips),
+    );
+    for reel_strips in reel_sets {
+        if reel_strips.len() != machine.config.reels {
+            return Err(format!(
+                "Expected {} reels, found {}",
+                machine.config.reels,
+                reel_strips.len()
+            ));
+        }
+        if let Some(strip) = reel_strips
+            .iter()
+            .find(|strip| strip.symbols.iter().all(|(_, weight)| *weight == 0))
+        {
+            return Err(format!(
+                "Reel {} has no weighted symbols",
+                strip.reel_number
+            ));
+        }
+    }
+    let total_bet = machine.total_bet(1);
+    if total_bet == 0 {
+        return Err("Slot config has no active paylines".to_string());
+    }
+    let stake = machine.bet_cost(1, params.bet_mode).ok_or_else(|| {
+        format!(
+            "Slot config has no {} option",
+            params.bet_mode.as_str().replace('_', " ")
+        )
+    })?;
+
+    let threads = params
+        .threads
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+        .clamp(1, params.spins.min(256) as usize);
+    let seed = params.seed.unwrap_or_else(|| rand::rng().random());
+
+    let started = Instant::now();
+    // 等待所有线程结束后再处理结果，某个线程 panic 时返回错误而不是让整个任务崩溃
+    let tallies: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads as u64)
+            .map(|i| {
+                let spins =
+                    params.spins / threads as u64 + u64::from(i < params.spins % threads as u64);
+                let thread_seed = seed.wrapping_add(i.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                let bet_mode = params.bet_mode;
+                scope.spawn(move || run_chunk(machine, bet_mode, spins, thread_seed, control))
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join()).collect()
+    });
+    let tally = tallies
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|panic| format!("Simulation thread panicked: {}", panic_message(&*panic)))?
+        .into_iter()
+        .reduce(Tally::merge)
+        .unwrap_or_default();
+
+    if control.is_cancelled() {
+        return Err("Simulation was cancelled".to_string());
+    }
+
+    Ok(report(
+        machine,
+        tally,
+        params.bet_mode,
+        (total_bet, stake),
+        threads,
+        seed,
+        started,
+    ))
+}
+
+pub fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+fn report(
+    machine: &UniversalSlotMachine,
+    tally: Tally,
+    bet_mode: BetMode,
+    (total_bet, stake): (u64, u64),
+    threads: usize,
+    seed: u64,
+    started: Instant,
+) -> SimulationReport {
+    let spins = tally.spins as f64;
+    let bet = stake as f64;
+    let staked = spins * bet;
+
+    // 以总投注为单位的每次旋转赢金：均值即RTP，标准差用于置信区间
+    let rtp = tally.total_win as f64 / staked;
+    let mean_square = tally.sum_squares as f64 / (spins * bet * bet);
+    let variance = (mean_square - rtp * rtp).max(0.0);
+    let standard_deviation = variance.sqrt();
+    let margin = CONFIDENCE_Z * standard_deviation / spins.sqrt();
+
+    let histogram = tally
+        .histogram
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| HistogramBucket {
+            min_multiplier: HISTOGRAM_BOUNDS[i],
+            max_multiplier: match i {
+                0 => Some(0.0),
+                _ => HISTOGRAM_BOUNDS.get(i + 1).copied(),
+            },
+            count,
+            frequency: count as f64 / spins,
+        })
+        .collect();
+
+    SimulationReport {
+        slot_config_id: machine.config.id,
+        bet_mode,
+        bet_cost: stake as f64 / total_bet as f64,
+        spins: tally.spins,
+        threads,
+        seed,
+        elapsed_ms: started.elapsed().as_millis(),
+        rtp,
+        rtp_confidence_low: rtp - margin,
+        rtp_confidence_high: rtp + margin,
+        base_game_rtp: tally.base_win as f64 / staked,
+        free_spins_rtp: tally.free_spins_win as f64 / staked,
+        bonus_rtp: tally.bonus_win as f64 / staked,
+        hold_and_spin_rtp: tally.hold_and_spin_win as f64 / staked,
+        hit_frequency: tally.hits as f64 / spins,
+        standard_deviation,
+        max_win_multiplier: tally.max_win as f64 / bet,
+        free_spins_trigger_rate: tally.free_spins_triggers as f64 / spins,
+        bonus_trigger_rate: tally.bonus_triggers as f64 / spins,
+        hold_and_spin_trigger_rate: tally.hold_and_spin_triggers as f64 / spins,
+        average_free_spins: if tally.free_spins_triggers > 0 {
+            tally.free_spins_played as f64 / tally.free_spins_triggers as f64
+        } else {
+            0.0
+        },
+        longest_losing_streak: tally.longest_losses,
+        histogram,
+        configured_rtp: machine.config.rtp_percentage / 100.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slot_math;
+    use crate::universal_slots::{
+        Payline, ReelStrip, ScatterTrigger, SlotConfig, SlotSymbol, SymbolType,
+    };
+
+    fn round(win: u64) -> Round {
+        Round {
+            base_win: win,
+            free_spins_win: 0,
+            free_spins_played: 0,
+            bonus_win: 0,
+            bonus_triggered: false,
+            hold_and_spin_win: 0,
+            hold_and_spin_triggered: false,
+        }
+    }
+
+    #[test]
+    fn test_losing_streak_spans_thread_chunks() {
+        let wins = [0, 0, 3, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 2, 0];
+        let tally_of = |wins: &[u64]| {
+            let mut tally = Tally::default();
+            for &win in wins {
+                tally.record(&round(win), 1);
+            }
+            tally
+        };
+        let whole = tally_of(&wins);
+        assert_eq!(whole.longest_losses, 5);
+
+        for split in [(1, 4), (4, 6), (5, 12), (9, 10)] {
+            let merged = [&wins[..split.0], &wins[split.0..split.1], &wins[split.1..]]
+                .into_iter()
+                .map(tally_of)
+                .reduce(Tally::merge)
+                .unwrap();
+            assert_eq!(merged.longest_losses, whole.longest_losses);
+            assert_eq!(merged.leading_losses, whole.leading_losses);
+            assert_eq!(merged.trailing_losses, whole.trailing_losses);
+            assert_eq!(merged.histogram, whole.histogram);
+        }
+    }
+
+    // 3x3，单条中线，3个Scatter奖励免费旋转
+    fn machine() -> UniversalSlotMachine {
+        let symbol = |id, name: &str, symbol_type, payouts: &[(usize, u32)]| SlotSymbol {
+            id,
+            name: name.to_string(),
+            symbol_type,
+            value: id as u32,
+            image_url: None,
+            payouts: payouts.iter().copied().collect(),
+        };
+        let symbols = vec![
+            symbol(1, "A", SymbolType::Normal, &[(3, 10)]),
+            symbol(2, "B", SymbolType::Normal, &[(3, 5)]),
+            symbol(3, "Scatter", SymbolType::Scatter, &[(3, 2)]),
+        ];
+        let reel_strips = (0..3)
+            .map(|reel_number| ReelStrip {
+                reel_number,
+                symbols: vec![
+                    (symbols[0].clone(), 4),
+                    (symbols[1].clone(), 6),
+                    (symbols[2].clone(), 1),
+                ],
+            })
+            .collect();
+        let config = SlotConfig {
+            id: 1,
+            name: "Simulation".to_string(),
+            rows: 3,
+            reels: 3,
+            is_megaway: false,
+            min_megaway_rows: 2,
+            max_megaway_rows: 7,
+            default_bet: 1,
+            min_bet: 1,
+            max_bet: 100,
+            wild_enabled: true,
+            free_spins_enabled: true,
+            free_spins_multiplier: 2,
+            reel_mode: Default::default(),
+            evaluation_mode: Default::default(),
+            cascading: false,
+            cascade_multipliers: Vec::new(),
+            rtp_percentage: 96.0,
+        };
+        let payline = Payline {
+            line_number: 1,
+            pattern: vec![(0, 1), (1, 1), (2, 1)],
+            is_active: true,
+        };
+        let mut machine = UniversalSlotMachine::new(config, symbols, reel_strips, vec![payline]);
+        machine.scatter_triggers = vec![ScatterTrigger {
+            scatter_count: 3,
+            free_spins: 5,
+        }];
+        machine
+    }
+
+    #[test]
+    fn test_simulation_converges_to_exact_rtp() {
+        let machine = machine();
+        let params = SimulationParams {
+            spins: 100_000,
+            threads: Some(4),
+            seed: Some(42),
+            bet_mode: BetMode::Standard,
+        };
+
+        let report = simulate(&machine, &params, &SimulationControl::default()).unwrap();
+        let again = simulate(&machine, &params, &SimulationControl::default()).unwrap();
+        assert_eq!(report.spins, 100_000);
+        assert_eq!(report.rtp, again.rtp);
+        assert_eq!(report.longest_losing_streak, again.longest_losing_streak);
+        assert_eq!(
+            report.histogram.iter().map(|b| b.count).sum::<u64>(),
+            report.spins
+        );
+        assert!(report.free_spins_trigger_rate > 0.0);
+
+        let exact = slot_math::analyze(&machine).unwrap();
+        let margin = report.rtp_confidence_high - report.rtp;
+        assert!(
+            (report.rtp - exact.rtp).abs() < 2.0 * margin,
+            "simulated {} vs exact {}",
+            report.rtp,
+            exact.rtp
+        );
+    }
+}
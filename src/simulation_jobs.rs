@@ -0,0 +1,335 @@
+use crate::api_error::ApiError;
+use crate::slot_config_api::build_playable_machine;
+use actix_web::{web, HttpResponse, Result};
+use saber::simulation::{self, SimulationControl, SimulationParams, SimulationReport};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+
+// 单个任务允许的最大旋转次数
+const MAX_SIMULATION_SPINS: u64 = 1_000_000_000;
+// 同时排队或运行的任务数上限
+const MAX_ACTIVE_JOBS: usize = 4;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug)]
+pub struct SimulationJob {
+    pub id: u64,
+    pub slot_config_id: i32,
+    pub params: SimulationParams,
+    pub status: JobStatus,
+    pub control: Arc<SimulationControl>,
+    pub report: Option<SimulationReport>,
+    pub error: Option<String>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl SimulationJob {
+    fn to_json(&self) -> serde_json::Value {
+        let spins_done = self.control.spins_done().min(self.params.spins);
+        serde_json::json!({
+            "id": self.id,
+            "slot_config_id": self.slot_config_id,
+            "status": self.status,
+            "spins": self.params.spins,
+            "spins_done": spins_done,
+            "progress": spins_done as f64 / self.params.spins as f64,
+            "started_at": self.started_at,
+            "report": self.report,
+            "error": self.error
+        })
+    }
+}
+
+// 模拟任务保存在内存中，服务重启后丢失。
+// 所有任务共用一组模拟线程（数量为CPU核心数），线程不足时任务排队等待
+#[derive(Debug)]
+pub struct SimulationJobs {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<u64, SimulationJob>>,
+    workers: Arc<Semaphore>,
+    worker_count: usize,
+}
+
+impl Default for SimulationJobs {
+    fn default() -> Self {
+        let worker_count = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        Self::with_workers(worker_count)
+    }
+}
+
+impl SimulationJobs {
+    fn with_workers(worker_count: usize) -> Self {
+        Self {
+            next_id: AtomicU64::new(0),
+            jobs: Mutex::new(HashMap::new()),
+            workers: Arc::new(Semaphore::new(worker_count)),
+            worker_count,
+        }
+    }
+
+    // 登记新任务，排队或运行中的任务达到上限时拒绝
+    fn admit(
+        &self,
+        slot_config_id: i32,
+        params: SimulationParams,
+    ) -> Result<(u64, serde_json::Value), ApiError> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let active = jobs
+            .values()
+            .filter(|job| matches!(job.status, JobStatus::Queued | JobStatus::Running))
+            .count();
+        if active >= MAX_ACTIVE_JOBS {
+            return Err(ApiError::Conflict(format!(
+                "At most {MAX_ACTIVE_JOBS} simulations can be queued or running, cancel one or wait for it to finish"
+            )));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = SimulationJob {
+            id,
+            slot_config_id,
+            params,
+            status: JobStatus::Queued,
+            control: Arc::new(SimulationControl::default()),
+            report: None,
+            error: None,
+            started_at: chrono::Utc::now(),
+        };
+        let body = job.to_json();
+        jobs.insert(id, job);
+        Ok((id, body))
+    }
+
+    // 等待足够的模拟线程后运行任务；run panic 时任务标记为失败
+    async fn run<F>(self: Arc<Self>, id: u64, threads: usize, run: F)
+    where
+        F: FnOnce(&SimulationControl) -> Result<SimulationReport, String> + Send + 'static,
+    {
+        let _permit = self
+            .workers
+            .clone()
+            .acquire_many_owned(threads as u32)
+            .await
+            .expect("simulation worker pool is never closed");
+
+        let control = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let Some(job) = jobs.get_mut(&id) else {
+                return;
+            };
+            // 排队时已被取消
+            if job.status != JobStatus::Queued {
+                return;
+            }
+            job.status = JobStatus::Running;
+            job.control.clone()
+        };
+
+        let outcome = tokio::task::spawn_blocking(move || run(&control))
+            .await
+            .unwrap_or_else(|e| match e.try_into_panic() {
+                Ok(panic) => Err(format!(
+                    "Simulation panicked: {}",
+                    simulation::panic_message(&*panic)
+                )),
+                Err(e) => Err(format!("Simulation task failed: {e}")),
+            });
+        self.finish(id, outcome);
+    }
+
+    fn finish(&self, id: u64, outcome: Result<SimulationReport, String>) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(job) = jobs.get_mut(&id) else {
+            return;
+        };
+        match outcome {
+            Ok(report) => {
+                job.status = JobStatus::Completed;
+                job.report = Some(report);
+            }
+            Err(_) if job.control.is_cancelled() => job.status = JobStatus::Cancelled,
+            Err(e) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(e);
+            }
+        }
+    }
+}
+
+// 启动后台模拟任务，立即返回任务ID，通过 /simulations/{job_id} 查询进度和报告
+pub async fn start_simulation(
+    pool: web::Data<Pool<Postgres>>,
+    jobs: web::Data<SimulationJobs>,
+    path: web::Path<i32>,
+    req: web::Json<SimulationParams>,
+) -> Result<HttpResponse> {
+    let params = req.into_inner();
+    if params.spins == 0 || params.spins > MAX_SIMULATION_SPINS {
+        return Err(ApiError::BadRequest(format!(
+            "spins must be between 1 and {MAX_SIMULATION_SPINS}"
+        ))
+        .into());
+    }
+
+    let machine = build_playable_machine(pool.get_ref(), path.into_inner()).await?;
+
+    // 每个任务最多使用全部模拟线程
+    let threads = params
+        .threads
+        .unwrap_or(jobs.worker_count)
+        .clamp(1, jobs.worker_count);
+    let params = SimulationParams {
+        threads: Some(threads),
+        ..params
+    };
+    let (id, body) = jobs.admit(machine.config.id, params.clone())?;
+
+    tokio::spawn(jobs.into_inner().run(id, threads, move |control| {
+        simulation::simulate(&machine, &params, control)
+    }));
+
+    Ok(HttpResponse::Accepted().json(body))
+}
+
+pub async fn list_simulations(jobs: web::Data<SimulationJobs>) -> Result<HttpResponse> {
+    let jobs = jobs.jobs.lock().unwrap();
+    let mut list: Vec<_> = jobs.values().collect();
+    list.sort_by_key(|job| job.id);
+
+    Ok(HttpResponse::Ok().json(
+        list.iter()
+            .map(|job| {
+                serde_json::json!({
+                    "id": job.id,
+                    "slot_config_id": job.slot_config_id,
+                    "status": job.status,
+                    "spins": job.params.spins,
+                    "spins_done": job.control.spins_done().min(job.params.spins),
+                    "started_at": job.started_at
+                })
+            })
+            .collect::<Vec<_>>(),
+    ))
+}
+
+pub async fn get_simulation(
+    jobs: web::Data<SimulationJobs>,
+    path: web::Path<u64>,
+) -> Result<HttpResponse> {
+    let jobs = jobs.jobs.lock().unwrap();
+    let job = jobs
+        .get(&path.into_inner())
+        .ok_or_else(|| ApiError::NotFound("Simulation job not found".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(job.to_json()))
+}
+
+// 取消排队或运行中的任务；已结束的任务从列表中删除
+pub async fn cancel_simulation(
+    jobs: web::Data<SimulationJobs>,
+    path: web::Path<u64>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let mut jobs = jobs.jobs.lock().unwrap();
+    let job = jobs
+        .get_mut(&id)
+        .ok_or_else(|| ApiError::NotFound("Simulation job not found".to_string()))?;
+
+    if matches!(job.status, JobStatus::Queued | JobStatus::Running) {
+        job.control.cancel();
+        // 排队中的任务不会再运行，直接标记为已取消
+        if job.status == JobStatus::Queued {
+            job.status = JobStatus::Cancelled;
+        }
+        return Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Simulation cancelled"
+        })));
+    }
+
+    jobs.remove(&id);
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Simulation deleted"
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> SimulationParams {
+        SimulationParams {
+            spins: 1,
+            threads: Some(1),
+            seed: None,
+            bet_mode: Default::default(),
+        }
+    }
+
+    fn status(jobs: &SimulationJobs, id: u64) -> JobStatus {
+        jobs.jobs.lock().unwrap()[&id].status
+    }
+
+    #[test]
+    fn test_active_jobs_are_limited() {
+        let jobs = SimulationJobs::with_workers(1);
+        let ids: Vec<u64> = (0..MAX_ACTIVE_JOBS)
+            .map(|_| jobs.admit(1, params()).unwrap().0)
+            .collect();
+        assert!(matches!(
+            jobs.admit(1, params()),
+            Err(ApiError::Conflict(_))
+        ));
+
+        jobs.finish(ids[0], Err("failed".to_string()));
+        assert!(jobs.admit(1, params()).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_panicking_simulation_is_marked_failed() {
+        let jobs = Arc::new(SimulationJobs::with_workers(1));
+        let (id, _) = jobs.admit(1, params()).unwrap();
+
+        jobs.clone()
+            .run(id, 1, |_| panic!("reel strip exploded"))
+            .await;
+
+        assert_eq!(status(&jobs, id), JobStatus::Failed);
+        let error = jobs.jobs.lock().unwrap()[&id].error.clone().unwrap();
+        assert!(error.contains("reel strip exploded"), "{error}");
+        // panic 后线程归还给线程池
+        assert_eq!(jobs.workers.available_permits(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_job_cancelled_while_queued_never_runs() {
+        let jobs = Arc::new(SimulationJobs::with_workers(1));
+        let (id, _) = jobs.admit(1, params()).unwrap();
+        {
+            let mut all = jobs.jobs.lock().unwrap();
+            let job = all.get_mut(&id).unwrap();
+            job.control.cancel();
+            job.status = JobStatus::Cancelled;
+        }
+
+        jobs.clone()
+            .run(id, 1, |_| panic!("cancelled jobs must not run"))
+            .await;
+
+        assert_eq!(status(&jobs, id), JobStatus::Cancelled);
+    }
+}
@@ -1,7 +1,24 @@
-use crate::universal_slots::{Payline, SlotConfig, SlotConfigBuilder, SlotSymbol, SymbolType};
+use crate::api_error::ApiError;
+use crate::bonus_game::{self, BonusRound};
+use crate::config_versions::{load_live_machine, load_machine_at};
+use crate::free_spins::{self, FreeSpinFeature};
+use crate::hold_and_spin::{self, HoldAndSpinRound};
+use crate::provably_fair::{self, FairProof};
+use crate::rtp_solver::{self, SolverParams, WeightBound};
+use crate::slot_math;
+use crate::spin_history::{record_spin, SpinRecord};
+use crate::universal_slots::{
+    AnteBetConfig, BetMode, BetRequest, BonusBuyConfig, BonusGameConfig, BonusPrize,
+    EvaluationMode, HeldWild, HoldAndSpinConfig, HoldAndSpinState, JackpotTier, MoneyValue,
+    Payline, ReelMode, ScatterTrigger, SlotConfig, SlotConfigBuilder, SlotSymbol, SymbolType,
+    UniversalSlotMachine, UniversalSpinResult, WildFeature, ANTE_REEL_SET, BASE_REEL_SET,
+    BONUS_BUY_REEL_SET, FREE_SPINS_REEL_SET,
+};
+use crate::wallet;
 use actix_web::{web, HttpResponse, Result};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Postgres};
+use sqlx::{PgConnection, Pool, Postgres};
 use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,6 +34,13 @@ pub struct CreateSlotConfigRequest {
     pub max_bet: i32,
     pub wild_enabled: bool,
     pub free_spins_enabled: bool,
+    pub free_spins_multiplier: Option<i32>,
+    pub reel_mode: Option<String>,       // weighted（默认）或 stepper
+    pub evaluation_mode: Option<String>, // lines（默认）或 cluster
+    #[serde(default)]
+    pub cascading: bool,
+    #[serde(default)]
+    pub cascade_multipliers: Vec<i32>,
     pub rtp_percentage: f64,
 }
 
@@ -33,12 +57,20 @@ pub struct CreateSymbolRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateReelSymbolRequest {
     pub slot_config_id: i32,
+    pub reel_set: Option<String>, // 卷轴组，默认 base
     pub reel_number: i32,
     pub position: i32,
     pub symbol_id: i32,
     pub weight: i32,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetReelSetWeightRequest {
+    pub slot_config_id: i32,
+    pub reel_set: String,
+    pub weight: i32,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreatePaylineRequest {
     pub slot_config_id: i32,
@@ -47,17 +79,178 @@ pub struct CreatePaylineRequest {
     pub is_active: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateScatterTriggerRequest {
+    pub slot_config_id: i32,
+    pub scatter_count: i32,
+    pub free_spins: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetWildFeatureRequest {
+    pub symbol_id: i32,
+    pub multiplier: i32,
+    #[serde(default)]
+    pub cannot_substitute: Vec<i32>,
+    #[serde(default)]
+    pub expanding: bool,
+    #[serde(default)]
+    pub sticky: bool,
+    #[serde(default)]
+    pub walking: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetBonusGameRequest {
+    pub slot_config_id: i32,
+    pub trigger_count: i32,
+    pub picks: i32,
+    pub board_size: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetHoldAndSpinRequest {
+    pub slot_config_id: i32,
+    pub trigger_count: i32,
+    pub respins: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateMoneyValueRequest {
+    pub slot_config_id: i32,
+    pub multiplier: i32,
+    pub jackpot: Option<String>, // mini、minor、major 或 grand
+    pub weight: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetAnteBetRequest {
+    pub slot_config_id: i32,
+    pub cost_percent: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetBonusBuyRequest {
+    pub slot_config_id: i32,
+    pub cost_multiplier: i32,
+    pub free_spins: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetJurisdictionRuleRequest {
+    pub slot_config_id: i32,
+    pub jurisdiction: String,
+    #[serde(default = "default_true")]
+    pub ante_bet_enabled: bool,
+    #[serde(default = "default_true")]
+    pub bonus_buy_enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BetOptionsQuery {
+    pub player_id: Option<i32>, // 按该玩家记录中的地区返回投注方式是否可用
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateBonusPrizeRequest {
+    pub slot_config_id: i32,
+    pub multiplier: i32,
+    pub weight: i32,
+}
+
+// 修改配置：只更新提供的字段
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateSlotConfigRequest {
+    pub name: Option<String>,
+    pub rows: Option<i32>,
+    pub reels: Option<i32>,
+    pub is_megaway: Option<bool>,
+    pub min_megaway_rows: Option<i32>,
+    pub max_megaway_rows: Option<i32>,
+    pub default_bet: Option<i32>,
+    pub min_bet: Option<i32>,
+    pub max_bet: Option<i32>,
+    pub wild_enabled: Option<bool>,
+    pub free_spins_enabled: Option<bool>,
+    pub free_spins_multiplier: Option<i32>,
+    pub reel_mode: Option<String>,
+    pub evaluation_mode: Option<String>,
+    pub cascading: Option<bool>,
+    pub cascade_multipliers: Option<Vec<i32>>,
+    pub rtp_percentage: Option<f64>,
+    pub is_active: Option<bool>, // 停用的配置不能旋转，也不出现在配置列表中
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateSymbolRequest {
+    pub name: Option<String>,
+    pub symbol_type: Option<String>,
+    pub value: Option<i32>,
+    pub image_url: Option<String>,
+    pub payouts: Option<HashMap<i32, i32>>, // 提供时替换整个赔付表
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateReelSymbolRequest {
+    pub symbol_id: Option<i32>,
+    pub weight: Option<i32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReelSetQuery {
+    pub reel_set: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdatePaylineRequest {
+    pub pattern: Option<Vec<(i32, i32)>>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ListSlotConfigsQuery {
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
 // 创建新的slot配置
 pub async fn create_slot_config(
     pool: web::Data<Pool<Postgres>>,
     req: web::Json<CreateSlotConfigRequest>,
 ) -> Result<HttpResponse> {
+    if !matches!(
+        req.reel_mode.as_deref(),
+        None | Some("weighted" | "stepper")
+    ) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "reel_mode must be 'weighted' or 'stepper'"
+        })));
+    }
+    if !matches!(
+        req.evaluation_mode.as_deref(),
+        None | Some("lines" | "cluster")
+    ) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "evaluation_mode must be 'lines' or 'cluster'"
+        })));
+    }
+    if req.cascade_multipliers.iter().any(|&m| m < 1) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "cascade_multipliers must be at least 1"
+        })));
+    }
+
     let result = sqlx::query!(
         r#"
         INSERT INTO slot_configurations 
         (name, rows, reels, is_megaway, min_megaway_rows, max_megaway_rows,
-         default_bet, min_bet, max_bet, wild_enabled, free_spins_enabled, rtp_percentage)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+         default_bet, min_bet, max_bet, wild_enabled, free_spins_enabled, rtp_percentage,
+         free_spins_multiplier, reel_mode, evaluation_mode, cascading, cascade_multipliers)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
         RETURNING id
         "#,
         req.name,
@@ -71,7 +264,12 @@ pub async fn create_slot_config(
         req.max_bet,
         req.wild_enabled,
         req.free_spins_enabled,
-        req.rtp_percentage as f64
+        req.rtp_percentage as f64,
+        req.free_spins_multiplier.unwrap_or(1),
+        req.reel_mode.as_deref().unwrap_or("weighted"),
+        req.evaluation_mode.as_deref().unwrap_or("lines"),
+        req.cascading,
+        &req.cascade_multipliers
     )
     .fetch_one(pool.get_ref())
     .await;
@@ -87,10 +285,22 @@ pub async fn create_slot_config(
     }
 }
 
-// 获取所有slot配置列表
-pub async fn list_slot_configs(pool: web::Data<Pool<Postgres>>) -> Result<HttpResponse> {
+// 获取slot配置列表，include_inactive=true 时包含已停用的配置
+pub async fn list_slot_configs(
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<ListSlotConfigsQuery>,
+) -> Result<HttpResponse> {
     let configs = sqlx::query!(
-        "SELECT * FROM slot_configurations WHERE is_active = true ORDER BY created_at DESC"
+        r#"
+        SELECT id, name, rows, reels, is_megaway, min_megaway_rows, max_megaway_rows,
+               default_bet, min_bet, max_bet, wild_enabled, free_spins_enabled,
+               free_spins_multiplier, reel_mode, evaluation_mode, cascading, cascade_multipliers,
+               rtp_percentage, created_at, updated_at, is_active, live_version_id
+        FROM slot_configurations
+        WHERE is_active = true OR $1
+        ORDER BY created_at DESC
+        "#,
+        query.include_inactive
     )
     .fetch_all(pool.get_ref())
     .await;
@@ -113,8 +323,14 @@ pub async fn list_slot_configs(pool: web::Data<Pool<Postgres>>) -> Result<HttpRe
                         "max_bet": config.max_bet,
                         "wild_enabled": config.wild_enabled,
                         "free_spins_enabled": config.free_spins_enabled,
+                        "free_spins_multiplier": config.free_spins_multiplier,
+                        "reel_mode": config.reel_mode,
+                        "evaluation_mode": config.evaluation_mode,
+                        "cascading": config.cascading,
+                        "cascade_multipliers": config.cascade_multipliers,
                         "rtp_percentage": config.rtp_percentage.map(|r| r.to_string()),
                         "is_active": config.is_active,
+                        "live_version_id": config.live_version_id,
                         "created_at": config.created_at.map(|t| t.to_string()),
                         "updated_at": config.updated_at.map(|t| t.to_string())
                     })
@@ -135,9 +351,19 @@ pub async fn get_slot_config(
 ) -> Result<HttpResponse> {
     let config_id = path.into_inner();
 
-    let config = sqlx::query!("SELECT * FROM slot_configurations WHERE id = $1", config_id)
-        .fetch_one(pool.get_ref())
-        .await;
+    let config = sqlx::query!(
+        r#"
+        SELECT id, name, rows, reels, is_megaway, min_megaway_rows, max_megaway_rows,
+               default_bet, min_bet, max_bet, wild_enabled, free_spins_enabled,
+               free_spins_multiplier, reel_mode, evaluation_mode, cascading, cascade_multipliers,
+               rtp_percentage, created_at, updated_at, is_active, live_version_id
+        FROM slot_configurations
+        WHERE id = $1
+        "#,
+        config_id
+    )
+    .fetch_one(pool.get_ref())
+    .await;
 
     match config {
         Ok(config) => {
@@ -154,8 +380,11 @@ pub async fn get_slot_config(
                 "max_bet": config.max_bet,
                 "wild_enabled": config.wild_enabled,
                 "free_spins_enabled": config.free_spins_enabled,
+                "free_spins_multiplier": config.free_spins_multiplier,
+                "reel_mode": config.reel_mode,
                 "rtp_percentage": config.rtp_percentage.map(|r| r.to_string()),
                 "is_active": config.is_active,
+                "live_version_id": config.live_version_id,
                 "created_at": config.created_at.map(|t| t.to_string()),
                 "updated_at": config.updated_at.map(|t| t.to_string())
             });
@@ -167,12 +396,227 @@ pub async fn get_slot_config(
     }
 }
 
+// 修改slot配置，修改的是草稿，需要重新发布后才会用于线上旋转
+pub async fn update_slot_config(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    req: web::Json<UpdateSlotConfigRequest>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+    if !matches!(
+        req.reel_mode.as_deref(),
+        None | Some("weighted" | "stepper")
+    ) {
+        return Err(
+            ApiError::BadRequest("reel_mode must be 'weighted' or 'stepper'".to_string()).into(),
+        );
+    }
+    if !matches!(
+        req.evaluation_mode.as_deref(),
+        None | Some("lines" | "cluster")
+    ) {
+        return Err(ApiError::BadRequest(
+            "evaluation_mode must be 'lines' or 'cluster'".to_string(),
+        )
+        .into());
+    }
+    if req
+        .cascade_multipliers
+        .as_ref()
+        .is_some_and(|ladder| ladder.iter().any(|&m| m < 1))
+    {
+        return Err(
+            ApiError::BadRequest("cascade_multipliers must be at least 1".to_string()).into(),
+        );
+    }
+
+    if let Some(name) = &req.name {
+        let name_taken = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM slot_configurations WHERE name = $1 AND id <> $2) as "exists!""#,
+            name,
+            config_id
+        )
+        .fetch_one(pool.get_ref())
+        .await
+        .map_err(ApiError::from)?;
+        if name_taken {
+            return Err(
+                ApiError::Conflict(format!("Slot config named '{name}' already exists")).into(),
+            );
+        }
+    }
+
+    sqlx::query_scalar!(
+        r#"
+        UPDATE slot_configurations SET
+            name = COALESCE($2, name),
+            rows = COALESCE($3, rows),
+            reels = COALESCE($4, reels),
+            is_megaway = COALESCE($5, is_megaway),
+            min_megaway_rows = COALESCE($6, min_megaway_rows),
+            max_megaway_rows = COALESCE($7, max_megaway_rows),
+            default_bet = COALESCE($8, default_bet),
+            min_bet = COALESCE($9, min_bet),
+            max_bet = COALESCE($10, max_bet),
+            wild_enabled = COALESCE($11, wild_enabled),
+            free_spins_enabled = COALESCE($12, free_spins_enabled),
+            free_spins_multiplier = COALESCE($13, free_spins_multiplier),
+            reel_mode = COALESCE($14, reel_mode),
+            rtp_percentage = COALESCE($15::float8, rtp_percentage),
+            is_active = COALESCE($16, is_active),
+            evaluation_mode = COALESCE($17, evaluation_mode),
+            cascading = COALESCE($18, cascading),
+            cascade_multipliers = COALESCE($19, cascade_multipliers),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        RETURNING id
+        "#,
+        config_id,
+        req.name,
+        req.rows,
+        req.reels,
+        req.is_megaway,
+        req.min_megaway_rows,
+        req.max_megaway_rows,
+        req.default_bet,
+        req.min_bet,
+        req.max_bet,
+        req.wild_enabled,
+        req.free_spins_enabled,
+        req.free_spins_multiplier,
+        req.reel_mode,
+        req.rtp_percentage,
+        req.is_active,
+        req.evaluation_mode,
+        req.cascading,
+        req.cascade_multipliers.as_deref()
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| ApiError::NotFound(format!("Slot config {config_id} not found")))?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": config_id,
+        "message": "Slot configuration updated successfully"
+    })))
+}
+
+// 删除slot配置及其所有组件；已有旋转记录的配置只能停用
+pub async fn delete_slot_config(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+
+    sqlx::query_scalar!(
+        "SELECT id FROM slot_configurations WHERE id = $1 FOR UPDATE",
+        config_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| ApiError::NotFound(format!("Slot config {config_id} not found")))?;
+
+    let has_spins = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM slot_spin_history WHERE slot_config_id = $1) as "exists!""#,
+        config_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+    if has_spins {
+        return Err(ApiError::Conflict(format!(
+            "Slot config {config_id} has spin history and cannot be deleted, set is_active to false instead"
+        ))
+        .into());
+    }
+
+    // 发布过的版本不可修改也不能删除
+    let has_versions = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM slot_config_versions WHERE slot_config_id = $1) as "exists!""#,
+        config_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+    if has_versions {
+        return Err(ApiError::Conflict(format!(
+            "Slot config {config_id} has published versions and cannot be deleted, set is_active to false instead"
+        ))
+        .into());
+    }
+
+    sqlx::query!("DELETE FROM slot_configurations WHERE id = $1", config_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Slot configuration deleted successfully"
+    })))
+}
+
+// 校验请求中的赔付表：符号数量至少为1，赔付不能为负数；赔付为0的档位不保存
+fn payout_table(payouts: &HashMap<i32, i32>) -> Result<HashMap<usize, u32>, ApiError> {
+    let mut table = HashMap::new();
+    for (&count, &payout) in payouts {
+        if count < 1 || payout < 0 {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid payout {payout} for {count} symbols"
+            )));
+        }
+        if payout > 0 {
+            table.insert(count as usize, payout as u32);
+        }
+    }
+    Ok(table)
+}
+
+// 替换符号的整个赔付表
+pub async fn replace_symbol_payouts(
+    conn: &mut PgConnection,
+    symbol_id: i32,
+    payouts: &HashMap<usize, u32>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM slot_symbol_payouts WHERE symbol_id = $1",
+        symbol_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let (counts, values): (Vec<i32>, Vec<i32>) = payouts
+        .iter()
+        .filter(|(_, &payout)| payout > 0)
+        .map(|(&count, &payout)| (count as i32, payout as i32))
+        .unzip();
+    sqlx::query!(
+        r#"
+        INSERT INTO slot_symbol_payouts (symbol_id, symbol_count, payout)
+        SELECT $1, * FROM UNNEST($2::int4[], $3::int4[])
+        "#,
+        symbol_id,
+        &counts,
+        &values
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
 // 添加符号到slot配置
 pub async fn add_symbol(
     pool: web::Data<Pool<Postgres>>,
     req: web::Json<CreateSymbolRequest>,
 ) -> Result<HttpResponse> {
-    let symbol_id = sqlx::query!(
+    validate_symbol_type(&req.symbol_type)?;
+    let payouts = payout_table(&req.payouts)?;
+
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+    let symbol_id = sqlx::query_scalar!(
         r#"
         INSERT INTO slot_symbols 
         (slot_config_id, name, symbol_type, value, image_url)
@@ -185,39 +629,151 @@ pub async fn add_symbol(
         req.value,
         req.image_url
     )
-    .fetch_one(pool.get_ref())
-    .await;
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to create symbol: {e}")))?;
 
-    match symbol_id {
-        Ok(rec) => {
-            // 添加赔付表
-            for (count, payout) in &req.payouts {
-                let column = match count {
-                    2 => "payout_2x",
-                    3 => "payout_3x",
-                    4 => "payout_4x",
-                    5 => "payout_5x",
-                    6 => "payout_6x",
-                    _ => continue,
-                };
-
-                let query = format!(
-                    "UPDATE slot_symbols SET {} = {} WHERE id = {}",
-                    column, payout, rec.id
-                );
-
-                sqlx::query(&query).execute(pool.get_ref()).await.ok();
-            }
+    replace_symbol_payouts(&mut tx, symbol_id, &payouts)
+        .await
+        .map_err(ApiError::from)?;
+    tx.commit().await.map_err(ApiError::from)?;
 
-            Ok(HttpResponse::Ok().json(serde_json::json!({
-                "id": rec.id,
-                "message": "Symbol created successfully"
-            })))
-        }
-        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Failed to create symbol: {}", e)
-        }))),
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": symbol_id,
+        "message": "Symbol created successfully"
+    })))
+}
+
+fn validate_symbol_type(symbol_type: &str) -> Result<(), ApiError> {
+    if matches!(
+        symbol_type,
+        "normal" | "wild" | "scatter" | "bonus" | "money"
+    ) {
+        Ok(())
+    } else {
+        Err(ApiError::BadRequest(
+            "symbol_type must be 'normal', 'wild', 'scatter', 'bonus' or 'money'".to_string(),
+        ))
+    }
+}
+
+// 修改符号，提供 payouts 时替换整个赔付表
+pub async fn update_symbol(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    req: web::Json<UpdateSymbolRequest>,
+) -> Result<HttpResponse> {
+    let symbol_id = path.into_inner();
+    if let Some(symbol_type) = &req.symbol_type {
+        validate_symbol_type(symbol_type)?;
+    }
+    let payouts = req.payouts.as_ref().map(payout_table).transpose()?;
+
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+    sqlx::query_scalar!(
+        r#"
+        UPDATE slot_symbols SET
+            name = COALESCE($2, name),
+            symbol_type = COALESCE($3, symbol_type),
+            value = COALESCE($4, value),
+            image_url = COALESCE($5, image_url)
+        WHERE id = $1
+        RETURNING id
+        "#,
+        symbol_id,
+        req.name,
+        req.symbol_type,
+        req.value,
+        req.image_url
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| ApiError::NotFound(format!("Symbol {symbol_id} not found")))?;
+
+    if let Some(payouts) = &payouts {
+        replace_symbol_payouts(&mut tx, symbol_id, payouts)
+            .await
+            .map_err(ApiError::from)?;
+    }
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": symbol_id,
+        "message": "Symbol updated successfully"
+    })))
+}
+
+// 删除符号；卷轴上仍在使用的符号不能删除
+pub async fn delete_symbol(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let symbol_id = path.into_inner();
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+
+    let config_id = sqlx::query_scalar!(
+        "SELECT slot_config_id FROM slot_symbols WHERE id = $1 FOR UPDATE",
+        symbol_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::from)?
+    .ok_or_else(|| ApiError::NotFound(format!("Symbol {symbol_id} not found")))?;
+
+    let reel_positions = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM slot_reel_symbols WHERE symbol_id = $1"#,
+        symbol_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+    if reel_positions > 0 {
+        return Err(ApiError::Conflict(format!(
+            "Symbol {symbol_id} is used at {reel_positions} reel positions, remove them first"
+        ))
+        .into());
+    }
+
+    // 该符号的Wild属性随符号级联删除，其他Wild的不能替代列表中也移除该符号
+    sqlx::query!(
+        r#"
+        UPDATE slot_wild_features SET cannot_substitute = array_remove(cannot_substitute, $2)
+        WHERE slot_config_id = $1
+        "#,
+        config_id,
+        symbol_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(ApiError::from)?;
+
+    sqlx::query!("DELETE FROM slot_symbols WHERE id = $1", symbol_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Symbol deleted successfully"
+    })))
+}
+
+// 卷轴组名称：未指定时为基础卷轴；free_spins、ante、bonus_buy 有固定用途，
+// 其他名称作为基础游戏的备选卷轴，设置权重后参与抽取
+fn reel_set_name(reel_set: Option<&str>) -> Result<&str, ApiError> {
+    let name = reel_set.unwrap_or(BASE_REEL_SET);
+    let valid = name.len() <= 50
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid reel set '{name}', use up to 50 lowercase letters, digits and underscores starting with a letter"
+        )));
     }
+    Ok(name)
 }
 
 // 配置卷轴符号
@@ -225,15 +781,22 @@ pub async fn add_reel_symbol(
     pool: web::Data<Pool<Postgres>>,
     req: web::Json<CreateReelSymbolRequest>,
 ) -> Result<HttpResponse> {
+    let reel_set = reel_set_name(req.reel_set.as_deref())?;
+    if req.weight < 1 {
+        return Err(ApiError::BadRequest("weight must be at least 1".to_string()).into());
+    }
+    ensure_config_symbol(pool.get_ref(), req.slot_config_id, req.symbol_id).await?;
+
     let result = sqlx::query!(
         r#"
         INSERT INTO slot_reel_symbols 
-        (slot_config_id, reel_number, position, symbol_id, weight)
-        VALUES ($1, $2, $3, $4, $5)
-        ON CONFLICT (slot_config_id, reel_number, position) 
-        DO UPDATE SET symbol_id = $4, weight = $5
+        (slot_config_id, reel_set, reel_number, position, symbol_id, weight)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (slot_config_id, reel_set, reel_number, position) 
+        DO UPDATE SET symbol_id = $5, weight = $6
         "#,
         req.slot_config_id,
+        reel_set,
         req.reel_number,
         req.position,
         req.symbol_id,
@@ -252,70 +815,1189 @@ pub async fn add_reel_symbol(
     }
 }
 
-// 添加支付线
-pub async fn add_payline(
+// 卷轴只能使用本配置的符号
+async fn ensure_config_symbol(
+    pool: &Pool<Postgres>,
+    config_id: i32,
+    symbol_id: i32,
+) -> Result<(), ApiError> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM slot_symbols WHERE id = $1 AND slot_config_id = $2) as "exists!""#,
+        symbol_id,
+        config_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Err(ApiError::NotFound(format!(
+            "Symbol {symbol_id} not found in slot config {config_id}"
+        )));
+    }
+    Ok(())
+}
+
+// 修改卷轴位置上的符号或权重
+pub async fn update_reel_symbol(
     pool: web::Data<Pool<Postgres>>,
-    req: web::Json<CreatePaylineRequest>,
+    path: web::Path<(i32, i32, i32)>,
+    query: web::Query<ReelSetQuery>,
+    req: web::Json<UpdateReelSymbolRequest>,
 ) -> Result<HttpResponse> {
-    let pattern_json = serde_json::to_value(&req.pattern).unwrap();
+    let (config_id, reel_number, position) = path.into_inner();
+    let reel_set = reel_set_name(query.reel_set.as_deref())?;
+    if req.weight.is_some_and(|weight| weight < 1) {
+        return Err(ApiError::BadRequest("weight must be at least 1".to_string()).into());
+    }
+
+    if let Some(symbol_id) = req.symbol_id {
+        ensure_config_symbol(pool.get_ref(), config_id, symbol_id).await?;
+    }
 
     let result = sqlx::query!(
         r#"
-        INSERT INTO slot_paylines 
-        (slot_config_id, line_number, pattern, is_active)
-        VALUES ($1, $2, $3, $4)
-        ON CONFLICT (slot_config_id, line_number)
-        DO UPDATE SET pattern = $3, is_active = $4
+        UPDATE slot_reel_symbols
+        SET symbol_id = COALESCE($4, symbol_id), weight = COALESCE($5, weight)
+        WHERE slot_config_id = $1 AND reel_number = $2 AND position = $3 AND reel_set = $6
         "#,
-        req.slot_config_id,
-        req.line_number,
-        pattern_json,
-        req.is_active
+        config_id,
+        reel_number,
+        position,
+        req.symbol_id,
+        req.weight,
+        reel_set
     )
     .execute(pool.get_ref())
-    .await;
-
-    match result {
-        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
-            "message": "Payline configured successfully"
-        }))),
-        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Failed to configure payline: {}", e)
-        }))),
+    .await
+    .map_err(ApiError::from)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!(
+            "Reel {reel_number} has no symbol at position {position}"
+        ))
+        .into());
     }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Reel symbol updated successfully"
+    })))
 }
 
-// 获取slot配置的所有符号
-pub async fn get_slot_symbols(
+// 删除卷轴位置，其余位置的编号不变
+pub async fn delete_reel_symbol(
     pool: web::Data<Pool<Postgres>>,
-    path: web::Path<i32>,
+    path: web::Path<(i32, i32, i32)>,
+    query: web::Query<ReelSetQuery>,
 ) -> Result<HttpResponse> {
-    let config_id = path.into_inner();
+    let (config_id, reel_number, position) = path.into_inner();
+    let reel_set = reel_set_name(query.reel_set.as_deref())?;
 
-    let symbols = sqlx::query!(
-        "SELECT * FROM slot_symbols WHERE slot_config_id = $1 ORDER BY value",
-        config_id
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM slot_reel_symbols
+        WHERE slot_config_id = $1 AND reel_number = $2 AND position = $3 AND reel_set = $4
+        "#,
+        config_id,
+        reel_number,
+        position,
+        reel_set
     )
-    .fetch_all(pool.get_ref())
-    .await;
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!(
+            "Reel {reel_number} has no symbol at position {position}"
+        ))
+        .into());
+    }
 
-    match symbols {
-        Ok(symbols) => {
-            let serializable_symbols: Vec<serde_json::Value> = symbols
-                .into_iter()
-                .map(|symbol| {
-                    serde_json::json!({
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Reel symbol deleted successfully"
+    })))
+}
+
+// 设置基础游戏抽取卷轴组的权重，base 为基础卷轴
+pub async fn set_reel_set_weight(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<SetReelSetWeightRequest>,
+) -> Result<HttpResponse> {
+    let reel_set = reel_set_name(Some(&req.reel_set))?;
+    if matches!(
+        reel_set,
+        FREE_SPINS_REEL_SET | ANTE_REEL_SET | BONUS_BUY_REEL_SET
+    ) {
+        return Err(ApiError::BadRequest(format!(
+            "Reel set '{reel_set}' has a fixed role and can't be drawn in the base game"
+        ))
+        .into());
+    }
+    if req.weight < 1 {
+        return Err(ApiError::BadRequest("weight must be at least 1".to_string()).into());
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO slot_reel_set_weights (slot_config_id, reel_set, weight)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (slot_config_id, reel_set)
+        DO UPDATE SET weight = $3
+        "#,
+        req.slot_config_id,
+        reel_set,
+        req.weight
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Reel set weight configured successfully"
+    })))
+}
+
+// 删除卷轴组的权重，该卷轴组不再在基础游戏中抽取
+pub async fn delete_reel_set_weight(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, String)>,
+) -> Result<HttpResponse> {
+    let (config_id, reel_set) = path.into_inner();
+
+    let result = sqlx::query!(
+        "DELETE FROM slot_reel_set_weights WHERE slot_config_id = $1 AND reel_set = $2",
+        config_id,
+        reel_set
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Reel set '{reel_set}' has no weight")).into());
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Reel set weight deleted successfully"
+    })))
+}
+
+// 删除整个卷轴组（所有位置和权重），基础卷轴不能删除
+pub async fn delete_reel_set(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, String)>,
+) -> Result<HttpResponse> {
+    let (config_id, reel_set) = path.into_inner();
+    if reel_set == BASE_REEL_SET {
+        return Err(ApiError::BadRequest("The base reel set can't be deleted".to_string()).into());
+    }
+
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+    let positions = sqlx::query!(
+        "DELETE FROM slot_reel_symbols WHERE slot_config_id = $1 AND reel_set = $2",
+        config_id,
+        reel_set
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(ApiError::from)?
+    .rows_affected();
+    let weights = sqlx::query!(
+        "DELETE FROM slot_reel_set_weights WHERE slot_config_id = $1 AND reel_set = $2",
+        config_id,
+        reel_set
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(ApiError::from)?
+    .rows_affected();
+    if positions == 0 && weights == 0 {
+        return Err(ApiError::NotFound(format!(
+            "Reel set '{reel_set}' not found in slot config {config_id}"
+        ))
+        .into());
+    }
+    tx.commit().await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Reel set deleted successfully",
+        "positions_deleted": positions
+    })))
+}
+
+// 列出配置的卷轴组：用途、每个卷轴的位置数和基础游戏中的抽取权重
+pub async fn get_reel_sets(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let positions = sqlx::query!(
+        r#"
+        SELECT reel_set, reel_number, COUNT(*) as "positions!"
+        FROM slot_reel_symbols WHERE slot_config_id = $1
+        GROUP BY reel_set, reel_number
+        ORDER BY reel_set, reel_number
+        "#,
+        config_id
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    let weights: HashMap<String, i32> = sqlx::query!(
+        "SELECT reel_set, weight FROM slot_reel_set_weights WHERE slot_config_id = $1",
+        config_id
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?
+    .into_iter()
+    .map(|record| (record.reel_set, record.weight))
+    .collect();
+
+    let mut reel_sets: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
+    for record in positions {
+        let reel = serde_json::json!({
+            "reel_number": record.reel_number,
+            "positions": record.positions
+        });
+        match reel_sets.last_mut() {
+            Some((name, reels)) if *name == record.reel_set => reels.push(reel),
+            _ => reel_sets.push((record.reel_set, vec![reel])),
+        }
+    }
+    // 只有权重、还没有配置卷轴的卷轴组同样列出
+    let mut weight_only: Vec<_> = weights
+        .keys()
+        .filter(|name| !reel_sets.iter().any(|(set, _)| set == *name))
+        .cloned()
+        .collect();
+    weight_only.sort();
+    reel_sets.extend(weight_only.into_iter().map(|name| (name, Vec::new())));
+
+    let reel_sets: Vec<serde_json::Value> = reel_sets
+        .into_iter()
+        .map(|(name, reels)| {
+            let role = match name.as_str() {
+                BASE_REEL_SET => "base",
+                FREE_SPINS_REEL_SET => "free_spins",
+                ANTE_REEL_SET => "ante",
+                BONUS_BUY_REEL_SET => "bonus_buy",
+                _ if weights.contains_key(&name) => "base_variant",
+                _ => "unused",
+            };
+            serde_json::json!({
+                "reel_set": name,
+                "role": role,
+                "weight": weights.get(&name),
+                "reels": reels
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "slot_config_id": config_id,
+        "reel_sets": reel_sets
+    })))
+}
+
+// 添加支付线
+pub async fn add_payline(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<CreatePaylineRequest>,
+) -> Result<HttpResponse> {
+    let pattern_json = serde_json::to_value(&req.pattern).unwrap();
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO slot_paylines 
+        (slot_config_id, line_number, pattern, is_active)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (slot_config_id, line_number)
+        DO UPDATE SET pattern = $3, is_active = $4
+        "#,
+        req.slot_config_id,
+        req.line_number,
+        pattern_json,
+        req.is_active
+    )
+    .execute(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Payline configured successfully"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to configure payline: {}", e)
+        }))),
+    }
+}
+
+// 修改支付线的模式或启用状态
+pub async fn update_payline(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+    req: web::Json<UpdatePaylineRequest>,
+) -> Result<HttpResponse> {
+    let (config_id, line_number) = path.into_inner();
+    let pattern_json = req
+        .pattern
+        .as_ref()
+        .map(|pattern| serde_json::to_value(pattern).unwrap());
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE slot_paylines
+        SET pattern = COALESCE($3, pattern), is_active = COALESCE($4, is_active)
+        WHERE slot_config_id = $1 AND line_number = $2
+        "#,
+        config_id,
+        line_number,
+        pattern_json,
+        req.is_active
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Payline {line_number} not found")).into());
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Payline updated successfully"
+    })))
+}
+
+// 删除支付线
+pub async fn delete_payline(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    let (config_id, line_number) = path.into_inner();
+
+    let result = sqlx::query!(
+        "DELETE FROM slot_paylines WHERE slot_config_id = $1 AND line_number = $2",
+        config_id,
+        line_number
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Payline {line_number} not found")).into());
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Payline deleted successfully"
+    })))
+}
+
+// 配置Scatter触发表（按Scatter数量覆盖）
+pub async fn add_scatter_trigger(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<CreateScatterTriggerRequest>,
+) -> Result<HttpResponse> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO slot_scatter_triggers (slot_config_id, scatter_count, free_spins)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (slot_config_id, scatter_count)
+        DO UPDATE SET free_spins = $3
+        "#,
+        req.slot_config_id,
+        req.scatter_count,
+        req.free_spins
+    )
+    .execute(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Scatter trigger configured successfully"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to configure scatter trigger: {}", e)
+        }))),
+    }
+}
+
+// 删除Scatter触发档
+pub async fn delete_scatter_trigger(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    let (config_id, scatter_count) = path.into_inner();
+
+    let result = sqlx::query!(
+        "DELETE FROM slot_scatter_triggers WHERE slot_config_id = $1 AND scatter_count = $2",
+        config_id,
+        scatter_count
+    )
+    .execute(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(result) if result.rows_affected() == 0 => {
+            Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Scatter trigger not found"
+            })))
+        }
+        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Scatter trigger deleted successfully"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to delete scatter trigger: {}", e)
+        }))),
+    }
+}
+
+// 获取slot配置的Scatter触发表
+pub async fn get_scatter_triggers(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let triggers = sqlx::query!(
+        "SELECT id, slot_config_id, scatter_count, free_spins FROM slot_scatter_triggers WHERE slot_config_id = $1 ORDER BY scatter_count",
+        config_id
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match triggers {
+        Ok(triggers) => {
+            let serializable_triggers: Vec<serde_json::Value> = triggers
+                .into_iter()
+                .map(|trigger| {
+                    serde_json::json!({
+                        "id": trigger.id,
+                        "slot_config_id": trigger.slot_config_id,
+                        "scatter_count": trigger.scatter_count,
+                        "free_spins": trigger.free_spins
+                    })
+                })
+                .collect();
+            Ok(HttpResponse::Ok().json(serializable_triggers))
+        }
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to fetch scatter triggers: {}", e)
+        }))),
+    }
+}
+
+// 配置Wild符号属性（符号必须是wild类型）
+pub async fn set_wild_feature(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<SetWildFeatureRequest>,
+) -> Result<HttpResponse> {
+    if req.multiplier < 1 {
+        return Err(ApiError::BadRequest("multiplier must be at least 1".to_string()).into());
+    }
+
+    let symbol = sqlx::query!(
+        "SELECT slot_config_id, symbol_type FROM slot_symbols WHERE id = $1",
+        req.symbol_id
+    )
+    .fetch_optional(pool.get_ref())
+    .await;
+
+    let slot_config_id = match symbol {
+        Ok(Some(symbol)) if symbol.symbol_type == "wild" => symbol.slot_config_id,
+        Ok(Some(_)) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Wild features can only be set on wild symbols"
+            })))
+        }
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Symbol not found"
+            })))
+        }
+        Err(e) => {
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to fetch symbol: {}", e)
+            })))
+        }
+    };
+
+    // 不能替代的符号必须属于同一配置
+    let known = sqlx::query_scalar!(
+        "SELECT id FROM slot_symbols WHERE slot_config_id = $1 AND id = ANY($2)",
+        slot_config_id,
+        &req.cannot_substitute
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+    if let Some(unknown) = req.cannot_substitute.iter().find(|id| !known.contains(id)) {
+        return Err(ApiError::BadRequest(format!(
+            "cannot_substitute symbol {unknown} does not belong to the wild symbol's slot config"
+        ))
+        .into());
+    }
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO slot_wild_features
+        (slot_config_id, symbol_id, multiplier, cannot_substitute, expanding, sticky, walking)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (symbol_id)
+        DO UPDATE SET multiplier = $3, cannot_substitute = $4, expanding = $5, sticky = $6, walking = $7
+        "#,
+        slot_config_id,
+        req.symbol_id,
+        req.multiplier,
+        &req.cannot_substitute,
+        req.expanding,
+        req.sticky,
+        req.walking
+    )
+    .execute(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Wild feature configured successfully"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to configure wild feature: {}", e)
+        }))),
+    }
+}
+
+// 删除Wild符号属性，恢复为默认行为
+pub async fn delete_wild_feature(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let symbol_id = path.into_inner();
+
+    let result = sqlx::query!(
+        "DELETE FROM slot_wild_features WHERE symbol_id = $1",
+        symbol_id
+    )
+    .execute(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(result) if result.rows_affected() == 0 => {
+            Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Wild feature not found"
+            })))
+        }
+        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Wild feature deleted successfully"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to delete wild feature: {}", e)
+        }))),
+    }
+}
+
+// 获取slot配置的Wild属性
+pub async fn get_wild_features(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let features = sqlx::query!(
+        r#"
+        SELECT id, symbol_id, multiplier, cannot_substitute, expanding, sticky, walking
+        FROM slot_wild_features WHERE slot_config_id = $1 ORDER BY symbol_id
+        "#,
+        config_id
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match features {
+        Ok(features) => {
+            let serializable_features: Vec<serde_json::Value> = features
+                .into_iter()
+                .map(|feature| {
+                    serde_json::json!({
+                        "id": feature.id,
+                        "symbol_id": feature.symbol_id,
+                        "multiplier": feature.multiplier,
+                        "cannot_substitute": feature.cannot_substitute,
+                        "expanding": feature.expanding,
+                        "sticky": feature.sticky,
+                        "walking": feature.walking
+                    })
+                })
+                .collect();
+            Ok(HttpResponse::Ok().json(serializable_features))
+        }
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to fetch wild features: {}", e)
+        }))),
+    }
+}
+
+// 配置Bonus选宝游戏
+pub async fn set_bonus_game(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<SetBonusGameRequest>,
+) -> Result<HttpResponse> {
+    if req.trigger_count < 1 || req.picks < 1 || req.board_size < 1 {
+        return Err(ApiError::BadRequest(
+            "trigger_count, picks and board_size must be at least 1".to_string(),
+        )
+        .into());
+    }
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO slot_bonus_games (slot_config_id, trigger_count, picks, board_size)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (slot_config_id)
+        DO UPDATE SET trigger_count = $2, picks = $3, board_size = $4
+        "#,
+        req.slot_config_id,
+        req.trigger_count,
+        req.picks,
+        req.board_size
+    )
+    .execute(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Bonus game configured successfully"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to configure bonus game: {}", e)
+        }))),
+    }
+}
+
+// 添加选宝奖品
+pub async fn add_bonus_prize(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<CreateBonusPrizeRequest>,
+) -> Result<HttpResponse> {
+    if req.multiplier < 0 {
+        return Err(ApiError::BadRequest("multiplier must not be negative".to_string()).into());
+    }
+    if req.weight < 1 {
+        return Err(ApiError::BadRequest("weight must be at least 1".to_string()).into());
+    }
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO slot_bonus_prizes (slot_config_id, multiplier, weight)
+        VALUES ($1, $2, $3)
+        RETURNING id
+        "#,
+        req.slot_config_id,
+        req.multiplier,
+        req.weight
+    )
+    .fetch_one(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(rec) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "id": rec.id,
+            "message": "Bonus prize created successfully"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to create bonus prize: {}", e)
+        }))),
+    }
+}
+
+// 删除选宝奖品
+pub async fn delete_bonus_prize(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let prize_id = path.into_inner();
+
+    let result = sqlx::query!("DELETE FROM slot_bonus_prizes WHERE id = $1", prize_id)
+        .execute(pool.get_ref())
+        .await;
+
+    match result {
+        Ok(result) if result.rows_affected() == 0 => {
+            Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Bonus prize not found"
+            })))
+        }
+        Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "message": "Bonus prize deleted successfully"
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to delete bonus prize: {}", e)
+        }))),
+    }
+}
+
+// 获取slot配置的Bonus选宝游戏及奖品表
+pub async fn get_bonus_game(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let game = sqlx::query!(
+        "SELECT trigger_count, picks, board_size FROM slot_bonus_games WHERE slot_config_id = $1",
+        config_id
+    )
+    .fetch_optional(pool.get_ref())
+    .await;
+
+    let prizes = sqlx::query!(
+        "SELECT id, multiplier, weight FROM slot_bonus_prizes WHERE slot_config_id = $1 ORDER BY multiplier",
+        config_id
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match (game, prizes) {
+        (Ok(game), Ok(prizes)) => {
+            let serializable_prizes: Vec<serde_json::Value> = prizes
+                .into_iter()
+                .map(|prize| {
+                    serde_json::json!({
+                        "id": prize.id,
+                        "multiplier": prize.multiplier,
+                        "weight": prize.weight
+                    })
+                })
+                .collect();
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "slot_config_id": config_id,
+                "trigger_count": game.as_ref().map(|g| g.trigger_count),
+                "picks": game.as_ref().map(|g| g.picks),
+                "board_size": game.as_ref().map(|g| g.board_size),
+                "prizes": serializable_prizes
+            })))
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to fetch bonus game: {}", e)
+            })))
+        }
+    }
+}
+
+// 配置Hold and Spin
+pub async fn set_hold_and_spin(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<SetHoldAndSpinRequest>,
+) -> Result<HttpResponse> {
+    if req.trigger_count < 1 || req.respins < 1 {
+        return Err(ApiError::BadRequest(
+            "trigger_count and respins must be at least 1".to_string(),
+        )
+        .into());
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO slot_hold_and_spin (slot_config_id, trigger_count, respins)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (slot_config_id)
+        DO UPDATE SET trigger_count = $2, respins = $3
+        "#,
+        req.slot_config_id,
+        req.trigger_count,
+        req.respins
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Hold and spin configured successfully"
+    })))
+}
+
+// 添加钱币金额
+pub async fn add_money_value(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<CreateMoneyValueRequest>,
+) -> Result<HttpResponse> {
+    if !matches!(
+        req.jackpot.as_deref(),
+        None | Some("mini" | "minor" | "major" | "grand")
+    ) {
+        return Err(ApiError::BadRequest(
+            "jackpot must be 'mini', 'minor', 'major' or 'grand'".to_string(),
+        )
+        .into());
+    }
+
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO slot_money_values (slot_config_id, multiplier, jackpot, weight)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        req.slot_config_id,
+        req.multiplier,
+        req.jackpot,
+        req.weight
+    )
+    .fetch_one(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": id,
+        "message": "Money value created successfully"
+    })))
+}
+
+// 删除钱币金额
+pub async fn delete_money_value(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let value_id = path.into_inner();
+
+    let result = sqlx::query!("DELETE FROM slot_money_values WHERE id = $1", value_id)
+        .execute(pool.get_ref())
+        .await
+        .map_err(ApiError::from)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Money value not found".to_string()).into());
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Money value deleted successfully"
+    })))
+}
+
+// 获取slot配置的Hold and Spin设置及钱币金额表
+pub async fn get_hold_and_spin(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let settings = sqlx::query!(
+        "SELECT trigger_count, respins FROM slot_hold_and_spin WHERE slot_config_id = $1",
+        config_id
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    let values: Vec<serde_json::Value> = sqlx::query!(
+        "SELECT id, multiplier, jackpot, weight FROM slot_money_values WHERE slot_config_id = $1 ORDER BY multiplier",
+        config_id
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?
+    .into_iter()
+    .map(|value| {
+        serde_json::json!({
+            "id": value.id,
+            "multiplier": value.multiplier,
+            "jackpot": value.jackpot,
+            "weight": value.weight
+        })
+    })
+    .collect();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "slot_config_id": config_id,
+        "trigger_count": settings.as_ref().map(|s| s.trigger_count),
+        "respins": settings.as_ref().map(|s| s.respins),
+        "values": values
+    })))
+}
+
+// 配置加注，修改后需要重新发布
+pub async fn set_ante_bet(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<SetAnteBetRequest>,
+) -> Result<HttpResponse> {
+    if req.cost_percent < 1 {
+        return Err(ApiError::BadRequest("cost_percent must be at least 1".to_string()).into());
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO slot_ante_bets (slot_config_id, cost_percent)
+        VALUES ($1, $2)
+        ON CONFLICT (slot_config_id)
+        DO UPDATE SET cost_percent = $2
+        "#,
+        req.slot_config_id,
+        req.cost_percent
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Ante bet configured successfully"
+    })))
+}
+
+// 关闭加注
+pub async fn delete_ante_bet(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let result = sqlx::query!(
+        "DELETE FROM slot_ante_bets WHERE slot_config_id = $1",
+        config_id
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Slot config {config_id} has no ante bet")).into());
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Ante bet deleted successfully"
+    })))
+}
+
+// 配置购买免费旋转，修改后需要重新发布
+pub async fn set_bonus_buy(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<SetBonusBuyRequest>,
+) -> Result<HttpResponse> {
+    if req.cost_multiplier < 1 || req.free_spins < 1 {
+        return Err(ApiError::BadRequest(
+            "cost_multiplier and free_spins must be at least 1".to_string(),
+        )
+        .into());
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO slot_bonus_buys (slot_config_id, cost_multiplier, free_spins)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (slot_config_id)
+        DO UPDATE SET cost_multiplier = $2, free_spins = $3
+        "#,
+        req.slot_config_id,
+        req.cost_multiplier,
+        req.free_spins
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Bonus buy configured successfully"
+    })))
+}
+
+// 关闭购买免费旋转
+pub async fn delete_bonus_buy(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let result = sqlx::query!(
+        "DELETE FROM slot_bonus_buys WHERE slot_config_id = $1",
+        config_id
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Slot config {config_id} has no bonus buy")).into());
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Bonus buy deleted successfully"
+    })))
+}
+
+// 设置某个司法管辖区能否使用加注和购买免费旋转，立即生效
+pub async fn set_jurisdiction_rule(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<SetJurisdictionRuleRequest>,
+) -> Result<HttpResponse> {
+    let jurisdiction = req.jurisdiction.trim().to_uppercase();
+    if jurisdiction.is_empty() || jurisdiction.len() > 10 {
+        return Err(
+            ApiError::BadRequest("jurisdiction must be 1 to 10 characters".to_string()).into(),
+        );
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO slot_jurisdiction_rules
+        (slot_config_id, jurisdiction, ante_bet_enabled, bonus_buy_enabled)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (slot_config_id, jurisdiction)
+        DO UPDATE SET ante_bet_enabled = $3, bonus_buy_enabled = $4
+        "#,
+        req.slot_config_id,
+        jurisdiction,
+        req.ante_bet_enabled,
+        req.bonus_buy_enabled
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Jurisdiction rule configured successfully"
+    })))
+}
+
+// 删除司法管辖区规则，该地区恢复全部可用
+pub async fn delete_jurisdiction_rule(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(i32, String)>,
+) -> Result<HttpResponse> {
+    let (config_id, jurisdiction) = path.into_inner();
+
+    let result = sqlx::query!(
+        "DELETE FROM slot_jurisdiction_rules WHERE slot_config_id = $1 AND jurisdiction = $2",
+        config_id,
+        jurisdiction.to_uppercase()
+    )
+    .execute(pool.get_ref())
+    .await
+    .map_err(ApiError::from)?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Jurisdiction rule not found".to_string()).into());
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Jurisdiction rule deleted successfully"
+    })))
+}
+
+// 玩家所在地区，以服务端的玩家记录为准；匿名旋转或未登记地区时为 None
+async fn player_jurisdiction(
+    conn: &mut PgConnection,
+    player_id: Option<i32>,
+) -> Result<Option<String>, sqlx::Error> {
+    let Some(player_id) = player_id else {
+        return Ok(None);
+    };
+    let jurisdiction =
+        sqlx::query_scalar!("SELECT jurisdiction FROM players WHERE id = $1", player_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    Ok(jurisdiction.flatten())
+}
+
+// 投注方式在该地区是否可用：地区未知时只允许普通投注，已知地区没有规则时全部可用
+async fn bet_mode_allowed(
+    conn: &mut PgConnection,
+    config_id: i32,
+    jurisdiction: Option<&str>,
+    mode: BetMode,
+) -> Result<bool, sqlx::Error> {
+    let Some(jurisdiction) = jurisdiction else {
+        return Ok(mode == BetMode::Standard);
+    };
+    let rule = sqlx::query!(
+        r#"
+        SELECT ante_bet_enabled, bonus_buy_enabled FROM slot_jurisdiction_rules
+        WHERE slot_config_id = $1 AND jurisdiction = $2
+        "#,
+        config_id,
+        jurisdiction.to_uppercase()
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(match (mode, rule) {
+        (_, None) | (BetMode::Standard, _) => true,
+        (BetMode::Ante, Some(rule)) => rule.ante_bet_enabled,
+        (BetMode::BonusBuy, Some(rule)) => rule.bonus_buy_enabled,
+    })
+}
+
+// 玩家可用的投注方式及价格：使用线上版本的设置，并应用该地区的规则
+pub async fn get_bet_options(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    query: web::Query<BetOptionsQuery>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+    let (machine, _) = load_live_machine(pool.get_ref(), config_id).await?;
+    let mut conn = pool.acquire().await.map_err(ApiError::from)?;
+    let jurisdiction = player_jurisdiction(&mut conn, query.player_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    let mut options = Vec::new();
+    for mode in [BetMode::Standard, BetMode::Ante, BetMode::BonusBuy] {
+        // 按默认投注计算价格
+        let Some(cost) = machine.bet_cost(machine.config.default_bet, mode) else {
+            continue;
+        };
+        let allowed = bet_mode_allowed(&mut conn, config_id, jurisdiction.as_deref(), mode)
+            .await
+            .map_err(ApiError::from)?;
+        options.push(serde_json::json!({
+            "bet_mode": mode,
+            "enabled": allowed,
+            "default_cost": cost
+        }));
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "slot_config_id": config_id,
+        "jurisdiction": jurisdiction.as_deref().map(str::to_uppercase),
+        "default_bet": machine.config.default_bet,
+        "min_bet": machine.config.min_bet,
+        "max_bet": machine.config.max_bet,
+        "max_lines": (!machine.is_ways_game()).then(|| machine.active_line_count()),
+        "ante_bet": machine.ante_bet,
+        "bonus_buy": machine.bonus_buy,
+        "options": options
+    })))
+}
+
+// 获取slot配置的所有符号
+pub async fn get_slot_symbols(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+
+    let symbols = sqlx::query!(
+        r#"
+        SELECT s.id, s.slot_config_id, s.name, s.symbol_type, s.value, s.image_url, s.created_at,
+               COALESCE(
+                   jsonb_object_agg(p.symbol_count, p.payout) FILTER (WHERE p.id IS NOT NULL),
+                   '{}'
+               ) as "payouts!"
+        FROM slot_symbols s
+        LEFT JOIN slot_symbol_payouts p ON p.symbol_id = s.id
+        WHERE s.slot_config_id = $1
+        GROUP BY s.id
+        ORDER BY s.value
+        "#,
+        config_id
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match symbols {
+        Ok(symbols) => {
+            let serializable_symbols: Vec<serde_json::Value> = symbols
+                .into_iter()
+                .map(|symbol| {
+                    serde_json::json!({
                         "id": symbol.id,
                         "slot_config_id": symbol.slot_config_id,
                         "name": symbol.name,
                         "symbol_type": symbol.symbol_type,
                         "value": symbol.value,
                         "image_url": symbol.image_url,
-                        "payout_2x": symbol.payout_2x,
-                        "payout_3x": symbol.payout_3x,
-                        "payout_4x": symbol.payout_4x,
-                        "payout_5x": symbol.payout_5x,
-                        "payout_6x": symbol.payout_6x,
+                        "payouts": symbol.payouts,
                         "created_at": symbol.created_at.map(|t| t.to_string())
                     })
                 })
@@ -337,11 +2019,12 @@ pub async fn get_slot_reels(
 
     let reels = sqlx::query!(
         r#"
-        SELECT sr.*, s.name as symbol_name, s.symbol_type 
+        SELECT sr.id, sr.slot_config_id, sr.reel_set, sr.reel_number, sr.position, sr.symbol_id,
+               sr.weight, s.name as symbol_name, s.symbol_type
         FROM slot_reel_symbols sr
         JOIN slot_symbols s ON sr.symbol_id = s.id
         WHERE sr.slot_config_id = $1 
-        ORDER BY sr.reel_number, sr.position
+        ORDER BY sr.reel_set, sr.reel_number, sr.position
         "#,
         config_id
     )
@@ -356,6 +2039,7 @@ pub async fn get_slot_reels(
                     serde_json::json!({
                         "id": reel.id,
                         "slot_config_id": reel.slot_config_id,
+                        "reel_set": reel.reel_set,
                         "reel_number": reel.reel_number,
                         "position": reel.position,
                         "symbol_id": reel.symbol_id,
@@ -381,7 +2065,7 @@ pub async fn get_slot_paylines(
     let config_id = path.into_inner();
 
     let paylines = sqlx::query!(
-        "SELECT * FROM slot_paylines WHERE slot_config_id = $1 ORDER BY line_number",
+        "SELECT id, slot_config_id, line_number, pattern, is_active FROM slot_paylines WHERE slot_config_id = $1 ORDER BY line_number",
         config_id
     )
     .fetch_all(pool.get_ref())
@@ -413,45 +2097,105 @@ pub async fn get_slot_paylines(
 pub async fn build_slot_machine(
     pool: &Pool<Postgres>,
     config_id: i32,
-) -> Result<crate::universal_slots::UniversalSlotMachine, String> {
+) -> Result<crate::universal_slots::UniversalSlotMachine, String> {
+    let builder = load_slot_config_builder(pool, config_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(builder.build())
+}
+
+// 构建用于上线旋转的机器：配置未通过校验时拒绝，错误详情见 /{id}/validate
+pub async fn build_playable_machine(
+    pool: &Pool<Postgres>,
+    config_id: i32,
+) -> Result<UniversalSlotMachine, ApiError> {
+    let builder = load_slot_config_builder(pool, config_id).await?;
+    let report = builder.validate();
+    if !report.valid {
+        return Err(ApiError::Unprocessable(format!(
+            "Slot config {} is invalid: {}",
+            config_id,
+            report.error_summary()
+        )));
+    }
+    Ok(builder.build())
+}
+
+// 从数据库读取完整的slot配置。所有读取在同一个 REPEATABLE READ 事务中进行，
+// 读取期间的并发修改不会让快照混合新旧数据（发布的版本依赖于此）
+pub async fn load_slot_config_builder(
+    pool: &Pool<Postgres>,
+    config_id: i32,
+) -> Result<SlotConfigBuilder, ApiError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+    let builder = read_slot_config_builder(&mut tx, config_id).await?;
+    tx.commit().await?;
+    Ok(builder)
+}
+
+async fn read_slot_config_builder(
+    conn: &mut PgConnection,
+    config_id: i32,
+) -> Result<SlotConfigBuilder, ApiError> {
     // 获取配置
-    let config = sqlx::query!("SELECT * FROM slot_configurations WHERE id = $1", config_id)
-        .fetch_one(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch config: {e}"))?;
+    let config = sqlx::query!(
+        r#"
+        SELECT id, name, rows, reels, is_megaway, min_megaway_rows, max_megaway_rows,
+               default_bet, min_bet, max_bet, wild_enabled, free_spins_enabled,
+               free_spins_multiplier, reel_mode, evaluation_mode, cascading, cascade_multipliers,
+               rtp_percentage, created_at, updated_at, is_active, live_version_id
+        FROM slot_configurations
+        WHERE id = $1
+        "#,
+        config_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch config: {e}")))?
+    .ok_or_else(|| ApiError::NotFound(format!("Slot config {config_id} not found")))?;
 
-    // 获取符号
+    // 获取符号及赔付表
     let symbol_records = sqlx::query!(
-        "SELECT * FROM slot_symbols WHERE slot_config_id = $1",
+        "SELECT id, name, symbol_type, value, image_url FROM slot_symbols WHERE slot_config_id = $1 ORDER BY id",
         config_id
     )
-    .fetch_all(pool)
+    .fetch_all(&mut *conn)
     .await
-    .map_err(|e| format!("Failed to fetch symbols: {e}"))?;
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch symbols: {e}")))?;
+
+    let payout_records = sqlx::query!(
+        r#"
+        SELECT p.symbol_id, p.symbol_count, p.payout
+        FROM slot_symbol_payouts p
+        JOIN slot_symbols s ON s.id = p.symbol_id
+        WHERE s.slot_config_id = $1
+        "#,
+        config_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch payouts: {e}")))?;
+
+    let mut payout_tables: HashMap<i32, HashMap<usize, u32>> = HashMap::new();
+    for record in payout_records {
+        payout_tables
+            .entry(record.symbol_id)
+            .or_default()
+            .insert(record.symbol_count as usize, record.payout as u32);
+    }
 
     let mut symbols = Vec::new();
     for record in symbol_records {
-        let mut payouts = HashMap::new();
-        if record.payout_2x.is_some() && record.payout_2x.unwrap() > 0 {
-            payouts.insert(2, record.payout_2x.unwrap() as u32);
-        }
-        if record.payout_3x.is_some() && record.payout_3x.unwrap() > 0 {
-            payouts.insert(3, record.payout_3x.unwrap() as u32);
-        }
-        if record.payout_4x.is_some() && record.payout_4x.unwrap() > 0 {
-            payouts.insert(4, record.payout_4x.unwrap() as u32);
-        }
-        if record.payout_5x.is_some() && record.payout_5x.unwrap() > 0 {
-            payouts.insert(5, record.payout_5x.unwrap() as u32);
-        }
-        if record.payout_6x.is_some() && record.payout_6x.unwrap() > 0 {
-            payouts.insert(6, record.payout_6x.unwrap() as u32);
-        }
+        let payouts = payout_tables.remove(&record.id).unwrap_or_default();
 
         let symbol_type = match record.symbol_type.as_str() {
             "wild" => SymbolType::Wild,
             "scatter" => SymbolType::Scatter,
             "bonus" => SymbolType::Bonus,
+            "money" => SymbolType::Money,
             _ => SymbolType::Normal,
         };
 
@@ -465,18 +2209,29 @@ pub async fn build_slot_machine(
         });
     }
 
-    // 获取卷轴配置
+    // 获取卷轴配置，基础卷轴以外的卷轴组单独保存
     let reel_records = sqlx::query!(
-        "SELECT * FROM slot_reel_symbols WHERE slot_config_id = $1 ORDER BY reel_number, position",
+        r#"
+        SELECT id, slot_config_id, reel_set, reel_number, position, symbol_id, weight
+        FROM slot_reel_symbols
+        WHERE slot_config_id = $1
+        ORDER BY reel_number, position
+        "#,
         config_id
     )
-    .fetch_all(pool)
+    .fetch_all(&mut *conn)
     .await
-    .map_err(|e| format!("Failed to fetch reels: {e}"))?;
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch reels: {e}")))?;
 
     let mut reel_compositions = HashMap::new();
+    let mut reel_sets: HashMap<String, HashMap<usize, Vec<(i32, u32)>>> = HashMap::new();
     for record in reel_records {
-        reel_compositions
+        let compositions = if record.reel_set == BASE_REEL_SET {
+            &mut reel_compositions
+        } else {
+            reel_sets.entry(record.reel_set).or_default()
+        };
+        compositions
             .entry(record.reel_number as usize)
             .or_insert_with(Vec::new)
             .push((
@@ -485,19 +2240,30 @@ pub async fn build_slot_machine(
             ));
     }
 
+    let base_reel_set_weights = sqlx::query!(
+        "SELECT reel_set, weight FROM slot_reel_set_weights WHERE slot_config_id = $1",
+        config_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch reel set weights: {e}")))?
+    .into_iter()
+    .map(|record| (record.reel_set, record.weight as u32))
+    .collect();
+
     // 获取支付线
     let payline_records = sqlx::query!(
-        "SELECT * FROM slot_paylines WHERE slot_config_id = $1 AND is_active = true ORDER BY line_number",
+        "SELECT line_number, pattern, is_active FROM slot_paylines WHERE slot_config_id = $1 AND is_active = true ORDER BY line_number",
         config_id
     )
-    .fetch_all(pool)
+    .fetch_all(&mut *conn)
     .await
-    .map_err(|e| format!("Failed to fetch paylines: {e}"))?;
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch paylines: {e}")))?;
 
     let mut paylines = Vec::new();
     for record in payline_records {
         let pattern: Vec<Vec<i32>> = serde_json::from_value(record.pattern)
-            .map_err(|e| format!("Failed to parse payline pattern: {e}"))?;
+            .map_err(|e| ApiError::Internal(format!("Failed to parse payline pattern: {e}")))?;
 
         let pattern_tuples: Vec<(usize, usize)> = pattern
             .into_iter()
@@ -511,6 +2277,143 @@ pub async fn build_slot_machine(
         });
     }
 
+    // 获取Scatter触发表
+    let scatter_triggers = sqlx::query!(
+        "SELECT scatter_count, free_spins FROM slot_scatter_triggers WHERE slot_config_id = $1 ORDER BY scatter_count",
+        config_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch scatter triggers: {e}")))?
+    .into_iter()
+    .map(|record| ScatterTrigger {
+        scatter_count: record.scatter_count as usize,
+        free_spins: record.free_spins as u32,
+    })
+    .collect();
+
+    // 获取Wild属性
+    let wild_features = sqlx::query!(
+        r#"
+        SELECT symbol_id as "symbol_id!", multiplier, cannot_substitute, expanding, sticky, walking
+        FROM slot_wild_features WHERE slot_config_id = $1
+        "#,
+        config_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch wild features: {e}")))?
+    .into_iter()
+    .map(|record| WildFeature {
+        symbol_id: record.symbol_id,
+        multiplier: record.multiplier as u32,
+        cannot_substitute: record.cannot_substitute,
+        expanding: record.expanding,
+        sticky: record.sticky,
+        walking: record.walking,
+    })
+    .collect();
+
+    // 获取Bonus选宝游戏
+    let bonus_game = sqlx::query!(
+        "SELECT trigger_count, picks, board_size FROM slot_bonus_games WHERE slot_config_id = $1",
+        config_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch bonus game: {e}")))?;
+
+    let bonus_game = match bonus_game {
+        Some(game) => {
+            let prizes = sqlx::query!(
+                "SELECT multiplier, weight FROM slot_bonus_prizes WHERE slot_config_id = $1 ORDER BY id",
+                config_id
+            )
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to fetch bonus prizes: {e}")))?
+            .into_iter()
+            .map(|prize| BonusPrize {
+                multiplier: prize.multiplier as u32,
+                weight: prize.weight as u32,
+            })
+            .collect();
+
+            Some(BonusGameConfig {
+                trigger_count: game.trigger_count as usize,
+                picks: game.picks as u32,
+                board_size: game.board_size as usize,
+                prizes,
+            })
+        }
+        None => None,
+    };
+
+    // 获取Hold and Spin
+    let hold_and_spin = sqlx::query!(
+        "SELECT trigger_count, respins FROM slot_hold_and_spin WHERE slot_config_id = $1",
+        config_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch hold and spin: {e}")))?;
+
+    let hold_and_spin = match hold_and_spin {
+        Some(settings) => {
+            let values = sqlx::query!(
+                "SELECT multiplier, jackpot, weight FROM slot_money_values WHERE slot_config_id = $1 ORDER BY id",
+                config_id
+            )
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to fetch money values: {e}")))?
+            .into_iter()
+            .map(|value| MoneyValue {
+                multiplier: value.multiplier as u32,
+                jackpot: match value.jackpot.as_deref() {
+                    Some("mini") => Some(JackpotTier::Mini),
+                    Some("minor") => Some(JackpotTier::Minor),
+                    Some("major") => Some(JackpotTier::Major),
+                    Some("grand") => Some(JackpotTier::Grand),
+                    _ => None,
+                },
+                weight: value.weight as u32,
+            })
+            .collect();
+
+            Some(HoldAndSpinConfig {
+                trigger_count: settings.trigger_count as usize,
+                respins: settings.respins as u32,
+                values,
+            })
+        }
+        None => None,
+    };
+
+    // 获取加注和购买免费旋转
+    let ante_bet = sqlx::query!(
+        "SELECT cost_percent FROM slot_ante_bets WHERE slot_config_id = $1",
+        config_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch ante bet: {e}")))?
+    .map(|record| AnteBetConfig {
+        cost_percent: record.cost_percent as u32,
+    });
+
+    let bonus_buy = sqlx::query!(
+        "SELECT cost_multiplier, free_spins FROM slot_bonus_buys WHERE slot_config_id = $1",
+        config_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Failed to fetch bonus buy: {e}")))?
+    .map(|record| BonusBuyConfig {
+        cost_multiplier: record.cost_multiplier as u32,
+        free_spins: record.free_spins as u32,
+    });
+
     // 构建slot配置
     let slot_config = SlotConfig {
         id: config.id,
@@ -525,40 +2428,949 @@ pub async fn build_slot_machine(
         max_bet: config.max_bet.unwrap_or(1000) as u32,
         wild_enabled: config.wild_enabled.unwrap_or(false),
         free_spins_enabled: config.free_spins_enabled.unwrap_or(false),
+        free_spins_multiplier: config.free_spins_multiplier.max(1) as u32,
+        reel_mode: match config.reel_mode.as_str() {
+            "stepper" => ReelMode::Stepper,
+            _ => ReelMode::Weighted,
+        },
+        evaluation_mode: match config.evaluation_mode.as_str() {
+            "cluster" => EvaluationMode::Cluster,
+            _ => EvaluationMode::Lines,
+        },
+        cascading: config.cascading,
+        cascade_multipliers: config
+            .cascade_multipliers
+            .iter()
+            .map(|&m| m.max(1) as u32)
+            .collect(),
         rtp_percentage: config
             .rtp_percentage
             .map(|r| r.to_string().parse::<f64>().unwrap_or(96.0))
             .unwrap_or(96.0),
     };
 
-    let builder = SlotConfigBuilder {
+    Ok(SlotConfigBuilder {
         config: slot_config,
         symbols,
         reel_compositions,
+        reel_sets,
+        base_reel_set_weights,
         paylines,
+        scatter_triggers,
+        bonus_game,
+        wild_features,
+        hold_and_spin,
+        ante_bet,
+        bonus_buy,
+    })
+}
+
+// 计算slot配置的理论RTP、命中率和波动性
+pub async fn get_slot_math(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let machine = build_playable_machine(pool.get_ref(), path.into_inner()).await?;
+
+    // 完整周期枚举可能需要数秒，放到阻塞线程池中执行
+    let report = web::block(move || slot_math::analyze(&machine))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(ApiError::BadRequest)?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+// 校验slot配置，返回错误和警告；存在错误的配置不能旋转
+pub async fn validate_slot_config(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let builder = load_slot_config_builder(pool.get_ref(), path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(builder.validate()))
+}
+
+// RTP求解器的权重范围，按卷轴位置指定
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReelWeightBound {
+    pub reel_number: i32,
+    pub position: i32,
+    pub min_weight: u32,
+    pub max_weight: u32,
+}
+
+// 比例均为小数（0.965 即 96.5%），target_rtp 默认使用配置的 rtp_percentage
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RtpSolverRequest {
+    pub target_rtp: Option<f64>,
+    pub volatility_min: Option<f64>,
+    pub volatility_max: Option<f64>,
+    pub rtp_tolerance: Option<f64>,
+    pub max_change_percent: Option<u32>,
+    pub max_evaluations: Option<u32>,
+    #[serde(default)]
+    pub bounds: Vec<ReelWeightBound>,
+}
+
+// 应用求解结果：可直接提交预览返回的 changes，带 old_weight 时检查权重未被修改过
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReelWeightChange {
+    pub reel_number: i32,
+    pub position: i32,
+    pub new_weight: u32,
+    pub old_weight: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApplyWeightsRequest {
+    pub changes: Vec<ReelWeightChange>,
+}
+
+// 每个卷轴按顺序排列的位置编号，与 build_slot_machine 构建卷轴条的顺序一致
+async fn reel_positions(pool: &Pool<Postgres>, config_id: i32) -> Result<Vec<Vec<i32>>, ApiError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT reel_number, position FROM slot_reel_symbols
+        WHERE slot_config_id = $1 AND reel_set = 'base'
+        ORDER BY reel_number, position
+        "#,
+        config_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut positions: Vec<Vec<i32>> = Vec::new();
+    for row in rows {
+        let reel = row.reel_number as usize;
+        if positions.len() <= reel {
+            positions.resize(reel + 1, Vec::new());
+        }
+        positions[reel].push(row.position);
+    }
+    Ok(positions)
+}
+
+// 预览RTP求解结果：在范围内调整卷轴权重，返回建议的权重和调整后的数学指标，不修改配置
+pub async fn preview_rtp_solver(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    req: web::Json<RtpSolverRequest>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+    let machine = build_playable_machine(pool.get_ref(), config_id).await?;
+    let positions = reel_positions(pool.get_ref(), config_id).await?;
+
+    let req = req.into_inner();
+    let bounds = req
+        .bounds
+        .iter()
+        .map(|bound| {
+            positions
+                .get(bound.reel_number as usize)
+                .and_then(|reel| reel.iter().position(|&p| p == bound.position))
+                .map(|index| WeightBound {
+                    reel_number: bound.reel_number as usize,
+                    index,
+                    min_weight: bound.min_weight,
+                    max_weight: bound.max_weight,
+                })
+                .ok_or_else(|| {
+                    ApiError::NotFound(format!(
+                        "Reel {} has no symbol at position {}",
+                        bound.reel_number, bound.position
+                    ))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let params = SolverParams {
+        target_rtp: req.target_rtp,
+        volatility_min: req.volatility_min,
+        volatility_max: req.volatility_max,
+        rtp_tolerance: req.rtp_tolerance,
+        max_change_percent: req.max_change_percent,
+        bounds,
+        max_evaluations: req.max_evaluations,
     };
 
-    Ok(builder.build())
+    // 每一步都要重新计算数学模型，放到阻塞线程池中执行
+    let result = web::block(move || rtp_solver::solve(&machine, &params))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(ApiError::BadRequest)?;
+
+    let changes: Vec<_> = result
+        .changes
+        .iter()
+        .map(|change| {
+            serde_json::json!({
+                "reel_number": change.reel_number,
+                "position": positions[change.reel_number][change.index],
+                "symbol_id": change.symbol_id,
+                "symbol_name": change.symbol_name,
+                "old_weight": change.old_weight,
+                "new_weight": change.new_weight
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "slot_config_id": config_id,
+        "target_rtp": result.target_rtp,
+        "volatility_min": result.volatility_min,
+        "volatility_max": result.volatility_max,
+        "converged": result.converged,
+        "evaluations": result.evaluations,
+        "before": result.before,
+        "after": result.after,
+        "changes": changes,
+        "report": result.report
+    })))
+}
+
+// 应用建议的卷轴权重（整体在一个事务中更新），返回更新后的数学指标
+pub async fn apply_rtp_solver(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    req: web::Json<ApplyWeightsRequest>,
+) -> Result<HttpResponse> {
+    let config_id = path.into_inner();
+    if req.changes.is_empty() {
+        return Err(ApiError::BadRequest("No weight changes to apply".to_string()).into());
+    }
+    // 权重保存为正的 INTEGER，超出范围的值在写入前拒绝
+    if let Some(change) = req
+        .changes
+        .iter()
+        .find(|c| !(1..=i32::MAX as u32).contains(&c.new_weight))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Weight of reel {} position {} must be between 1 and {}",
+            change.reel_number,
+            change.position,
+            i32::MAX
+        ))
+        .into());
+    }
+
+    let mut tx = pool.begin().await.map_err(ApiError::from)?;
+    for change in &req.changes {
+        let weight = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(weight, 1) as "weight!" FROM slot_reel_symbols
+            WHERE slot_config_id = $1 AND reel_number = $2 AND position = $3 AND reel_set = 'base'
+            FOR UPDATE
+            "#,
+            config_id,
+            change.reel_number,
+            change.position
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Reel {} has no symbol at position {}",
+                change.reel_number, change.position
+            ))
+        })?;
+
+        if let Some(old_weight) = change.old_weight {
+            if weight as u32 != old_weight {
+                return Err(ApiError::Conflict(format!(
+                    "Weight of reel {} position {} changed from {} to {} since the preview",
+                    change.reel_number, change.position, old_weight, weight
+                ))
+                .into());
+            }
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE slot_reel_symbols SET weight = $4
+            WHERE slot_config_id = $1 AND reel_number = $2 AND position = $3 AND reel_set = 'base'
+            "#,
+            config_id,
+            change.reel_number,
+            change.position,
+            change.new_weight as i32
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ApiError::from)?;
+    }
+    tx.commit().await.map_err(ApiError::from)?;
+
+    let machine = build_slot_machine(pool.get_ref(), config_id)
+        .await
+        .map_err(ApiError::Internal)?;
+    let report = web::block(move || slot_math::analyze(&machine))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(ApiError::BadRequest)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Reel weights updated successfully",
+        "updated": req.changes.len(),
+        "report": report
+    })))
 }
 
 // 测试spin接口
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SpinRequest {
     pub slot_config_id: i32,
-    pub bet_per_line: u32,
+    #[serde(flatten)]
+    pub bet: BetRequest, // 每线投注（或硬币面值 × 每线硬币数）和激活的支付线数
+    pub player_id: Option<i32>,
+    pub session_id: Option<i32>,
+    #[serde(default)]
+    pub provably_fair: bool,
+    #[serde(default)]
+    pub bet_mode: BetMode,
+}
+
+// 旋转响应：在旋转结果的基础上附加钱包、公平性和免费旋转信息
+#[derive(Debug, Serialize)]
+pub struct SpinResponse {
+    #[serde(flatten)]
+    pub result: UniversalSpinResult,
+    pub spin_id: i32,
+    pub balance: Option<i64>,
+    pub fair: Option<FairProof>,
+    pub free_spins_feature: Option<FreeSpinFeature>,
+    pub bonus_round: Option<BonusRound>,
+    pub hold_and_spin_round: Option<HoldAndSpinRound>,
 }
 
 pub async fn test_spin(
     pool: web::Data<Pool<Postgres>>,
     req: web::Json<SpinRequest>,
 ) -> Result<HttpResponse> {
-    match build_slot_machine(pool.get_ref(), req.slot_config_id).await {
-        Ok(machine) => {
-            let result = machine.spin(req.bet_per_line);
-            Ok(HttpResponse::Ok().json(result))
+    let (machine, version_id) = load_live_machine(pool.get_ref(), req.slot_config_id).await?;
+
+    let response = execute_spin(pool.get_ref(), &machine, version_id, &req).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+// 付费旋转：扣除投注、旋转、记录、派彩，并在触发时保存免费旋转，全部在同一事务内完成
+async fn execute_spin(
+    pool: &Pool<Postgres>,
+    machine: &UniversalSlotMachine,
+    version_id: i32,
+    req: &SpinRequest,
+) -> Result<SpinResponse, ApiError> {
+    let bet = machine.validate_bet(&req.bet)?;
+    // 只选择部分支付线时，旋转、记录和免费旋转都使用只激活这些支付线的机器
+    let machine = &*machine.with_active_lines(bet.lines);
+    // 赢金、选宝和钱币奖金都按总投注计算，加注和购买免费旋转额外收取的部分只影响支付金额
+    let total_bet = bet.total_bet as i64;
+    let bet_cost = machine
+        .bet_cost(bet.bet_per_line, req.bet_mode)
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Slot config {} does not offer bet mode '{}'",
+                machine.config.id,
+                req.bet_mode.as_str()
+            ))
+        })? as i64;
+    let mut tx = pool.begin().await?;
+
+    // 地区取自玩家记录而不是请求，客户端不能通过换地区绕过限制
+    let jurisdiction = player_jurisdiction(&mut tx, req.player_id).await?;
+    if !bet_mode_allowed(
+        &mut tx,
+        machine.config.id,
+        jurisdiction.as_deref(),
+        req.bet_mode,
+    )
+    .await?
+    {
+        return Err(ApiError::Forbidden(match jurisdiction {
+            Some(jurisdiction) => format!(
+                "Bet mode '{}' is not available in jurisdiction {}",
+                req.bet_mode.as_str(),
+                jurisdiction.to_uppercase()
+            ),
+            None => format!(
+                "Bet mode '{}' requires a player with a known jurisdiction",
+                req.bet_mode.as_str()
+            ),
+        }));
+    }
+
+    if let Some(player_id) = req.player_id {
+        // 先锁定玩家行，同一玩家的并发旋转排队执行，不会同时通过下面的检查各自触发特性
+        wallet::lock_balance(&mut tx, player_id, bet_cost).await?;
+        if free_spins::active_feature(&mut tx, player_id, machine.config.id)
+            .await?
+            .is_some()
+        {
+            return Err(ApiError::Conflict(
+                "Free spins in progress, play them via /api/slot-config/free-spin".to_string(),
+            ));
         }
-        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": e
-        }))),
+        if bonus_game::has_active_round(&mut tx, player_id, machine.config.id).await? {
+            return Err(ApiError::Conflict(
+                "Bonus round in progress, finish it via /api/slot-config/bonus/pick".to_string(),
+            ));
+        }
+        if hold_and_spin::has_active_round(&mut tx, player_id, machine.config.id).await? {
+            return Err(ApiError::Conflict(
+                "Hold and spin in progress, finish it via /api/slot-config/hold-and-spin/respin"
+                    .to_string(),
+            ));
+        }
+    }
+
+    let (result, fair) = if req.provably_fair {
+        let player_id = req.player_id.ok_or_else(|| {
+            ApiError::BadRequest("player_id is required for provably fair spins".to_string())
+        })?;
+        let (result, proof) = provably_fair::spin_with_active_seed(
+            &mut tx,
+            machine,
+            player_id,
+            bet.bet_per_line,
+            req.bet_mode,
+        )
+        .await?;
+        (result, Some(proof))
+    } else {
+        (machine.spin_mode(bet.bet_per_line, req.bet_mode), None)
+    };
+
+    let mut record = SpinRecord::universal(machine, &result, req.player_id, req.session_id);
+    record.config_version_id = Some(version_id);
+    if let Some(proof) = &fair {
+        record.fair_seed_id = Some(proof.seed_id);
+        record.fair_nonce = Some(proof.nonce as i32);
+    }
+    record.active_lines = bet.lines.map(|lines| lines as i32);
+    let spin_id = record_spin(&mut tx, &record).await?;
+
+    let mut balance = None;
+    let mut free_spins_feature = None;
+    let mut bonus_round = None;
+    let mut hold_and_spin_round = None;
+    if let Some(player_id) = req.player_id {
+        balance =
+            Some(wallet::settle(&mut tx, player_id, bet_cost, result.total_win as i64).await?);
+
+        if result.free_spins > 0 {
+            free_spins_feature = Some(
+                free_spins::award(
+                    &mut tx,
+                    player_id,
+                    req.session_id,
+                    machine,
+                    Some(version_id),
+                    spin_id,
+                    bet.bet_per_line,
+                    bet.lines,
+                    result.free_spins,
+                )
+                .await?,
+            );
+        }
+
+        if result.bonus_triggered {
+            // 面板不使用旋转种子（会随结果返回），而是使用只保存在服务器上的独立种子
+            let board_seed = rand::rng().random();
+            bonus_round = bonus_game::start_round(
+                &mut tx, player_id, machine, spin_id, total_bet, board_seed,
+            )
+            .await?;
+        }
+
+        if result.hold_and_spin_triggered {
+            hold_and_spin_round = hold_and_spin::start_round(
+                &mut tx,
+                player_id,
+                machine,
+                Some(version_id),
+                spin_id,
+                total_bet,
+                &result,
+                result.seed,
+            )
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(SpinResponse {
+        result,
+        spin_id,
+        balance,
+        fair,
+        free_spins_feature,
+        bonus_round,
+        hold_and_spin_round,
+    })
+}
+
+// 重放接口：根据配置ID和种子重新计算旋转结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplayRequest {
+    pub slot_config_id: i32,
+    pub seed: u64,
+    pub bet_per_line: u32,
+    // 旋转记录中的 config_version_id，未指定时使用线上版本
+    #[serde(default)]
+    pub version_id: Option<i32>,
+    // 重放免费旋转时传入该次旋转前保留的Wild
+    #[serde(default)]
+    pub held_wilds: Option<Vec<HeldWild>>,
+    // 旋转记录中的 bet_mode，默认标准旋转
+    #[serde(default)]
+    pub bet_mode: BetMode,
+    // 旋转记录中的 active_lines，未指定时使用全部支付线
+    #[serde(default)]
+    pub lines: Option<u32>,
+    // 旋转记录的ID，触发的选宝回合结束后返回完整面板
+    #[serde(default)]
+    pub spin_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayResponse {
+    #[serde(flatten)]
+    pub result: UniversalSpinResult,
+    pub bonus_board: Option<Vec<u32>>,
+    pub hold_and_spin: Option<HoldAndSpinState>, // 触发时重放到结束的完整状态
+}
+
+pub async fn replay_spin(
+    pool: web::Data<Pool<Postgres>>,
+    req: web::Json<ReplayRequest>,
+) -> Result<HttpResponse> {
+    let machine = load_machine_at(pool.get_ref(), req.slot_config_id, req.version_id).await?;
+    let machine = machine.with_active_lines(req.lines);
+
+    let result = match &req.held_wilds {
+        Some(held_wilds) => machine.spin_free_seeded(req.bet_per_line, req.seed, held_wilds),
+        None => machine.spin_seeded_mode(req.bet_per_line, req.bet_mode, req.seed),
+    };
+    // 选宝面板使用独立的种子，只有指定了旋转记录且回合已经结束时才返回
+    let bonus_board = match req.spin_id {
+        Some(spin_id) if result.bonus_triggered => {
+            bonus_game::completed_board(pool.get_ref(), spin_id).await?
+        }
+        _ => None,
+    };
+    let hold_and_spin = match &req.held_wilds {
+        Some(_) => None,
+        None => machine.play_hold_and_spin(&result, req.seed),
+    };
+    Ok(HttpResponse::Ok().json(ReplayResponse {
+        result,
+        bonus_board,
+        hold_and_spin,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::migrate::Migrator;
+    use std::borrow::Cow;
+
+    static MIGRATOR: Migrator = sqlx::migrate!();
+
+    #[sqlx::test(migrations = false)]
+    async fn test_payout_migration_moves_legacy_columns(pool: Pool<Postgres>) {
+        // 先运行到赔付表迁移之前，写入旧的 payout_Nx 列
+        let legacy = Migrator {
+            migrations: Cow::Owned(
+                MIGRATOR
+                    .iter()
+                    .filter(|m| m.version < 10)
+                    .cloned()
+                    .collect(),
+            ),
+            ..Migrator::DEFAULT
+        };
+        legacy.run(&pool).await.unwrap();
+        sqlx::query("INSERT INTO slot_configurations (id, name) VALUES (1, 'Legacy')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO slot_symbols (id, slot_config_id, name, symbol_type, value, payout_3x, payout_5x)
+             VALUES (1, 1, 'Cherry', 'normal', 1, 5, 20)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        MIGRATOR.run(&pool).await.unwrap();
+
+        let payouts: Vec<(i32, i32)> = sqlx::query_as(
+            "SELECT symbol_count, payout FROM slot_symbol_payouts WHERE symbol_id = 1 ORDER BY symbol_count",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(payouts, vec![(3, 5), (5, 20)]);
+
+        let builder = load_slot_config_builder(&pool, 1).await.unwrap();
+        assert_eq!(builder.symbols[0].payouts.get(&3), Some(&5));
+        assert_eq!(builder.symbols[0].payouts.get(&4), None);
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    async fn test_reel_weights_must_be_positive(pool: Pool<Postgres>) {
+        sqlx::query(
+            "INSERT INTO slot_symbols (id, slot_config_id, name, symbol_type, value) VALUES (1, 1, 'Cherry', 'normal', 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let err = add_reel_symbol(
+            web::Data::new(pool.clone()),
+            web::Json(CreateReelSymbolRequest {
+                slot_config_id: 1,
+                reel_set: None,
+                reel_number: 0,
+                position: 0,
+                symbol_id: 1,
+                weight: 0,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::BAD_REQUEST
+        );
+
+        // 绕过接口直接写入也会被约束拒绝，避免抽样时 random_range(0..0) panic
+        let inserted = sqlx::query(
+            "INSERT INTO slot_reel_symbols (slot_config_id, reel_number, position, symbol_id, weight) VALUES (1, 0, 0, 1, 0)",
+        )
+        .execute(&pool)
+        .await;
+        assert!(inserted.is_err());
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    async fn test_replay_of_unpublished_config_requires_valid_draft(pool: Pool<Postgres>) {
+        // 草稿没有卷轴，未通过校验，不能用于重放
+        let result = crate::config_versions::load_machine_at(&pool, 1, None).await;
+        assert!(matches!(result, Err(ApiError::Unprocessable(_))));
+    }
+
+    #[sqlx::test(fixtures("playable"))]
+    async fn test_symbol_on_reels_cannot_be_deleted(pool: Pool<Postgres>) {
+        let err = delete_symbol(web::Data::new(pool.clone()), web::Path::from(2))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::CONFLICT
+        );
+
+        // 移除卷轴位置后可以删除，其他Wild的不能替代列表也随之清理
+        sqlx::query("DELETE FROM slot_reel_symbols WHERE symbol_id = 2")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO slot_wild_features (slot_config_id, symbol_id, cannot_substitute) VALUES (1, 1, '{2}')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        delete_symbol(web::Data::new(pool.clone()), web::Path::from(2))
+            .await
+            .unwrap();
+        let cannot_substitute: Vec<i32> = sqlx::query_scalar(
+            "SELECT cannot_substitute FROM slot_wild_features WHERE symbol_id = 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(cannot_substitute.is_empty());
+
+        let err = delete_symbol(web::Data::new(pool.clone()), web::Path::from(2))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::NOT_FOUND
+        );
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    async fn test_config_with_spin_history_cannot_be_deleted(pool: Pool<Postgres>) {
+        sqlx::query(
+            "INSERT INTO slot_spin_history (player_id, slot_config_id, bet_amount, win_amount, symbols) VALUES (1, 1, 10, 0, '[]')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let err = delete_slot_config(web::Data::new(pool.clone()), web::Path::from(1))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::CONFLICT
+        );
+
+        // 没有旋转记录的配置可以删除，删除后再删返回404
+        delete_slot_config(web::Data::new(pool.clone()), web::Path::from(2))
+            .await
+            .unwrap();
+        let err = delete_slot_config(web::Data::new(pool.clone()), web::Path::from(2))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::NOT_FOUND
+        );
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    async fn test_config_rename_rejects_taken_name(pool: Pool<Postgres>) {
+        let err = update_slot_config(
+            web::Data::new(pool.clone()),
+            web::Path::from(2),
+            web::Json(UpdateSlotConfigRequest {
+                name: Some("First".to_string()),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::CONFLICT
+        );
+
+        // 保留自己的名称不算冲突
+        update_slot_config(
+            web::Data::new(pool.clone()),
+            web::Path::from(2),
+            web::Json(UpdateSlotConfigRequest {
+                name: Some("Second".to_string()),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(fixtures("playable"))]
+    async fn test_base_reel_set_cannot_be_deleted(pool: Pool<Postgres>) {
+        let err = delete_reel_set(
+            web::Data::new(pool.clone()),
+            web::Path::from((1, BASE_REEL_SET.to_string())),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::BAD_REQUEST
+        );
+        let positions: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM slot_reel_symbols WHERE slot_config_id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(positions, 10);
+    }
+
+    #[sqlx::test(fixtures("playable"))]
+    async fn test_ante_bet_requires_known_player_jurisdiction(pool: Pool<Postgres>) {
+        sqlx::query("INSERT INTO slot_ante_bets (slot_config_id, cost_percent) VALUES (1, 25)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO players (id, username, email, jurisdiction) VALUES
+               (1, 'alice', 'alice@example.com', NULL), (2, 'bob', 'bob@example.com', 'GB')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        crate::config_versions::publish(web::Data::new(pool.clone()), web::Path::from(1), None)
+            .await
+            .unwrap();
+
+        let spin = |player_id: Option<i32>, bet_mode: BetMode| {
+            test_spin(
+                web::Data::new(pool.clone()),
+                web::Json(SpinRequest {
+                    slot_config_id: 1,
+                    bet: BetRequest {
+                        bet_per_line: Some(1),
+                        ..Default::default()
+                    },
+                    player_id,
+                    session_id: None,
+                    provably_fair: false,
+                    bet_mode,
+                }),
+            )
+        };
+        let forbidden = |result: Result<HttpResponse>| {
+            result.unwrap_err().as_response_error().status_code()
+                == actix_web::http::StatusCode::FORBIDDEN
+        };
+
+        // 地区未知（玩家未登记或匿名旋转）时拒绝加注，普通投注不受影响
+        assert!(forbidden(spin(Some(1), BetMode::Ante).await));
+        assert!(forbidden(spin(None, BetMode::Ante).await));
+        spin(Some(1), BetMode::Standard).await.unwrap();
+
+        // 已知地区没有规则时可用，关闭后按玩家记录的地区拒绝
+        spin(Some(2), BetMode::Ante).await.unwrap();
+        sqlx::query(
+            "INSERT INTO slot_jurisdiction_rules (slot_config_id, jurisdiction, ante_bet_enabled) VALUES (1, 'GB', false)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert!(forbidden(spin(Some(2), BetMode::Ante).await));
+    }
+
+    #[sqlx::test(fixtures("playable"))]
+    async fn test_solver_weights_out_of_range_are_rejected(pool: Pool<Postgres>) {
+        for new_weight in [0, i32::MAX as u32 + 1, u32::MAX] {
+            let err = apply_rtp_solver(
+                web::Data::new(pool.clone()),
+                web::Path::from(1),
+                web::Json(ApplyWeightsRequest {
+                    changes: vec![ReelWeightChange {
+                        reel_number: 0,
+                        position: 0,
+                        new_weight,
+                        old_weight: None,
+                    }],
+                }),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(
+                err.as_response_error().status_code(),
+                actix_web::http::StatusCode::BAD_REQUEST
+            );
+        }
+        let weight: i32 = sqlx::query_scalar(
+            "SELECT weight FROM slot_reel_symbols WHERE slot_config_id = 1 AND reel_number = 0 AND position = 0",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(weight, 3);
+    }
+
+    #[sqlx::test(fixtures("playable"))]
+    async fn test_wild_and_bonus_setters_reject_invalid_values(pool: Pool<Postgres>) {
+        sqlx::raw_sql(
+            "INSERT INTO slot_configurations (id, name) VALUES (2, 'Other');
+             INSERT INTO slot_symbols (id, slot_config_id, name, symbol_type, value) VALUES
+               (3, 1, 'Wild', 'wild', 0), (4, 2, 'Star', 'normal', 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let status = |err: actix_web::Error| err.as_response_error().status_code();
+        let wild = |multiplier, cannot_substitute| {
+            set_wild_feature(
+                web::Data::new(pool.clone()),
+                web::Json(SetWildFeatureRequest {
+                    symbol_id: 3,
+                    multiplier,
+                    cannot_substitute,
+                    expanding: false,
+                    sticky: false,
+                    walking: false,
+                }),
+            )
+        };
+
+        let err = wild(0, Vec::new()).await.unwrap_err();
+        assert_eq!(status(err), actix_web::http::StatusCode::BAD_REQUEST);
+        // 其他配置的符号不能出现在不能替代列表中
+        let err = wild(2, vec![1, 4]).await.unwrap_err();
+        assert_eq!(status(err), actix_web::http::StatusCode::BAD_REQUEST);
+        let resp = wild(2, vec![1]).await.unwrap();
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+        let err = set_bonus_game(
+            web::Data::new(pool.clone()),
+            web::Json(SetBonusGameRequest {
+                slot_config_id: 1,
+                trigger_count: 3,
+                picks: 0,
+                board_size: 5,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status(err), actix_web::http::StatusCode::BAD_REQUEST);
+
+        for (multiplier, weight) in [(-1, 1), (5, 0)] {
+            let err = add_bonus_prize(
+                web::Data::new(pool.clone()),
+                web::Json(CreateBonusPrizeRequest {
+                    slot_config_id: 1,
+                    multiplier,
+                    weight,
+                }),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(status(err), actix_web::http::StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[sqlx::test(fixtures("playable"))]
+    async fn test_reels_only_use_symbols_of_their_config(pool: Pool<Postgres>) {
+        sqlx::raw_sql(
+            "INSERT INTO slot_configurations (id, name) VALUES (2, 'Other');
+             INSERT INTO slot_symbols (id, slot_config_id, name, symbol_type, value) VALUES (3, 2, 'Star', 'normal', 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let err = add_reel_symbol(
+            web::Data::new(pool.clone()),
+            web::Json(CreateReelSymbolRequest {
+                slot_config_id: 1,
+                reel_set: None,
+                reel_number: 0,
+                position: 2,
+                symbol_id: 3,
+                weight: 1,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::NOT_FOUND
+        );
+
+        let err = update_reel_symbol(
+            web::Data::new(pool.clone()),
+            web::Path::from((1, 0, 0)),
+            web::Query(ReelSetQuery { reel_set: None }),
+            web::Json(UpdateReelSymbolRequest {
+                symbol_id: Some(3),
+                weight: None,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            actix_web::http::StatusCode::NOT_FOUND
+        );
     }
 }
@@ -0,0 +1,1117 @@
+use crate::universal_slots::{
+    BetMode, EvaluationMode, ReelMode, ReelStrip, SlotSymbol, SymbolType, UniversalSlotMachine,
+    FREE_SPINS_REEL_SET,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+// 完整周期枚举的组合数上限，超过时使用解析计算
+const FULL_CYCLE_LIMIT: u64 = 10_000_000;
+// 波动指数 = 标准差 x 90%置信度的z值
+const VOLATILITY_CONFIDENCE_Z: f64 = 1.645;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MathMethod {
+    // stepper模式：枚举所有停止位置组合，结果精确
+    FullCycle,
+    // 按卷轴独立计算每条线/每种符号的概率，RTP精确，命中率和标准差假设各赢奖相互独立
+    Analytic,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolContribution {
+    pub symbol_id: i32,
+    pub name: String,
+    pub rtp: f64,
+}
+
+// 加注或购买免费旋转的定价，金额均为总投注的倍数
+#[derive(Debug, Clone, Serialize)]
+pub struct BetModePricing {
+    pub cost: f64,         // 每次旋转收取的金额
+    pub expected_win: f64, // 每次旋转的期望赢金，含触发的免费旋转和选宝
+    pub rtp: f64,          // expected_win / cost
+    pub fair_cost: f64,    // 回报率与整个游戏的理论RTP相同时的价格
+}
+
+// 所有比例均相对于总投注，例如 rtp = 0.965 表示 96.5%
+#[derive(Debug, Clone, Serialize)]
+pub struct MathReport {
+    pub slot_config_id: i32,
+    pub method: MathMethod,
+    pub cycle_size: Option<u64>,
+    pub rtp: f64,
+    pub base_game_rtp: f64,
+    pub line_rtp: f64,
+    pub scatter_rtp: f64,
+    pub free_spins_rtp: f64,
+    pub bonus_rtp: f64,
+    pub hit_frequency: f64,
+    pub standard_deviation: f64,
+    pub volatility_index: f64,
+    pub free_spins_frequency: f64,
+    pub expected_free_spins: f64,
+    pub bonus_frequency: f64,
+    pub configured_rtp: f64,
+    pub ante_bet: Option<BetModePricing>,
+    pub bonus_buy: Option<BetModePricing>,
+    pub symbols: Vec<SymbolContribution>,
+    pub notes: Vec<String>,
+}
+
+// 单个卷轴一次旋转的结果分布
+struct ReelModel<'a> {
+    strip: &'a ReelStrip,
+    mode: ReelMode,
+    rows: Vec<(usize, f64)>, // (可见行数, 概率)
+    stop_probs: Vec<f64>,    // 每个位置被抽中的概率
+}
+
+impl<'a> ReelModel<'a> {
+    fn new(machine: &UniversalSlotMachine, strip: &'a ReelStrip) -> Result<Self, String> {
+        let total_weight: u64 = strip.symbols.iter().map(|(_, weight)| *weight as u64).sum();
+        if total_weight == 0 {
+            return Err(format!(
+                "Reel {} has no weighted symbols",
+                strip.reel_number
+            ));
+        }
+
+        let config = &machine.config;
+        let rows = if config.is_megaway {
+            let count = config.max_megaway_rows + 1 - config.min_megaway_rows;
+            (config.min_megaway_rows..=config.max_megaway_rows)
+                .map(|rows| (rows, 1.0 / count as f64))
+                .collect()
+        } else {
+            vec![(config.rows, 1.0)]
+        };
+
+        Ok(Self {
+            strip,
+            mode: config.reel_mode,
+            rows,
+            stop_probs: strip
+                .symbols
+                .iter()
+                .map(|(_, weight)| *weight as f64 / total_weight as f64)
+                .collect(),
+        })
+    }
+
+    // 某一行位置上各符号的概率；weighted模式每行相同，stepper模式为停止概率平移后的结果
+    fn cell_distribution(&self, row: usize) -> Vec<(&'a SlotSymbol, f64)> {
+        let len = self.strip.symbols.len();
+        let mut by_symbol: Vec<(&SlotSymbol, f64)> = Vec::new();
+
+        for (stop, &p) in self.stop_probs.iter().enumerate() {
+            let position = match self.mode {
+                ReelMode::Weighted => stop,
+                ReelMode::Stepper => (stop + row) % len,
+            };
+            let symbol = &self.strip.symbols[position].0;
+            match by_symbol.iter_mut().find(|(s, _)| s.id == symbol.id) {
+                Some((_, total)) => *total += p,
+                None => by_symbol.push((symbol, p)),
+            }
+        }
+
+        by_symbol.retain(|(_, p)| *p > 0.0);
+        by_symbol
+    }
+
+    // stepper模式：枚举所有 (停止位置, 行数) 得到的可见窗口及其概率
+    fn windows(&self) -> Vec<(f64, Vec<&'a SlotSymbol>)> {
+        let len = self.strip.symbols.len();
+        let mut windows = Vec::new();
+
+        for &(rows, p_rows) in &self.rows {
+            for (stop, &p_stop) in self.stop_probs.iter().enumerate() {
+                if p_stop == 0.0 {
+                    continue;
+                }
+                let window = (0..rows)
+                    .map(|row| &self.strip.symbols[(stop + row) % len].0)
+                    .collect();
+                windows.push((p_rows * p_stop, window));
+            }
+        }
+
+        windows
+    }
+
+    // 可见窗口中满足条件的符号数量分布（下标为数量）
+    fn count_distribution(&self, matches: impl Fn(&SlotSymbol) -> bool) -> Vec<f64> {
+        let mut distribution = Vec::new();
+
+        match self.mode {
+            ReelMode::Weighted => {
+                // 每个位置独立：行数分布与二项分布的混合
+                let q: f64 = self
+                    .cell_distribution(0)
+                    .iter()
+                    .filter(|(s, _)| matches(s))
+                    .map(|(_, p)| p)
+                    .sum();
+                for &(rows, p_rows) in &self.rows {
+                    for (count, p) in binomial(rows, q).into_iter().enumerate() {
+                        add_at(&mut distribution, count, p_rows * p);
+                    }
+                }
+            }
+            ReelMode::Stepper => {
+                for (p, window) in self.windows() {
+                    let count = window.iter().filter(|s| matches(s)).count();
+                    add_at(&mut distribution, count, p);
+                }
+            }
+        }
+
+        distribution
+    }
+
+    // 窗口中各位置得分之和 w 的统计量：(E[w], E[w²], P(w = 0))
+    fn score_moments(&self, score: impl Fn(&SlotSymbol) -> u32) -> (f64, f64, f64) {
+        match self.mode {
+            ReelMode::Weighted => {
+                let cells = self.cell_distribution(0);
+                let mean: f64 = cells.iter().map(|(s, p)| score(s) as f64 * p).sum();
+                let square: f64 = cells
+                    .iter()
+                    .map(|(s, p)| (score(s) as f64).powi(2) * p)
+                    .sum();
+                let zero: f64 = cells
+                    .iter()
+                    .filter(|(s, _)| score(s) == 0)
+                    .map(|(_, p)| p)
+                    .sum();
+
+                let mut moments = (0.0, 0.0, 0.0);
+                for &(rows, p_rows) in &self.rows {
+                    let rows = rows as f64;
+                    moments.0 += p_rows * rows * mean;
+                    moments.1 += p_rows * (rows * square + rows * (rows - 1.0) * mean * mean);
+                    moments.2 += p_rows * zero.powf(rows);
+                }
+                moments
+            }
+            ReelMode::Stepper => {
+                let mut moments = (0.0, 0.0, 0.0);
+                for (p, window) in self.windows() {
+                    let w: f64 = window.iter().map(|s| score(s) as f64).sum();
+                    moments.0 += p * w;
+                    moments.1 += p * w * w;
+                    if w == 0.0 {
+                        moments.2 += p;
+                    }
+                }
+                moments
+            }
+        }
+    }
+}
+
+fn add_at(distribution: &mut Vec<f64>, index: usize, p: f64) {
+    if distribution.len() <= index {
+        distribution.resize(index + 1, 0.0);
+    }
+    distribution[index] += p;
+}
+
+fn binomial(n: usize, q: f64) -> Vec<f64> {
+    let mut distribution = vec![1.0];
+    for _ in 0..n {
+        let mut next = vec![0.0; distribution.len() + 1];
+        for (k, p) in distribution.iter().enumerate() {
+            next[k] += p * (1.0 - q);
+            next[k + 1] += p * q;
+        }
+        distribution = next;
+    }
+    distribution
+}
+
+// 两个独立数量分布的和
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; a.len() + b.len() - 1];
+    for (i, pa) in a.iter().enumerate() {
+        for (j, pb) in b.iter().enumerate() {
+            result[i + j] += pa * pb;
+        }
+    }
+    result
+}
+
+// 整个画面上满足条件的符号数量分布（各卷轴独立）
+fn grid_count_distribution(
+    reels: &[ReelModel],
+    matches: impl Fn(&SlotSymbol) -> bool + Copy,
+) -> Vec<f64> {
+    reels.iter().fold(vec![1.0], |total, reel| {
+        convolve(&total, &reel.count_distribution(matches))
+    })
+}
+
+// 一条支付线的统计量，以及按基础符号拆分的期望赢奖
+type LineStats = (WinStats, HashMap<i32, f64>);
+
+// 一次旋转中某个赢奖来源的统计量（单位：总投注）
+#[derive(Default)]
+struct WinStats {
+    mean: f64,
+    second_moment: f64,
+    hit: f64,
+}
+
+impl WinStats {
+    fn variance(&self) -> f64 {
+        (self.second_moment - self.mean * self.mean).max(0.0)
+    }
+}
+
+// 基础游戏的统计结果
+#[derive(Default)]
+struct BaseGame {
+    line_rtp: f64,
+    scatter_rtp: f64,
+    hit_frequency: f64,
+    variance: f64,
+    free_spins_frequency: f64,
+    expected_free_spins: f64,
+    bonus_frequency: f64,
+    contributions: HashMap<i32, f64>,
+}
+
+pub fn analyze(machine: &UniversalSlotMachine) -> Result<MathReport, String> {
+    // 簇的形状依赖整个网格，无法按卷轴独立计算，需要使用模拟
+    if machine.config.evaluation_mode == EvaluationMode::Cluster {
+        return Err("Cluster pays can't be calculated exactly, use simulation instead".to_string());
+    }
+    // 重转的结果依赖已锁定的钱币位置
+    if machine.hold_and_spin.is_some() {
+        return Err(
+            "Hold and spin can't be calculated exactly, use simulation instead".to_string(),
+        );
+    }
+    // 连锁后的网格依赖前一步的赢奖位置
+    if machine.config.cascading {
+        return Err(
+            "Cascading reels can't be calculated exactly, use simulation instead".to_string(),
+        );
+    }
+
+    let mut notes = Vec::new();
+    // 基础游戏按权重抽取卷轴组时，统计量为各卷轴组的加权混合
+    let base_sets: Vec<(&[ReelStrip], f64)> = if machine.base_reel_set_weights.is_empty() {
+        vec![(&machine.reel_strips, 1.0)]
+    } else {
+        let total_weight: u64 = machine
+            .base_reel_set_weights
+            .iter()
+            .map(|(_, w)| *w as u64)
+            .sum();
+        machine
+            .base_reel_set_weights
+            .iter()
+            .map(|(name, weight)| {
+                (
+                    machine.reels_for(Some(name)).0,
+                    *weight as f64 / total_weight as f64,
+                )
+            })
+            .collect()
+    };
+    let (method, cycle_size, base) = mixed_game(machine, &base_sets, &mut notes)?;
+    let base_game_rtp = base.line_rtp + base.scatter_rtp;
+
+    // 免费旋转：每次免费旋转平均再奖励a次，构成分支过程；
+    // 奖励n次免费旋转时，包含再次触发在内的总次数期望为 n / (1 - a)
+    let mut free_spins_rtp = 0.0;
+    let mut expected_free_spins = 0.0;
+    // 奖励n次免费旋转时，包含再次触发在内的期望赢金为 n × free_spins_value
+    let mut free_spins_value = 0.0;
+    if machine.config.free_spins_enabled {
+        // 配置了 free_spins 卷轴组时免费旋转在其上计算，否则与基础游戏相同
+        let free_game = match machine.reel_sets.get(FREE_SPINS_REEL_SET) {
+            Some(set) => Some(mixed_game(machine, &[(&set.reel_strips, 1.0)], &mut notes)?.2),
+            None => None,
+        };
+        let free_game = free_game.as_ref().unwrap_or(&base);
+        let a = free_game.expected_free_spins;
+        if a >= 1.0 {
+            return Err(format!(
+                "Free spins award {a:.3} spins per spin on average, the feature never ends"
+            ));
+        }
+        expected_free_spins = base.expected_free_spins / (1.0 - a);
+        free_spins_value = (free_game.line_rtp + free_game.scatter_rtp)
+            * machine.config.free_spins_multiplier as f64
+            / (1.0 - a);
+        free_spins_rtp = base.expected_free_spins * free_spins_value;
+        if a > 0.0 && machine.wild_features.iter().any(|w| w.sticky || w.walking) {
+            notes.push("free spins RTP ignores sticky and walking wilds".to_string());
+        }
+    }
+
+    // 选宝游戏：每次选择的期望奖品为奖品表的加权平均倍数
+    let mut bonus_value = 0.0;
+    if let Some(bonus_game) = &machine.bonus_game {
+        let total_weight: u64 = bonus_game.prizes.iter().map(|p| p.weight as u64).sum();
+        if total_weight > 0 {
+            let mean_prize = bonus_game
+                .prizes
+                .iter()
+                .map(|p| p.multiplier as f64 * p.weight as f64)
+                .sum::<f64>()
+                / total_weight as f64;
+            let picks = (bonus_game.picks as usize).min(bonus_game.board_size) as f64;
+            bonus_value = picks * mean_prize;
+        }
+    }
+    let bonus_rtp = base.bonus_frequency * bonus_value;
+    let rtp = base_game_rtp + free_spins_rtp + bonus_rtp;
+
+    // 加注和购买免费旋转在各自的卷轴组上计算一次旋转的期望赢金，触发的免费旋转与基础游戏中触发的相同
+    let pricing = |mode: BetMode, cost: f64| -> Result<BetModePricing, String> {
+        let (reel_strips, _) = machine.reels_for(mode.reel_set());
+        let (_, _, game, reels) = base_game(machine, reel_strips, &mut Vec::new())?;
+        let awarded = match (mode, &machine.bonus_buy) {
+            // 购买时免费旋转次数取触发次数和购买次数中较大的一个
+            (BetMode::BonusBuy, Some(bonus_buy)) => {
+                grid_count_distribution(&reels, |s| s.symbol_type == SymbolType::Scatter)
+                    .iter()
+                    .enumerate()
+                    .map(|(count, p)| {
+                        machine.free_spins_for(count).max(bonus_buy.free_spins) as f64 * p
+                    })
+                    .sum()
+            }
+            _ => game.expected_free_spins,
+        };
+        let expected_win = game.line_rtp
+            + game.scatter_rtp
+            + awarded * free_spins_value
+            + game.bonus_frequency * bonus_value;
+        Ok(BetModePricing {
+            cost,
+            expected_win,
+            rtp: expected_win / cost,
+            fair_cost: if rtp > 0.0 { expected_win / rtp } else { 0.0 },
+        })
+    };
+    let ante_bet = machine
+        .ante_bet
+        .as_ref()
+        .map(|ante| pricing(BetMode::Ante, 1.0 + ante.cost_percent as f64 / 100.0))
+        .transpose()?;
+    let bonus_buy = machine
+        .bonus_buy
+        .as_ref()
+        .map(|buy| pricing(BetMode::BonusBuy, buy.cost_multiplier as f64))
+        .transpose()?;
+
+    let standard_deviation = base.variance.sqrt();
+    let free_spins_frequency = if machine.config.free_spins_enabled {
+        base.free_spins_frequency
+    } else {
+        0.0
+    };
+
+    let mut symbols: Vec<SymbolContribution> = machine
+        .symbols
+        .iter()
+        .filter_map(|symbol| {
+            base.contributions
+                .get(&symbol.id)
+                .map(|&rtp| SymbolContribution {
+                    symbol_id: symbol.id,
+                    name: symbol.name.clone(),
+                    rtp,
+                })
+        })
+        .collect();
+    symbols.sort_by(|a, b| b.rtp.total_cmp(&a.rtp));
+
+    Ok(MathReport {
+        slot_config_id: machine.config.id,
+        method,
+        cycle_size: cycle_size.filter(|_| method == MathMethod::FullCycle),
+        rtp,
+        base_game_rtp,
+        line_rtp: base.line_rtp,
+        scatter_rtp: base.scatter_rtp,
+        free_spins_rtp,
+        bonus_rtp,
+        hit_frequency: base.hit_frequency,
+        standard_deviation,
+        volatility_index: VOLATILITY_CONFIDENCE_Z * standard_deviation,
+        free_spins_frequency,
+        expected_free_spins,
+        bonus_frequency: base.bonus_frequency,
+        configured_rtp: machine.config.rtp_percentage / 100.0,
+        ante_bet,
+        bonus_buy,
+        symbols,
+        notes: {
+            // 多个卷轴组会重复同样的说明
+            let mut seen = HashSet::new();
+            notes.retain(|note| seen.insert(note.clone()));
+            notes
+        },
+    })
+}
+
+// 按概率抽取卷轴组时的统计：期望按概率加权，方差按 E[X²] - E[X]² 合并
+fn mixed_game(
+    machine: &UniversalSlotMachine,
+    sets: &[(&[ReelStrip], f64)],
+    notes: &mut Vec<String>,
+) -> Result<(MathMethod, Option<u64>, BaseGame), String> {
+    if let [(reel_strips, _)] = sets {
+        let (method, cycle_size, game, _) = base_game(machine, reel_strips, notes)?;
+        return Ok((method, cycle_size, game));
+    }
+
+    let mut method = MathMethod::FullCycle;
+    let mut mixed = BaseGame::default();
+    let mut second_moment = 0.0;
+    for &(reel_strips, p) in sets {
+        let (set_method, _, game, _) = base_game(machine, reel_strips, notes)?;
+        if set_method == MathMethod::Analytic {
+            method = MathMethod::Analytic;
+        }
+        let mean = game.line_rtp + game.scatter_rtp;
+        second_moment += p * (game.variance + mean * mean);
+        mixed.line_rtp += p * game.line_rtp;
+        mixed.scatter_rtp += p * game.scatter_rtp;
+        mixed.hit_frequency += p * game.hit_frequency;
+        mixed.free_spins_frequency += p * game.free_spins_frequency;
+        mixed.expected_free_spins += p * game.expected_free_spins;
+        mixed.bonus_frequency += p * game.bonus_frequency;
+        for (symbol_id, rtp) in game.contributions {
+            *mixed.contributions.entry(symbol_id).or_default() += p * rtp;
+        }
+    }
+    let mean = mixed.line_rtp + mixed.scatter_rtp;
+    mixed.variance = (second_moment - mean * mean).max(0.0);
+
+    // 多个卷轴组没有单一的完整周期
+    Ok((method, None, mixed))
+}
+
+// 一组卷轴上单次旋转的统计：stepper模式且组合数不多时完整枚举，否则解析计算
+#[allow(clippy::type_complexity)]
+fn base_game<'a>(
+    machine: &UniversalSlotMachine,
+    reel_strips: &'a [ReelStrip],
+    notes: &mut Vec<String>,
+) -> Result<(MathMethod, Option<u64>, BaseGame, Vec<ReelModel<'a>>), String> {
+    if reel_strips.len() != machine.config.reels {
+        return Err(format!(
+            "Expected {} reels, found {}",
+            machine.config.reels,
+            reel_strips.len()
+        ));
+    }
+    let reels = reel_strips
+        .iter()
+        .map(|strip| ReelModel::new(machine, strip))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let cycle_size = reels
+        .iter()
+        .try_fold(1u64, |total, reel| {
+            let stops = reel.stop_probs.iter().filter(|p| **p > 0.0).count() as u64;
+            total.checked_mul(stops)
+        })
+        .filter(|_| machine.config.reel_mode == ReelMode::Stepper && !machine.config.is_megaway);
+
+    let (method, base) = match cycle_size.filter(|&size| size <= FULL_CYCLE_LIMIT) {
+        Some(_) => match full_cycle(machine, &reels) {
+            Some(base) => (MathMethod::FullCycle, base),
+            None => (MathMethod::Analytic, analytic(machine, &reels, notes)),
+        },
+        None => (MathMethod::Analytic, analytic(machine, &reels, notes)),
+    };
+    if method == MathMethod::Analytic {
+        notes.push(
+            "hit_frequency and standard_deviation assume wins from different lines/symbols are independent"
+                .to_string(),
+        );
+    }
+
+    Ok((method, cycle_size, base, reels))
+}
+
+fn feature_stats(machine: &UniversalSlotMachine, reels: &[ReelModel]) -> (f64, f64, f64) {
+    let scatters = grid_count_distribution(reels, |s| s.symbol_type == SymbolType::Scatter);
+    let free_spins_frequency = scatters
+        .iter()
+        .enumerate()
+        .filter(|(count, _)| machine.free_spins_for(*count) > 0)
+        .map(|(_, p)| p)
+        .fold(0.0, |total, p| total + p);
+    let expected_free_spins = scatters
+        .iter()
+        .enumerate()
+        .map(|(count, p)| machine.free_spins_for(count) as f64 * p)
+        .sum();
+
+    let bonuses = grid_count_distribution(reels, |s| s.symbol_type == SymbolType::Bonus);
+    let bonus_frequency = bonuses
+        .iter()
+        .enumerate()
+        .filter(|(count, _)| machine.bonus_triggered_by(*count))
+        .map(|(_, p)| p)
+        .fold(0.0, |total, p| total + p);
+
+    (free_spins_frequency, expected_free_spins, bonus_frequency)
+}
+
+// 解析计算：各卷轴相互独立，逐条线（或逐个符号的路数）计算期望
+fn analytic(
+    machine: &UniversalSlotMachine,
+    reels: &[ReelModel],
+    notes: &mut Vec<String>,
+) -> BaseGame {
+    if machine.wild_features.iter().any(|w| w.expanding) {
+        notes.push("expanding wilds are not modelled by the analytic method".to_string());
+    }
+
+    let mut contributions: HashMap<i32, f64> = HashMap::new();
+    let mut sources = Vec::new();
+
+    let line_rtp = if machine.config.is_megaway {
+        let mut rtp = 0.0;
+        for (symbol_id, stats) in ways_stats(machine, reels) {
+            *contributions.entry(symbol_id).or_default() += stats.mean;
+            rtp += stats.mean;
+            sources.push(stats);
+        }
+        rtp
+    } else {
+        let active: Vec<_> = machine.paylines.iter().filter(|p| p.is_active).collect();
+        let lines = active.len().max(1) as f64;
+        let mut cache: HashMap<Vec<(usize, usize)>, LineStats> = HashMap::new();
+        let mut rtp = 0.0;
+
+        for payline in active {
+            let cells: Vec<(usize, usize)> = payline
+                .pattern
+                .iter()
+                .copied()
+                .filter(|&(reel, row)| reel < reels.len() && row < machine.config.rows)
+                .collect();
+            let (stats, by_symbol) = cache
+                .entry(cells.clone())
+                .or_insert_with(|| line_stats(machine, reels, &cells));
+
+            for (symbol_id, ev) in by_symbol.iter() {
+                *contributions.entry(*symbol_id).or_default() += ev / lines;
+            }
+            rtp += stats.mean / lines;
+            sources.push(WinStats {
+                mean: stats.mean / lines,
+                second_moment: stats.second_moment / (lines * lines),
+                hit: stats.hit,
+            });
+        }
+        rtp
+    };
+
+    let mut scatter_rtp = 0.0;
+    for symbol in machine
+        .symbols
+        .iter()
+        .filter(|s| s.symbol_type == SymbolType::Scatter)
+    {
+        let mut stats = WinStats::default();
+        for (count, p) in grid_count_distribution(reels, |s| s.id == symbol.id)
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(payout) = UniversalSlotMachine::scatter_payout(symbol, count) {
+                stats.mean += p * payout as f64;
+                stats.second_moment += p * (payout as f64).powi(2);
+                stats.hit += p;
+            }
+        }
+        if stats.hit > 0.0 {
+            *contributions.entry(symbol.id).or_default() += stats.mean;
+            scatter_rtp += stats.mean;
+            sources.push(stats);
+        }
+    }
+
+    let (free_spins_frequency, expected_free_spins, bonus_frequency) =
+        feature_stats(machine, reels);
+
+    BaseGame {
+        line_rtp,
+        scatter_rtp,
+        hit_frequency: 1.0 - sources.iter().map(|s| 1.0 - s.hit).product::<f64>(),
+        variance: sources.iter().map(WinStats::variance).sum(),
+        free_spins_frequency,
+        expected_free_spins,
+        bonus_frequency,
+        contributions,
+    }
+}
+
+// 枚举一条支付线上所有位置的符号组合（不同卷轴相互独立），单位为每线投注
+fn line_stats(
+    machine: &UniversalSlotMachine,
+    reels: &[ReelModel],
+    cells: &[(usize, usize)],
+) -> LineStats {
+    let distributions: Vec<_> = cells
+        .iter()
+        .map(|&(reel, row)| reels[reel].cell_distribution(row))
+        .collect();
+
+    let mut stats = WinStats::default();
+    let mut by_symbol = HashMap::new();
+    let mut line = Vec::with_capacity(cells.len());
+    enumerate_line(
+        machine,
+        &distributions,
+        &mut line,
+        1.0,
+        &mut stats,
+        &mut by_symbol,
+    );
+    (stats, by_symbol)
+}
+
+fn enumerate_line(
+    machine: &UniversalSlotMachine,
+    distributions: &[Vec<(&SlotSymbol, f64)>],
+    line: &mut Vec<SlotSymbol>,
+    p: f64,
+    stats: &mut WinStats,
+    by_symbol: &mut HashMap<i32, f64>,
+) {
+    if line.len() == distributions.len() {
+        if let Some((base_symbol, win)) = machine.line_win(line) {
+            let win = win as f64;
+            stats.mean += p * win;
+            stats.second_moment += p * win * win;
+            stats.hit += p;
+            *by_symbol.entry(base_symbol.id).or_default() += p * win;
+        }
+        return;
+    }
+
+    for (symbol, p_symbol) in &distributions[line.len()] {
+        line.push((*symbol).clone());
+        enumerate_line(machine, distributions, line, p * p_symbol, stats, by_symbol);
+        line.pop();
+    }
+}
+
+// Megaway路数：赢 n 个卷轴的期望 = 赔付(n) x 前n个卷轴得分期望之积 x 第n+1个卷轴无匹配的概率
+fn ways_stats(machine: &UniversalSlotMachine, reels: &[ReelModel]) -> Vec<(i32, WinStats)> {
+    let mut result = Vec::new();
+
+    for base_symbol in machine
+        .symbols
+        .iter()
+        .filter(|s| matches!(s.symbol_type, SymbolType::Normal | SymbolType::Wild))
+        .filter(|s| !s.payouts.is_empty())
+    {
+        let moments: Vec<(f64, f64, f64)> = reels
+            .iter()
+            .map(|reel| reel.score_moments(|s| machine.ways_score(s, base_symbol)))
+            .collect();
+
+        let mut stats = WinStats::default();
+        let (mut mean, mut second, mut nonzero) = (1.0, 1.0, 1.0);
+        for (n, &(e_w, e_w2, p_zero)) in moments.iter().enumerate() {
+            mean *= e_w;
+            second *= e_w2;
+            nonzero *= 1.0 - p_zero;
+            let reel_count = n + 1;
+            let stop = moments.get(reel_count).map_or(1.0, |m| m.2);
+
+            if reel_count < 3 {
+                continue;
+            }
+            if let Some(&payout) = base_symbol.payouts.get(&reel_count) {
+                let payout = payout as f64;
+                stats.mean += payout * mean * stop;
+                stats.second_moment += payout * payout * second * stop;
+                stats.hit += nonzero * stop;
+            }
+        }
+
+        if stats.hit > 0.0 {
+            result.push((base_symbol.id, stats));
+        }
+    }
+
+    result
+}
+
+// stepper模式完整周期：逐个枚举所有停止位置组合，按符号索引计算并缓存每条线的结果
+fn full_cycle(machine: &UniversalSlotMachine, reels: &[ReelModel]) -> Option<BaseGame> {
+    let palette = &machine.symbols;
+    let index_of: HashMap<i32, usize> =
+        palette.iter().enumerate().map(|(i, s)| (s.id, i)).collect();
+    let scatters: Vec<usize> = (0..palette.len())
+        .filter(|&i| palette[i].symbol_type == SymbolType::Scatter)
+        .collect();
+
+    // 每个卷轴每个停止位置的窗口（已应用扩展Wild）
+    struct Stop {
+        p: f64,
+        window: Vec<usize>,
+        scatter_counts: Vec<usize>,
+        scatter_type_count: usize,
+        bonus_count: usize,
+    }
+    let mut stops: Vec<Vec<Stop>> = Vec::new();
+    for reel in reels {
+        let mut reel_stops = Vec::new();
+        for (p, window) in reel.windows() {
+            let mut window: Vec<SlotSymbol> = window.into_iter().cloned().collect();
+            machine.expand_wilds(std::slice::from_mut(&mut window));
+            let window: Vec<usize> = window.iter().map(|s| index_of[&s.id]).collect();
+            reel_stops.push(Stop {
+                p,
+                scatter_counts: scatters
+                    .iter()
+                    .map(|&i| window.iter().filter(|&&w| w == i).count())
+                    .collect(),
+                scatter_type_count: window
+                    .iter()
+                    .filter(|&&w| palette[w].symbol_type == SymbolType::Scatter)
+                    .count(),
+                bonus_count: window
+                    .iter()
+                    .filter(|&&w| palette[w].symbol_type == SymbolType::Bonus)
+                    .count(),
+                window,
+            });
+        }
+        stops.push(reel_stops);
+    }
+
+    let paylines: Vec<Vec<(usize, usize)>> = machine
+        .paylines
+        .iter()
+        .filter(|p| p.is_active)
+        .map(|p| {
+            p.pattern
+                .iter()
+                .copied()
+                .filter(|&(reel, row)| reel < reels.len() && row < machine.config.rows)
+                .collect()
+        })
+        .collect();
+    let lines = paylines.len().max(1) as f64;
+
+    // 每条线的符号组合编码为一个u64作为缓存键
+    let radix = palette.len() as u64;
+    let max_cells = paylines.iter().map(Vec::len).max().unwrap_or(0) as u32;
+    radix.checked_pow(max_cells)?;
+    let mut line_cache: HashMap<u64, Option<(usize, u32)>> = HashMap::new();
+
+    let mut contributions = vec![0.0; palette.len()];
+    let (mut line_rtp, mut scatter_rtp) = (0.0, 0.0);
+    let (mut second_moment, mut hit_frequency) = (0.0, 0.0);
+    let (mut free_spins_frequency, mut expected_free_spins, mut bonus_frequency) = (0.0, 0.0, 0.0);
+
+    let mut odometer = vec![0usize; reels.len()];
+    loop {
+        let p: f64 = odometer
+            .iter()
+            .enumerate()
+            .map(|(reel, &stop)| stops[reel][stop].p)
+            .product();
+
+        let mut line_total = 0.0;
+        for cells in &paylines {
+            let key = cells.iter().fold(0u64, |key, &(reel, row)| {
+                key * radix + stops[reel][odometer[reel]].window[row] as u64
+            });
+            let win = *line_cache.entry(key).or_insert_with(|| {
+                let line: Vec<SlotSymbol> = cells
+                    .iter()
+                    .map(|&(reel, row)| palette[stops[reel][odometer[reel]].window[row]].clone())
+                    .collect();
+                machine
+                    .line_win(&line)
+                    .map(|(base, win)| (index_of[&base.id], win))
+            });
+            if let Some((base, win)) = win {
+                let win = win as f64 / lines;
+                line_total += win;
+                contributions[base] += p * win;
+            }
+        }
+
+        let mut scatter_total = 0.0;
+        for (j, &symbol) in scatters.iter().enumerate() {
+            let count = odometer
+                .iter()
+                .enumerate()
+                .map(|(reel, &stop)| stops[reel][stop].scatter_counts[j])
+                .sum();
+            if let Some(payout) = UniversalSlotMachine::scatter_payout(&palette[symbol], count) {
+                scatter_total += payout as f64;
+                contributions[symbol] += p * payout as f64;
+            }
+        }
+
+        let total = line_total + scatter_total;
+        line_rtp += p * line_total;
+        scatter_rtp += p * scatter_total;
+        second_moment += p * total * total;
+        if total > 0.0 {
+            hit_frequency += p;
+        }
+
+        let scatter_count = odometer
+            .iter()
+            .enumerate()
+            .map(|(reel, &stop)| stops[reel][stop].scatter_type_count)
+            .sum();
+        let awarded = machine.free_spins_for(scatter_count);
+        if awarded > 0 {
+            free_spins_frequency += p;
+            expected_free_spins += p * awarded as f64;
+        }
+        let bonus_count = odometer
+            .iter()
+            .enumerate()
+            .map(|(reel, &stop)| stops[reel][stop].bonus_count)
+            .sum();
+        if machine.bonus_triggered_by(bonus_count) {
+            bonus_frequency += p;
+        }
+
+        // 下一个停止位置组合
+        let mut reel = 0;
+        loop {
+            if reel == odometer.len() {
+                let mean = line_rtp + scatter_rtp;
+                return Some(BaseGame {
+                    line_rtp,
+                    scatter_rtp,
+                    hit_frequency,
+                    variance: (second_moment - mean * mean).max(0.0),
+                    free_spins_frequency,
+                    expected_free_spins,
+                    bonus_frequency,
+                    contributions: palette
+                        .iter()
+                        .zip(contributions)
+                        .filter(|(_, rtp)| *rtp > 0.0)
+                        .map(|(s, rtp)| (s.id, rtp))
+                        .collect(),
+                });
+            }
+            odometer[reel] += 1;
+            if odometer[reel] < stops[reel].len() {
+                break;
+            }
+            odometer[reel] = 0;
+            reel += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::universal_slots::{
+        AnteBetConfig, BonusBuyConfig, Payline, ReelSet, SlotConfig, BONUS_BUY_REEL_SET,
+    };
+
+    fn symbol(
+        id: i32,
+        name: &str,
+        symbol_type: SymbolType,
+        payouts: &[(usize, u32)],
+    ) -> SlotSymbol {
+        SlotSymbol {
+            id,
+            name: name.to_string(),
+            symbol_type,
+            value: id as u32,
+            image_url: None,
+            payouts: payouts.iter().copied().collect(),
+        }
+    }
+
+    // 3x3，单条中线；A 3连赔10，Scatter 2个赔1
+    fn machine(reel_mode: ReelMode) -> UniversalSlotMachine {
+        let symbols = vec![
+            symbol(1, "A", SymbolType::Normal, &[(3, 10)]),
+            symbol(2, "B", SymbolType::Normal, &[(3, 5)]),
+            symbol(3, "Scatter", SymbolType::Scatter, &[(2, 1)]),
+        ];
+        let strip = |reel_number| ReelStrip {
+            reel_number,
+            symbols: vec![
+                (symbols[0].clone(), 2),
+                (symbols[1].clone(), 1),
+                (symbols[2].clone(), 1),
+            ],
+        };
+        let config = SlotConfig {
+            id: 1,
+            name: "Math".to_string(),
+            rows: 3,
+            reels: 3,
+            is_megaway: false,
+            min_megaway_rows: 2,
+            max_megaway_rows: 7,
+            default_bet: 1,
+            min_bet: 1,
+            max_bet: 100,
+            wild_enabled: true,
+            free_spins_enabled: false,
+            free_spins_multiplier: 1,
+            reel_mode,
+            evaluation_mode: Default::default(),
+            cascading: false,
+            cascade_multipliers: Vec::new(),
+            rtp_percentage: 96.0,
+        };
+        let payline = Payline {
+            line_number: 1,
+            pattern: vec![(0, 1), (1, 1), (2, 1)],
+            is_active: true,
+        };
+        let reel_strips = (0..3).map(strip).collect();
+        UniversalSlotMachine::new(config, symbols, reel_strips, vec![payline])
+    }
+
+    #[test]
+    fn test_weighted_line_rtp_is_exact() {
+        let report = analyze(&machine(ReelMode::Weighted)).unwrap();
+
+        // A: (1/2)^3 x 10，B: (1/4)^3 x 5
+        let line_rtp = 0.125 * 10.0 + 5.0 / 64.0;
+        assert_eq!(report.method, MathMethod::Analytic);
+        assert!((report.line_rtp - line_rtp).abs() < 1e-12);
+        assert!(report.scatter_rtp > 0.0);
+        assert!(report.hit_frequency > 0.0 && report.hit_frequency < 1.0);
+    }
+
+    #[test]
+    fn test_full_cycle_matches_enumerated_spins() {
+        let machine = machine(ReelMode::Stepper);
+        let report = analyze(&machine).unwrap();
+        assert_eq!(report.method, MathMethod::FullCycle);
+        assert_eq!(report.cycle_size, Some(27));
+
+        // 暴力枚举同样的27个停止组合
+        let mut total = 0.0;
+        let mut hits = 0.0;
+        let weights = [0.5, 0.25, 0.25];
+        for a in 0..3 {
+            for b in 0..3 {
+                for c in 0..3 {
+                    let grid: Vec<Vec<SlotSymbol>> = [a, b, c]
+                        .iter()
+                        .map(|&stop| {
+                            (0..3)
+                                .map(|row| {
+                                    machine.reel_strips[0].symbols[(stop + row) % 3].0.clone()
+                                })
+                                .collect()
+                        })
+                        .collect();
+                    let p = weights[a] * weights[b] * weights[c];
+                    let line = [grid[0][1].clone(), grid[1][1].clone(), grid[2][1].clone()];
+                    let scatters = grid.iter().flatten().filter(|s| s.id == 3).count();
+                    let win = machine.line_win(&line).map_or(0, |(_, w)| w)
+                        + UniversalSlotMachine::scatter_payout(&machine.symbols[2], scatters)
+                            .unwrap_or(0);
+                    total += p * win as f64;
+                    if win > 0 {
+                        hits += p;
+                    }
+                }
+            }
+        }
+
+        assert!((report.base_game_rtp - total).abs() < 1e-12);
+        assert!((report.hit_frequency - hits).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ante_and_bonus_buy_pricing() {
+        let mut machine = machine(ReelMode::Weighted);
+        machine.ante_bet = Some(AnteBetConfig { cost_percent: 25 });
+        let report = analyze(&machine).unwrap();
+
+        // 没有ante卷轴组时加注只是多付25%，期望赢金与基础游戏相同
+        let ante = report.ante_bet.unwrap();
+        assert!((ante.expected_win - report.rtp).abs() < 1e-12);
+        assert!((ante.rtp - report.rtp / 1.25).abs() < 1e-12);
+        assert!((ante.fair_cost - 1.0).abs() < 1e-12);
+
+        // 只有A的卷轴组：每次旋转必中3连，加上购买的免费旋转
+        machine.config.free_spins_enabled = true;
+        machine.scatter_triggers = Vec::new();
+        machine.bonus_buy = Some(BonusBuyConfig {
+            cost_multiplier: 50,
+            free_spins: 10,
+        });
+        let only_a = ReelStrip {
+            reel_number: 0,
+            symbols: vec![(machine.symbols[0].clone(), 1)],
+        };
+        machine.reel_sets.insert(
+            BONUS_BUY_REEL_SET.to_string(),
+            ReelSet::new(vec![only_a; 3]),
+        );
+        let report = analyze(&machine).unwrap();
+        let bonus_buy = report.bonus_buy.unwrap();
+        assert!((bonus_buy.expected_win - (10.0 + 10.0 * report.base_game_rtp)).abs() < 1e-9);
+        assert!((bonus_buy.rtp - bonus_buy.expected_win / 50.0).abs() < 1e-12);
+        assert!((bonus_buy.fair_cost - bonus_buy.expected_win / report.rtp).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_weighted_base_reel_sets_and_free_spins_reels() {
+        let mut machine = machine(ReelMode::Weighted);
+        let base = analyze(&machine).unwrap();
+
+        // 只有A的卷轴组每次旋转赢10倍总投注，按1:3的权重与基础卷轴混合
+        let only_a = ReelStrip {
+            reel_number: 0,
+            symbols: vec![(machine.symbols[0].clone(), 1)],
+        };
+        machine
+            .reel_sets
+            .insert("only_a".to_string(), ReelSet::new(vec![only_a.clone(); 3]));
+        machine.base_reel_set_weights = vec![("base".to_string(), 3), ("only_a".to_string(), 1)];
+        let report = analyze(&machine).unwrap();
+        let expected = 0.75 * base.base_game_rtp + 0.25 * 10.0;
+        assert!((report.base_game_rtp - expected).abs() < 1e-9);
+        assert!((report.hit_frequency - (0.75 * base.hit_frequency + 0.25)).abs() < 1e-9);
+        assert_eq!(report.cycle_size, None);
+        // 混合后的方差 = E[X²] - E[X]²
+        let second_moment =
+            0.75 * (base.standard_deviation.powi(2) + base.base_game_rtp.powi(2)) + 0.25 * 100.0;
+        assert!(
+            (report.standard_deviation - (second_moment - expected * expected).sqrt()).abs() < 1e-9
+        );
+
+        // 免费旋转使用 free_spins 卷轴组
+        machine.base_reel_set_weights = Vec::new();
+        machine.config.free_spins_enabled = true;
+        machine.config.free_spins_multiplier = 2;
+        machine.reel_sets.insert(
+            FREE_SPINS_REEL_SET.to_string(),
+            ReelSet::new(vec![only_a; 3]),
+        );
+        let report = analyze(&machine).unwrap();
+        assert!((report.free_spins_rtp - report.expected_free_spins * 20.0).abs() < 1e-9);
+    }
+}
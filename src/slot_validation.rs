@@ -0,0 +1,627 @@
+use crate::universal_slots::{
+    EvaluationMode, SlotConfigBuilder, SymbolType, ANTE_REEL_SET, BASE_REEL_SET,
+    BONUS_BUY_REEL_SET, FREE_SPINS_REEL_SET,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+// 单条校验结果，code 为稳定的机器可读标识，message 描述具体位置
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ValidationIssue {
+    pub code: &'static str,
+    pub message: String,
+}
+
+// errors 不为空的配置不能上线旋转；warnings 只是提示，不影响旋转
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub errors: Vec<ValidationIssue>,
+    pub warnings: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    fn error(&mut self, code: &'static str, message: String) {
+        self.errors.push(ValidationIssue { code, message });
+    }
+
+    fn warning(&mut self, code: &'static str, message: String) {
+        self.warnings.push(ValidationIssue { code, message });
+    }
+
+    pub fn has_code(&self, code: &str) -> bool {
+        self.errors
+            .iter()
+            .chain(&self.warnings)
+            .any(|issue| issue.code == code)
+    }
+
+    // 所有错误信息合并为一行，用于拒绝旋转时的提示
+    pub fn error_summary(&self) -> String {
+        self.errors
+            .iter()
+            .map(|issue| issue.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl SlotConfigBuilder {
+    // 检查配置能否安全旋转：网格尺寸、卷轴、支付线、符号赔付以及各特性的设置
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        self.validate_grid(&mut report);
+        self.validate_reels(&mut report);
+        self.validate_paylines(&mut report);
+        self.validate_symbols(&mut report);
+        self.validate_features(&mut report);
+        report.valid = report.errors.is_empty();
+        report
+    }
+
+    fn validate_grid(&self, report: &mut ValidationReport) {
+        let config = &self.config;
+        if config.reels == 0 {
+            report.error(
+                "invalid_grid",
+                "Config must have at least 1 reel".to_string(),
+            );
+        }
+        if config.is_megaway {
+            if config.min_megaway_rows == 0 {
+                report.error(
+                    "invalid_megaway_rows",
+                    "min_megaway_rows must be at least 1".to_string(),
+                );
+            }
+            if config.min_megaway_rows > config.max_megaway_rows {
+                report.error(
+                    "invalid_megaway_rows",
+                    format!(
+                        "min_megaway_rows ({}) is greater than max_megaway_rows ({})",
+                        config.min_megaway_rows, config.max_megaway_rows
+                    ),
+                );
+            }
+        } else if config.rows == 0 {
+            report.error(
+                "invalid_grid",
+                "Config must have at least 1 row".to_string(),
+            );
+        }
+
+        if config.min_bet > config.max_bet {
+            report.error(
+                "invalid_bet_limits",
+                format!(
+                    "min_bet ({}) is greater than max_bet ({})",
+                    config.min_bet, config.max_bet
+                ),
+            );
+        } else if !(config.min_bet..=config.max_bet).contains(&config.default_bet) {
+            report.warning(
+                "default_bet_out_of_range",
+                format!(
+                    "default_bet ({}) is outside min_bet..max_bet ({}..{})",
+                    config.default_bet, config.min_bet, config.max_bet
+                ),
+            );
+        }
+        if config.rtp_percentage <= 0.0 || config.rtp_percentage > 100.0 {
+            report.warning(
+                "unusual_rtp_target",
+                format!("rtp_percentage {} is outside 0..100", config.rtp_percentage),
+            );
+        }
+        if config.cascade_multipliers.contains(&0) {
+            report.error(
+                "invalid_cascade_multiplier",
+                "cascade_multipliers must be at least 1".to_string(),
+            );
+        }
+    }
+
+    fn validate_reels(&self, report: &mut ValidationReport) {
+        self.validate_reel_set(None, &self.reel_compositions, report);
+
+        let mut names: Vec<_> = self.reel_sets.keys().collect();
+        names.sort();
+        for name in names {
+            let used = match name.as_str() {
+                FREE_SPINS_REEL_SET => self.config.free_spins_enabled,
+                ANTE_REEL_SET | BONUS_BUY_REEL_SET => true,
+                _ => self.base_reel_set_weights.contains_key(name),
+            };
+            if !used {
+                report.warning(
+                    "unused_reel_set",
+                    format!("Reel set '{name}' is not used by the base game, free spins or any bet mode"),
+                );
+            }
+            self.validate_reel_set(Some(name), &self.reel_sets[name], report);
+        }
+
+        // 基础游戏抽取的卷轴组必须存在，且不能是有固定用途的卷轴组
+        let mut weighted: Vec<_> = self.base_reel_set_weights.iter().collect();
+        weighted.sort();
+        for (name, &weight) in weighted {
+            if matches!(
+                name.as_str(),
+                FREE_SPINS_REEL_SET | ANTE_REEL_SET | BONUS_BUY_REEL_SET
+            ) {
+                report.error(
+                    "invalid_reel_set_weight",
+                    format!(
+                        "Reel set '{name}' has a fixed role and can't be drawn in the base game"
+                    ),
+                );
+            } else if name != BASE_REEL_SET && !self.reel_sets.contains_key(name) {
+                report.error(
+                    "unknown_reel_set",
+                    format!("Reel set weight references unknown reel set '{name}'"),
+                );
+            }
+            if weight == 0 {
+                report.error(
+                    "invalid_reel_set_weight",
+                    format!("Reel set '{name}' needs a weight of at least 1"),
+                );
+            }
+        }
+    }
+
+    // 基础卷轴（name 为空）或命名卷轴组的每个卷轴都必须有可抽取的符号
+    fn validate_reel_set(
+        &self,
+        name: Option<&str>,
+        reel_compositions: &HashMap<usize, Vec<(i32, u32)>>,
+        report: &mut ValidationReport,
+    ) {
+        let symbol_ids: HashSet<i32> = self.symbols.iter().map(|s| s.id).collect();
+        let label = |reel: usize| match name {
+            Some(name) => format!("Reel {reel} of reel set '{name}'"),
+            None => format!("Reel {reel}"),
+        };
+
+        for reel in 0..self.config.reels {
+            let composition = reel_compositions.get(&reel).map(Vec::as_slice);
+            let Some(composition) = composition.filter(|c| !c.is_empty()) else {
+                report.error("empty_reel", format!("{} has no symbols", label(reel)));
+                continue;
+            };
+
+            for (position, (symbol_id, weight)) in composition.iter().enumerate() {
+                if !symbol_ids.contains(symbol_id) {
+                    report.error(
+                        "unknown_reel_symbol",
+                        format!("{} references unknown symbol {symbol_id}", label(reel)),
+                    );
+                }
+                if *weight == 0 {
+                    report.error(
+                        "zero_weight_position",
+                        format!(
+                            "{} position {position} needs a weight of at least 1",
+                            label(reel)
+                        ),
+                    );
+                }
+            }
+            let total_weight: u64 = composition
+                .iter()
+                .filter(|(symbol_id, _)| symbol_ids.contains(symbol_id))
+                .map(|(_, weight)| *weight as u64)
+                .sum();
+            if total_weight == 0 {
+                report.error(
+                    "zero_weight_reel",
+                    format!("{} has no symbols with a positive weight", label(reel)),
+                );
+            }
+        }
+
+        let mut extra: Vec<_> = reel_compositions
+            .keys()
+            .filter(|&&reel| reel >= self.config.reels)
+            .collect();
+        extra.sort();
+        for &reel in extra {
+            report.warning(
+                "reel_outside_grid",
+                format!(
+                    "{} is outside the {} reel grid and is ignored",
+                    label(reel),
+                    self.config.reels
+                ),
+            );
+        }
+    }
+
+    fn validate_paylines(&self, report: &mut ValidationReport) {
+        // Megaway按路数计算，簇赢奖按相连区域计算，都不使用支付线
+        if self.config.is_megaway || self.config.evaluation_mode == EvaluationMode::Cluster {
+            return;
+        }
+
+        let active: Vec<_> = self.paylines.iter().filter(|p| p.is_active).collect();
+        if active.is_empty() {
+            report.error(
+                "no_active_paylines",
+                "Config has no active paylines".to_string(),
+            );
+        }
+
+        let mut seen = HashSet::new();
+        for payline in &self.paylines {
+            let line = payline.line_number;
+            if !seen.insert(line) {
+                report.error(
+                    "duplicate_payline",
+                    format!("Payline {line} is defined more than once"),
+                );
+            }
+            if payline.pattern.is_empty() {
+                report.error("empty_payline", format!("Payline {line} has no positions"));
+                continue;
+            }
+
+            for &(reel, row) in &payline.pattern {
+                if reel >= self.config.reels || row >= self.config.rows {
+                    report.error(
+                        "payline_out_of_grid",
+                        format!(
+                            "Payline {line} position ({reel}, {row}) is outside the {}x{} grid",
+                            self.config.reels, self.config.rows
+                        ),
+                    );
+                }
+            }
+
+            // 连线从左到右判断，位置应依次覆盖卷轴 0, 1, 2 ...
+            let in_order = payline
+                .pattern
+                .iter()
+                .enumerate()
+                .all(|(i, &(reel, _))| reel == i);
+            if !in_order {
+                report.warning(
+                    "payline_not_left_to_right",
+                    format!("Payline {line} does not visit reels 0, 1, 2, ... in order"),
+                );
+            }
+            if payline.pattern.len() < 3 {
+                report.warning(
+                    "payline_too_short",
+                    format!("Payline {line} is shorter than the 3 symbols needed to win"),
+                );
+            }
+        }
+    }
+
+    fn validate_symbols(&self, report: &mut ValidationReport) {
+        let mut seen = HashSet::new();
+        let max_count = self.config.reels;
+        let cluster = self.config.evaluation_mode == EvaluationMode::Cluster;
+        let rows = if self.config.is_megaway {
+            self.config.max_megaway_rows
+        } else {
+            self.config.rows
+        };
+        let max_cluster = self.config.reels * rows;
+
+        for symbol in &self.symbols {
+            if !seen.insert(symbol.id) {
+                report.error(
+                    "duplicate_symbol",
+                    format!("Symbol id {} is defined more than once", symbol.id),
+                );
+            }
+
+            if symbol.payouts.contains_key(&0) {
+                report.error(
+                    "invalid_payout",
+                    format!("Symbol '{}' has a payout for 0 symbols", symbol.name),
+                );
+            }
+
+            let paying: Vec<usize> = symbol
+                .payouts
+                .iter()
+                .filter(|(_, &payout)| payout > 0)
+                .map(|(&count, _)| count)
+                .collect();
+
+            match symbol.symbol_type {
+                SymbolType::Normal | SymbolType::Wild => {
+                    // Wild通常只用于替代，不要求自身有赔付
+                    if paying.is_empty() && symbol.symbol_type == SymbolType::Normal {
+                        report.warning(
+                            "symbol_without_payouts",
+                            format!("Symbol '{}' has no payouts", symbol.name),
+                        );
+                    }
+                    if cluster {
+                        // 簇赢奖中Wild只加入其他符号的簇，自身的赔付不会使用
+                        if symbol.symbol_type == SymbolType::Wild && !paying.is_empty() {
+                            report.warning(
+                                "unreachable_payout",
+                                format!(
+                                    "Wild '{}' payouts are ignored in cluster mode",
+                                    symbol.name
+                                ),
+                            );
+                        }
+                        for count in paying.iter().filter(|&&c| c > max_cluster) {
+                            report.warning(
+                                "unreachable_payout",
+                                format!(
+                                    "Symbol '{}' pays for a cluster of {}, larger than the {} position grid",
+                                    symbol.name, count, max_cluster
+                                ),
+                            );
+                        }
+                        continue;
+                    }
+                    // 连线/路数至少3个符号才赔付，且不会超过卷轴数
+                    for count in paying.iter().filter(|&&c| c < 3 || c > max_count) {
+                        report.warning(
+                            "unreachable_payout",
+                            format!(
+                                "Symbol '{}' pays for {} in a row, which can never occur",
+                                symbol.name, count
+                            ),
+                        );
+                    }
+                }
+                SymbolType::Scatter => {
+                    if paying.is_empty() && !self.config.free_spins_enabled {
+                        report.warning(
+                            "symbol_without_payouts",
+                            format!(
+                                "Scatter '{}' has no payouts and free spins are disabled",
+                                symbol.name
+                            ),
+                        );
+                    }
+                }
+                SymbolType::Bonus | SymbolType::Money => {}
+            }
+        }
+    }
+
+    fn validate_features(&self, report: &mut ValidationReport) {
+        let on_reels: HashSet<i32> = self
+            .reel_compositions
+            .values()
+            .flatten()
+            .filter(|(_, weight)| *weight > 0)
+            .map(|(symbol_id, _)| *symbol_id)
+            .collect();
+        let has_type_on_reels = |symbol_type: SymbolType| {
+            self.symbols
+                .iter()
+                .any(|s| s.symbol_type == symbol_type && on_reels.contains(&s.id))
+        };
+
+        for wild in &self.wild_features {
+            match self.symbols.iter().find(|s| s.id == wild.symbol_id) {
+                Some(symbol) if symbol.symbol_type == SymbolType::Wild => {}
+                Some(symbol) => report.error(
+                    "invalid_wild_feature",
+                    format!(
+                        "Wild settings refer to '{}' which is not a Wild",
+                        symbol.name
+                    ),
+                ),
+                None => report.error(
+                    "invalid_wild_feature",
+                    format!("Wild settings refer to unknown symbol {}", wild.symbol_id),
+                ),
+            }
+            if wild.multiplier == 0 {
+                report.error(
+                    "invalid_wild_feature",
+                    format!("Wild symbol {} has a multiplier of 0", wild.symbol_id),
+                );
+            }
+        }
+
+        for trigger in &self.scatter_triggers {
+            if trigger.scatter_count == 0 {
+                report.error(
+                    "invalid_scatter_trigger",
+                    "Scatter triggers need a scatter_count of at least 1".to_string(),
+                );
+            }
+        }
+        if self.config.free_spins_enabled && !has_type_on_reels(SymbolType::Scatter) {
+            report.warning(
+                "free_spins_unreachable",
+                "Free spins are enabled but no Scatter symbol is on the reels".to_string(),
+            );
+        }
+
+        if let Some(game) = &self.bonus_game {
+            if game.trigger_count == 0 || game.picks == 0 || game.board_size == 0 {
+                report.error(
+                    "invalid_bonus_game",
+                    "Bonus game needs trigger_count, picks and board_size of at least 1"
+                        .to_string(),
+                );
+            }
+            if game.prizes.iter().all(|prize| prize.weight == 0) {
+                report.error(
+                    "invalid_bonus_game",
+                    "Bonus game has no prizes with a positive weight".to_string(),
+                );
+            }
+            if game.picks as usize > game.board_size {
+                report.warning(
+                    "bonus_picks_exceed_board",
+                    format!(
+                        "Bonus game allows {} picks on a board of {}",
+                        game.picks, game.board_size
+                    ),
+                );
+            }
+            if !has_type_on_reels(SymbolType::Bonus) {
+                report.warning(
+                    "bonus_game_unreachable",
+                    "Bonus game is configured but no Bonus symbol is on the reels".to_string(),
+                );
+            }
+        }
+
+        match &self.hold_and_spin {
+            Some(hold_and_spin) => {
+                if hold_and_spin.trigger_count == 0 || hold_and_spin.respins == 0 {
+                    report.error(
+                        "invalid_hold_and_spin",
+                        "Hold and spin needs trigger_count and respins of at least 1".to_string(),
+                    );
+                }
+                if hold_and_spin.values.iter().all(|value| value.weight == 0) {
+                    report.error(
+                        "invalid_hold_and_spin",
+                        "Hold and spin has no money values with a positive weight".to_string(),
+                    );
+                }
+                if !has_type_on_reels(SymbolType::Money) {
+                    report.warning(
+                        "hold_and_spin_unreachable",
+                        "Hold and spin is configured but no Money symbol is on the reels"
+                            .to_string(),
+                    );
+                }
+            }
+            None if has_type_on_reels(SymbolType::Money) => report.warning(
+                "money_without_hold_and_spin",
+                "Money symbols are on the reels but hold and spin is not configured".to_string(),
+            ),
+            None => {}
+        }
+
+        if let Some(ante_bet) = &self.ante_bet {
+            if ante_bet.cost_percent == 0 {
+                report.error(
+                    "invalid_ante_bet",
+                    "Ante bet needs a cost_percent of at least 1".to_string(),
+                );
+            }
+            if !self.reel_sets.contains_key(ANTE_REEL_SET) {
+                report.warning(
+                    "ante_uses_base_reels",
+                    "Ante bet has no 'ante' reel set, ante spins use the base reels".to_string(),
+                );
+            }
+        }
+
+        if let Some(bonus_buy) = &self.bonus_buy {
+            if bonus_buy.cost_multiplier == 0 || bonus_buy.free_spins == 0 {
+                report.error(
+                    "invalid_bonus_buy",
+                    "Bonus buy needs cost_multiplier and free_spins of at least 1".to_string(),
+                );
+            }
+            if !self.config.free_spins_enabled {
+                report.error(
+                    "invalid_bonus_buy",
+                    "Bonus buy is configured but free spins are disabled".to_string(),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::universal_slots::{Payline, SlotConfig, SlotConfigBuilder, SlotSymbol, SymbolType};
+    use std::collections::HashMap;
+
+    fn builder() -> SlotConfigBuilder {
+        let symbol = |id, name: &str, payouts: &[(usize, u32)]| SlotSymbol {
+            id,
+            name: name.to_string(),
+            symbol_type: SymbolType::Normal,
+            value: id as u32,
+            image_url: None,
+            payouts: payouts.iter().copied().collect(),
+        };
+        SlotConfigBuilder {
+            config: SlotConfig {
+                id: 1,
+                name: "Validation".to_string(),
+                rows: 3,
+                reels: 3,
+                is_megaway: false,
+                min_megaway_rows: 2,
+                max_megaway_rows: 7,
+                default_bet: 1,
+                min_bet: 1,
+                max_bet: 100,
+                wild_enabled: false,
+                free_spins_enabled: false,
+                free_spins_multiplier: 1,
+                reel_mode: Default::default(),
+                evaluation_mode: Default::default(),
+                cascading: false,
+                cascade_multipliers: Vec::new(),
+                rtp_percentage: 96.0,
+            },
+            symbols: vec![symbol(1, "A", &[(3, 10)]), symbol(2, "Blank", &[])],
+            reel_compositions: (0..3).map(|reel| (reel, vec![(1, 1), (2, 3)])).collect(),
+            paylines: vec![Payline {
+                line_number: 1,
+                pattern: vec![(0, 1), (1, 1), (2, 1)],
+                is_active: true,
+            }],
+            scatter_triggers: Vec::new(),
+            bonus_game: None,
+            wild_features: Vec::new(),
+            hold_and_spin: None,
+            reel_sets: HashMap::new(),
+            base_reel_set_weights: HashMap::new(),
+            ante_bet: None,
+            bonus_buy: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_config_only_warns() {
+        let report = builder().validate();
+        assert!(report.valid, "{:?}", report.errors);
+        assert!(report.has_code("symbol_without_payouts"));
+    }
+
+    #[test]
+    fn test_invalid_configs_are_rejected() {
+        let mut builder = builder();
+        builder.reel_compositions = HashMap::from([(0, vec![(1, 1)]), (1, vec![(1, 0)])]);
+        builder.paylines[0].pattern.push((4, 0));
+        builder.config.is_megaway = true;
+        builder.config.min_megaway_rows = 8;
+
+        let report = builder.validate();
+        assert!(!report.valid);
+        for code in [
+            "empty_reel",
+            "zero_weight_reel",
+            "zero_weight_position",
+            "invalid_megaway_rows",
+        ] {
+            assert!(report.has_code(code), "missing {code}");
+        }
+        // Megaway不使用支付线
+        assert!(!report.has_code("payline_out_of_grid"));
+
+        builder.config.is_megaway = false;
+        assert!(builder.validate().has_code("payline_out_of_grid"));
+
+        // 基础游戏只能抽取存在的、没有固定用途的卷轴组
+        builder.base_reel_set_weights =
+            HashMap::from([("missing".to_string(), 1), ("ante".to_string(), 1)]);
+        let report = builder.validate();
+        assert!(report.has_code("unknown_reel_set"));
+        assert!(report.has_code("invalid_reel_set_weight"));
+    }
+}
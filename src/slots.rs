@@ -1,7 +1,12 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Bet limits for the classic machine, which has no slot configuration of its own
+pub const MIN_BET: u64 = 1;
+pub const MAX_BET: u64 = 1000;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Symbol {
     Cherry,
@@ -76,8 +81,7 @@ impl Reel {
         }
     }
 
-    pub fn spin(&mut self) -> Symbol {
-        let mut rng = rand::rng();
+    pub fn spin<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Symbol {
         self.position = rng.random_range(0..self.symbols.len());
         self.symbols[self.position].clone()
     }
@@ -118,10 +122,23 @@ impl SlotMachine {
     }
 
     pub fn spin(&mut self) -> SpinResult {
+        let seed = rand::rng().random();
+        self.spin_seeded(seed)
+    }
+
+    // Spins with a fresh RNG derived from `seed`, so the result can be replayed later
+    pub fn spin_seeded(&mut self, seed: u64) -> SpinResult {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let mut result = self.spin_with_rng(&mut rng);
+        result.seed = Some(seed);
+        result
+    }
+
+    pub fn spin_with_rng<R: Rng + ?Sized>(&mut self, rng: &mut R) -> SpinResult {
         let mut grid = Vec::new();
 
         for reel in &mut self.reels {
-            let symbols = (0..self.rows).map(|_| reel.spin()).collect();
+            let symbols = (0..self.rows).map(|_| reel.spin(rng)).collect();
             grid.push(symbols);
         }
 
@@ -132,6 +149,7 @@ impl SlotMachine {
             grid,
             winning_lines,
             total_win,
+            seed: None,
         }
     }
 
@@ -244,6 +262,8 @@ pub struct SpinResult {
     pub grid: Vec<Vec<Symbol>>,
     pub winning_lines: Vec<WinningLine>,
     pub total_win: u32,
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -283,7 +303,7 @@ pub fn calculate_rtp(spins: u32) -> f64 {
     (total_win as f64 / total_bet as f64) * 100.0
 }
 
-// Progressive jackpot implementation
+// Progressive jackpot; the pool itself is stored in the progressive_jackpot table
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgressiveJackpot {
     pub current_amount: u64,
@@ -293,15 +313,6 @@ pub struct ProgressiveJackpot {
 }
 
 impl ProgressiveJackpot {
-    pub fn new(min_amount: u64, contribution_rate: f64) -> Self {
-        Self {
-            current_amount: min_amount,
-            contribution_rate,
-            min_amount,
-            last_won: None,
-        }
-    }
-
     pub fn add_contribution(&mut self, bet_amount: u64) {
         let contribution = (bet_amount as f64 * self.contribution_rate) as u64;
         self.current_amount += contribution;
@@ -341,7 +352,12 @@ mod tests {
 
     #[test]
     fn test_progressive_jackpot() {
-        let mut jackpot = ProgressiveJackpot::new(1000, 0.02);
+        let mut jackpot = ProgressiveJackpot {
+            current_amount: 1000,
+            contribution_rate: 0.02,
+            min_amount: 1000,
+            last_won: None,
+        };
         jackpot.add_contribution(100);
         assert_eq!(jackpot.current_amount, 1002);
 
@@ -349,4 +365,17 @@ mod tests {
         assert_eq!(win, Some(1002));
         assert_eq!(jackpot.current_amount, 1000);
     }
+
+    #[test]
+    fn test_seeded_spin_is_reproducible() {
+        let first = SlotMachine::new(3, 3).spin_seeded(42);
+        let second = SlotMachine::new(3, 3).spin_seeded(42);
+
+        assert_eq!(first.seed, Some(42));
+        assert_eq!(
+            serde_json::to_value(&first.grid).unwrap(),
+            serde_json::to_value(&second.grid).unwrap()
+        );
+        assert_eq!(first.total_win, second.total_win);
+    }
 }
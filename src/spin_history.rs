@@ -0,0 +1,395 @@
+use crate::universal_slots::{UniversalSlotMachine, UniversalSpinResult};
+use actix_web::{web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::types::time::OffsetDateTime;
+use sqlx::{PgConnection, Pool, Postgres};
+
+// 一次旋转的完整记录，写入 slot_spin_history
+#[derive(Debug, Clone)]
+pub struct SpinRecord {
+    pub player_id: Option<i32>,
+    pub session_id: Option<i32>,
+    pub slot_config_id: Option<i32>,
+    pub bet_amount: i64,
+    pub win_amount: i64,
+    pub symbols: serde_json::Value,
+    pub winning_lines: serde_json::Value,
+    pub is_megaway_spin: bool,
+    pub megaway_rows: Option<serde_json::Value>,
+    pub cascades: Option<serde_json::Value>, // 连锁消除的每一步，用于回放动画
+    pub rng_seed: Option<u64>,
+    pub fair_seed_id: Option<i32>,
+    pub fair_nonce: Option<i32>,
+    pub is_free_spin: bool,
+    pub free_spin_session_id: Option<i32>,
+    pub config_version_id: Option<i32>, // 旋转使用的已发布配置版本
+    pub bet_mode: String,               // 投注方式：standard / ante / bonus_buy
+    pub active_lines: Option<i32>,      // 玩家选择的支付线数，None 表示全部支付线
+}
+
+impl SpinRecord {
+    // 通用老虎机旋转的记录
+    pub fn universal(
+        machine: &UniversalSlotMachine,
+        result: &UniversalSpinResult,
+        player_id: Option<i32>,
+        session_id: Option<i32>,
+    ) -> Self {
+        Self {
+            player_id,
+            session_id,
+            slot_config_id: Some(machine.config.id),
+            // 加注和购买免费旋转按实际支付的金额记录
+            bet_amount: machine
+                .bet_cost(result.bet_per_line, result.bet_mode)
+                .unwrap_or_else(|| machine.total_bet(result.bet_per_line))
+                as i64,
+            win_amount: result.total_win as i64,
+            symbols: serde_json::to_value(&result.grid).unwrap_or_default(),
+            winning_lines: serde_json::to_value(&result.winning_lines).unwrap_or_default(),
+            is_megaway_spin: machine.config.is_megaway,
+            megaway_rows: result
+                .megaway_rows
+                .as_ref()
+                .and_then(|rows| serde_json::to_value(rows).ok()),
+            cascades: if result.cascades.is_empty() {
+                None
+            } else {
+                serde_json::to_value(&result.cascades).ok()
+            },
+            rng_seed: result.seed,
+            fair_seed_id: None,
+            fair_nonce: None,
+            is_free_spin: false,
+            free_spin_session_id: None,
+            config_version_id: None,
+            bet_mode: result.bet_mode.as_str().to_string(),
+            active_lines: None,
+        }
+    }
+}
+
+// 写入旋转记录，并累计到所属的游戏会话
+pub async fn record_spin(conn: &mut PgConnection, record: &SpinRecord) -> Result<i32, sqlx::Error> {
+    let rec = sqlx::query!(
+        r#"
+        INSERT INTO slot_spin_history
+        (player_id, session_id, slot_config_id, bet_amount, win_amount,
+         symbols, winning_lines, is_megaway_spin, megaway_rows, rng_seed,
+         fair_seed_id, fair_nonce, is_free_spin, free_spin_session_id, config_version_id,
+         cascades, bet_mode, active_lines)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+        RETURNING id
+        "#,
+        record.player_id,
+        record.session_id,
+        record.slot_config_id,
+        record.bet_amount,
+        record.win_amount,
+        record.symbols,
+        record.winning_lines,
+        record.is_megaway_spin,
+        record.megaway_rows,
+        // 种子按位存入BIGINT，读取时再转回u64
+        record.rng_seed.map(|seed| seed as i64),
+        record.fair_seed_id,
+        record.fair_nonce,
+        record.is_free_spin,
+        record.free_spin_session_id,
+        record.config_version_id,
+        record.cascades,
+        record.bet_mode,
+        record.active_lines
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if let Some(session_id) = record.session_id {
+        sqlx::query!(
+            r#"
+            UPDATE game_sessions
+            SET total_spins = COALESCE(total_spins, 0) + 1,
+                total_bet = COALESCE(total_bet, 0) + $2,
+                total_won = COALESCE(total_won, 0) + $3
+            WHERE id = $1
+            "#,
+            session_id,
+            record.bet_amount,
+            record.win_amount
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(rec.id)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpinHistoryQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub slot_config_id: Option<i32>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn to_offset_datetime(dt: chrono::DateTime<chrono::Utc>) -> Option<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp_nanos(dt.timestamp_nanos_opt()? as i128).ok()
+}
+
+// 分页查询玩家的旋转历史
+pub async fn get_player_spins(
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i32>,
+    query: web::Query<SpinHistoryQuery>,
+) -> Result<HttpResponse> {
+    let player_id = path.into_inner();
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(20).clamp(1, 100);
+    let from = query.from.and_then(to_offset_datetime);
+    let to = query.to.and_then(to_offset_datetime);
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!" FROM slot_spin_history
+        WHERE player_id = $1
+          AND ($2::int4 IS NULL OR slot_config_id = $2)
+          AND ($3::timestamptz IS NULL OR spin_time >= $3)
+          AND ($4::timestamptz IS NULL OR spin_time < $4)
+        "#,
+        player_id,
+        query.slot_config_id,
+        from,
+        to
+    )
+    .fetch_one(pool.get_ref())
+    .await;
+
+    let total = match total {
+        Ok(total) => total,
+        Err(e) => {
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to count spins: {}", e)
+            })))
+        }
+    };
+
+    let spins = sqlx::query!(
+        r#"
+        SELECT id, player_id, session_id, slot_config_id, bet_amount, bet_mode, active_lines,
+               win_amount, symbols, winning_lines, is_megaway_spin, megaway_rows, cascades,
+               hold_and_spin_result, rng_seed, fair_seed_id, fair_nonce, is_free_spin,
+               free_spin_session_id, config_version_id, spin_time
+        FROM slot_spin_history
+        WHERE player_id = $1
+          AND ($2::int4 IS NULL OR slot_config_id = $2)
+          AND ($3::timestamptz IS NULL OR spin_time >= $3)
+          AND ($4::timestamptz IS NULL OR spin_time < $4)
+        ORDER BY spin_time DESC, id DESC
+        LIMIT $5 OFFSET $6
+        "#,
+        player_id,
+        query.slot_config_id,
+        from,
+        to,
+        page_size,
+        (page - 1) * page_size
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match spins {
+        Ok(spins) => {
+            let serializable_spins: Vec<serde_json::Value> = spins
+                .into_iter()
+                .map(|spin| {
+                    serde_json::json!({
+                        "id": spin.id,
+                        "player_id": spin.player_id,
+                        "session_id": spin.session_id,
+                        "slot_config_id": spin.slot_config_id,
+                        "bet_amount": spin.bet_amount,
+                        "bet_mode": spin.bet_mode,
+                        "active_lines": spin.active_lines,
+                        "win_amount": spin.win_amount,
+                        "symbols": spin.symbols,
+                        "winning_lines": spin.winning_lines,
+                        "is_megaway_spin": spin.is_megaway_spin,
+                        "megaway_rows": spin.megaway_rows,
+                        "cascades": spin.cascades,
+                        "hold_and_spin_result": spin.hold_and_spin_result,
+                        "rng_seed": spin.rng_seed.map(|seed| seed as u64),
+                        "fair_seed_id": spin.fair_seed_id,
+                        "fair_nonce": spin.fair_nonce,
+                        "is_free_spin": spin.is_free_spin,
+                        "free_spin_session_id": spin.free_spin_session_id,
+                        "config_version_id": spin.config_version_id,
+                        "spin_time": spin.spin_time.map(|t| t.to_string())
+                    })
+                })
+                .collect();
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "spins": serializable_spins,
+                "page": page,
+                "page_size": page_size,
+                "total": total
+            })))
+        }
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to fetch spins: {}", e)
+        }))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spin(player_id: i32, session_id: Option<i32>, slot_config_id: i32, bet: i64) -> SpinRecord {
+        SpinRecord {
+            player_id: Some(player_id),
+            session_id,
+            slot_config_id: Some(slot_config_id),
+            bet_amount: bet,
+            win_amount: bet * 2,
+            symbols: serde_json::json!([]),
+            winning_lines: serde_json::json!([]),
+            is_megaway_spin: false,
+            megaway_rows: None,
+            cascades: None,
+            rng_seed: Some(u64::MAX),
+            fair_seed_id: None,
+            fair_nonce: None,
+            is_free_spin: false,
+            free_spin_session_id: None,
+            config_version_id: None,
+            bet_mode: "standard".to_string(),
+            active_lines: None,
+        }
+    }
+
+    async fn spins_page(
+        pool: &Pool<Postgres>,
+        player_id: i32,
+        query: SpinHistoryQuery,
+    ) -> serde_json::Value {
+        let resp = get_player_spins(
+            web::Data::new(pool.clone()),
+            web::Path::from(player_id),
+            web::Query(query),
+        )
+        .await
+        .unwrap();
+        assert!(resp.status().is_success());
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn query(
+        page: Option<i64>,
+        page_size: Option<i64>,
+        slot_config_id: Option<i32>,
+    ) -> SpinHistoryQuery {
+        SpinHistoryQuery {
+            page,
+            page_size,
+            slot_config_id,
+            from: None,
+            to: None,
+        }
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    async fn test_record_spin_updates_session_totals(pool: Pool<Postgres>) {
+        let mut conn = pool.acquire().await.unwrap();
+        let first = record_spin(&mut conn, &spin(1, Some(1), 1, 10))
+            .await
+            .unwrap();
+        let second = record_spin(&mut conn, &spin(1, Some(1), 1, 5))
+            .await
+            .unwrap();
+        record_spin(&mut conn, &spin(1, None, 1, 100))
+            .await
+            .unwrap();
+        assert!(second > first);
+
+        let totals: (i32, i64, i64) = sqlx::query_as(
+            "SELECT total_spins, total_bet, total_won FROM game_sessions WHERE id = 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(totals, (2, 15, 30));
+
+        // 种子按位存入BIGINT，读取时还原为u64
+        let page = spins_page(&pool, 1, query(None, None, None)).await;
+        assert_eq!(page["spins"][0]["rng_seed"], u64::MAX);
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    async fn test_player_spins_paginate_and_filter(pool: Pool<Postgres>) {
+        let mut conn = pool.acquire().await.unwrap();
+        for bet in 1..=5 {
+            let config_id = if bet <= 3 { 1 } else { 2 };
+            record_spin(&mut conn, &spin(1, None, config_id, bet))
+                .await
+                .unwrap();
+        }
+        record_spin(&mut conn, &spin(2, None, 1, 99)).await.unwrap();
+        // 前三次旋转放到一月，后两次放到二月
+        sqlx::query(
+            "UPDATE slot_spin_history
+             SET spin_time = CASE WHEN bet_amount <= 3 THEN TIMESTAMPTZ '2024-01-15' + bet_amount * INTERVAL '1 hour'
+                                  ELSE TIMESTAMPTZ '2024-02-15' + bet_amount * INTERVAL '1 hour' END",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let bets = |page: &serde_json::Value| -> Vec<i64> {
+            page["spins"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|spin| spin["bet_amount"].as_i64().unwrap())
+                .collect()
+        };
+
+        // 按时间倒序分页，total 为过滤后的总数
+        let first = spins_page(&pool, 1, query(Some(1), Some(2), None)).await;
+        assert_eq!(first["total"], 5);
+        assert_eq!(bets(&first), vec![5, 4]);
+        let third = spins_page(&pool, 1, query(Some(3), Some(2), None)).await;
+        assert_eq!(bets(&third), vec![1]);
+        let beyond = spins_page(&pool, 1, query(Some(4), Some(2), None)).await;
+        assert_eq!(bets(&beyond), Vec::<i64>::new());
+
+        // page 和 page_size 超出范围时取边界值
+        let clamped = spins_page(&pool, 1, query(Some(0), Some(1000), None)).await;
+        assert_eq!(
+            (clamped["page"].as_i64(), clamped["page_size"].as_i64()),
+            (Some(1), Some(100))
+        );
+        assert_eq!(bets(&clamped).len(), 5);
+
+        let by_config = spins_page(&pool, 1, query(None, None, Some(2))).await;
+        assert_eq!(by_config["total"], 2);
+        assert_eq!(bets(&by_config), vec![5, 4]);
+
+        // from 包含、to 不包含
+        let january = spins_page(
+            &pool,
+            1,
+            SpinHistoryQuery {
+                from: Some("2024-01-15T02:00:00Z".parse().unwrap()),
+                to: Some("2024-02-01T00:00:00Z".parse().unwrap()),
+                ..query(None, None, None)
+            },
+        )
+        .await;
+        assert_eq!(bets(&january), vec![3, 2]);
+
+        let other_player = spins_page(&pool, 2, query(None, None, None)).await;
+        assert_eq!(bets(&other_player), vec![99]);
+    }
+}
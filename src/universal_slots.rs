@@ -1,10 +1,12 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlotConfig {
-    pub id: i32,
+    #[serde(default)]
+    pub id: i32, // 游戏定义文档中可以省略，导入时分配
     pub name: String,
     pub rows: usize,
     pub reels: usize,
@@ -16,15 +18,50 @@ pub struct SlotConfig {
     pub max_bet: u32,
     pub wild_enabled: bool,
     pub free_spins_enabled: bool,
+    pub free_spins_multiplier: u32, // 免费旋转期间的赢金倍数
+    #[serde(default)]
+    pub reel_mode: ReelMode,
+    #[serde(default)]
+    pub evaluation_mode: EvaluationMode,
+    #[serde(default)]
+    pub cascading: bool, // 连锁消除：赢奖符号消除后上方符号下落并补充新符号，直到不再有赢奖
+    #[serde(default)]
+    pub cascade_multipliers: Vec<u32>, // 每次连锁的赢金倍数，超出后使用最后一个，为空时为1
     pub rtp_percentage: f64,
 }
 
+// 单次旋转最多的连锁次数，避免卷轴配置导致无限连锁
+pub const MAX_CASCADES: usize = 50;
+
+// 卷轴抽样方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReelMode {
+    // 每个可见位置按权重独立抽取
+    #[default]
+    Weighted,
+    // 每个卷轴抽取一个停止位置，按卷轴条顺序连续读取可见窗口（首尾相连）
+    Stepper,
+}
+
+// 赢奖计算方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EvaluationMode {
+    // 支付线（Megaway时按路数）
+    #[default]
+    Lines,
+    // 簇赢奖：上下左右相连的相同符号（含可替代的Wild）按簇大小赔付
+    Cluster,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SymbolType {
     Normal,
     Wild,
     Scatter,
     Bonus,
+    Money, // Hold and Spin的钱币符号，每次出现时抽取金额
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +80,54 @@ pub struct ReelStrip {
     pub symbols: Vec<(SlotSymbol, u32)>, // (符号, 权重)
 }
 
+// 卷轴的累积权重表，在构建机器时预先计算，旋转时按索引抽取符号
+// 与逐个展开权重的符号池使用相同的随机数，相同种子的结果保持不变
+#[derive(Debug, Clone, Default)]
+pub struct ReelSampler {
+    cumulative_weights: Vec<u64>, // 单个权重最大为 i32::MAX，累加使用 u64 不会溢出
+}
+
+impl ReelSampler {
+    pub fn new(reel_strip: &ReelStrip) -> Self {
+        let cumulative_weights = reel_strip
+            .symbols
+            .iter()
+            .scan(0u64, |total, (_, weight)| {
+                *total += *weight as u64;
+                Some(*total)
+            })
+            .collect();
+        Self { cumulative_weights }
+    }
+
+    pub fn total_weight(&self) -> u64 {
+        self.cumulative_weights.last().copied().unwrap_or(0)
+    }
+
+    // 返回卷轴条中被抽中的符号索引
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let roll = roll(rng, self.total_weight());
+        self.cumulative_weights.partition_point(|&c| c <= roll)
+    }
+}
+
+// 赢金按 u64 计算，超出 u32 时封顶为 u32::MAX
+fn capped(value: u64) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+// 在 [0, n) 中均匀抽取一个整数，不依赖 rand 的 random_range 实现，结果只由随机数流决定：
+// 每次取8字节按小端序组成 u64，落在 2^64 对 n 取余后剩下的尾部时丢弃重取，否则对 n 取余
+pub fn roll<R: Rng + ?Sized>(rng: &mut R, n: u64) -> u64 {
+    let tail = (u64::MAX % n + 1) % n;
+    loop {
+        let value = rng.next_u64();
+        if value <= u64::MAX - tail {
+            return value % n;
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Payline {
     pub line_number: usize,
@@ -50,12 +135,374 @@ pub struct Payline {
     pub is_active: bool,
 }
 
+// Scatter触发表：出现 scatter_count 个及以上Scatter时奖励 free_spins 次免费旋转
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScatterTrigger {
+    pub scatter_count: usize,
+    pub free_spins: u32,
+}
+
+pub fn default_scatter_triggers() -> Vec<ScatterTrigger> {
+    [(3, 10), (4, 15), (5, 20), (6, 25)]
+        .into_iter()
+        .map(|(scatter_count, free_spins)| ScatterTrigger {
+            scatter_count,
+            free_spins,
+        })
+        .collect()
+}
+
+// Bonus选宝游戏：trigger_count 个及以上Bonus符号触发，
+// 从奖品表中按权重抽取 board_size 个隐藏奖品，玩家可以选择 picks 次
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BonusGameConfig {
+    pub trigger_count: usize,
+    pub picks: u32,
+    pub board_size: usize,
+    pub prizes: Vec<BonusPrize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BonusPrize {
+    pub multiplier: u32, // 总投注的倍数
+    pub weight: u32,
+}
+
+impl BonusGameConfig {
+    // 由种子生成隐藏的奖品面板，相同种子得到相同面板
+    pub fn generate_board(&self, seed: u64) -> Vec<u32> {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let total_weight: u64 = self.prizes.iter().map(|p| p.weight as u64).sum();
+        if total_weight == 0 {
+            return vec![0; self.board_size];
+        }
+
+        (0..self.board_size)
+            .map(|_| {
+                let mut roll = roll(&mut rng, total_weight);
+                for prize in &self.prizes {
+                    if roll < prize.weight as u64 {
+                        return prize.multiplier;
+                    }
+                    roll -= prize.weight as u64;
+                }
+                0
+            })
+            .collect()
+    }
+}
+
+// Hold and Spin（锁定重转）：trigger_count 个及以上钱币符号触发，钱币锁定在原位置，
+// 其余位置重转 respins 次，每出现新的钱币重置剩余次数，结束时赢得所有钱币金额之和
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HoldAndSpinConfig {
+    pub trigger_count: usize,
+    pub respins: u32,
+    pub values: Vec<MoneyValue>, // 钱币金额表，每个钱币按权重抽取
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MoneyValue {
+    pub multiplier: u32, // 总投注的倍数
+    #[serde(default)]
+    pub jackpot: Option<JackpotTier>, // 奖池钱币的标签，金额同样为 multiplier
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JackpotTier {
+    Mini,
+    Minor,
+    Major,
+    Grand,
+}
+
+// 网格上的一个钱币及其抽取到的金额
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Coin {
+    pub reel: usize,
+    pub row: usize,
+    pub multiplier: u32,
+    #[serde(default)]
+    pub jackpot: Option<JackpotTier>,
+}
+
+impl HoldAndSpinConfig {
+    fn draw_coin<R: Rng + ?Sized>(&self, reel: usize, row: usize, rng: &mut R) -> Coin {
+        let total_weight: u64 = self.values.iter().map(|v| v.weight as u64).sum();
+        let mut coin = Coin {
+            reel,
+            row,
+            multiplier: 0,
+            jackpot: None,
+        };
+        if total_weight == 0 {
+            return coin;
+        }
+
+        let mut roll = roll(rng, total_weight);
+        for value in &self.values {
+            if roll < value.weight as u64 {
+                coin.multiplier = value.multiplier;
+                coin.jackpot = value.jackpot;
+                break;
+            }
+            roll -= value.weight as u64;
+        }
+        coin
+    }
+}
+
+// Hold and Spin的进度：reel_rows 为触发时每个卷轴的行数，coins 为已锁定的钱币
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HoldAndSpinState {
+    pub reel_rows: Vec<usize>,
+    pub coins: Vec<Coin>,
+    pub respins_remaining: u32,
+    pub respins_played: u32,
+}
+
+impl HoldAndSpinState {
+    pub fn new(reel_rows: Vec<usize>, coins: Vec<Coin>, respins: u32) -> Self {
+        let mut state = Self {
+            reel_rows,
+            coins,
+            respins_remaining: respins,
+            respins_played: 0,
+        };
+        if state.is_full() {
+            state.respins_remaining = 0;
+        }
+        state
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.coins.len() >= self.reel_rows.iter().sum::<usize>()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.respins_remaining == 0
+    }
+
+    // 所有锁定钱币的金额之和（总投注的倍数）
+    pub fn total_multiplier(&self) -> u64 {
+        self.coins.iter().map(|coin| coin.multiplier as u64).sum()
+    }
+
+    // 第 n 次重转的随机数：以触发旋转的种子为密钥、n 为流编号的 ChaCha20 流，整个特性可以重放；
+    // 不同触发种子的密钥不同，各回合的重转不会共用同一段随机数
+    pub fn respin_rng(trigger_seed: u64, respin: u32) -> ChaCha20Rng {
+        let mut rng = ChaCha20Rng::seed_from_u64(trigger_seed);
+        rng.set_stream(respin as u64);
+        rng
+    }
+}
+
+// 卷轴组名称：基础卷轴，以及免费旋转、加注和购买免费旋转使用的卷轴组（未配置时使用基础游戏的卷轴）
+pub const BASE_REEL_SET: &str = "base";
+pub const FREE_SPINS_REEL_SET: &str = "free_spins";
+pub const ANTE_REEL_SET: &str = "ante";
+pub const BONUS_BUY_REEL_SET: &str = "bonus_buy";
+
+// 投注方式：标准旋转、加注旋转或购买免费旋转
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BetMode {
+    #[default]
+    Standard,
+    Ante,
+    BonusBuy,
+}
+
+impl BetMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BetMode::Standard => "standard",
+            BetMode::Ante => "ante",
+            BetMode::BonusBuy => "bonus_buy",
+        }
+    }
+
+    // 该投注方式使用的卷轴组，标准旋转使用基础卷轴
+    pub fn reel_set(self) -> Option<&'static str> {
+        match self {
+            BetMode::Standard => None,
+            BetMode::Ante => Some(ANTE_REEL_SET),
+            BetMode::BonusBuy => Some(BONUS_BUY_REEL_SET),
+        }
+    }
+}
+
+// 加注：额外支付总投注的 cost_percent%，在 ante 卷轴组上旋转以提高特性触发频率，赢金仍按原投注计算
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnteBetConfig {
+    pub cost_percent: u32,
+}
+
+// 购买免费旋转：支付 cost_multiplier 倍总投注，在 bonus_buy 卷轴组上旋转一次，
+// 必定获得至少 free_spins 次免费旋转
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BonusBuyConfig {
+    pub cost_multiplier: u32,
+    pub free_spins: u32,
+}
+
+// 客户端提交的投注，三种写法只能选一种：
+// bet_per_line 直接给出每线投注；coin_value × coins_per_line 为每线投注；
+// total_bet 只用于Megaway和簇赢奖等不按线计的游戏。lines 为激活的支付线数，未指定时使用全部支付线
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct BetRequest {
+    pub bet_per_line: Option<u32>,
+    pub coin_value: Option<u32>,
+    pub coins_per_line: Option<u32>,
+    pub total_bet: Option<u32>,
+    pub lines: Option<u32>,
+}
+
+// 校验通过的投注：bet_per_line 在 min_bet..=max_bet 内，total_bet 已按激活的支付线计算
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValidatedBet {
+    pub bet_per_line: u32,
+    pub lines: Option<u32>,
+    pub total_bet: u64,
+}
+
+// 投注校验失败的原因，code() 返回给客户端用于区分错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum BetError {
+    MissingBet,
+    AmbiguousBet,
+    ZeroBet,
+    BetOverflow,
+    BelowMinimum { bet: u64, min: u64 },
+    AboveMaximum { bet: u64, max: u64 },
+    TotalBetNotSupported,
+    LinesNotSelectable,
+    InvalidLineCount { lines: u32, max: u32 },
+}
+
+impl BetError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            BetError::MissingBet => "missing_bet",
+            BetError::AmbiguousBet => "ambiguous_bet",
+            BetError::ZeroBet => "zero_bet",
+            BetError::BetOverflow => "bet_overflow",
+            BetError::BelowMinimum { .. } => "bet_below_minimum",
+            BetError::AboveMaximum { .. } => "bet_above_maximum",
+            BetError::TotalBetNotSupported => "total_bet_not_supported",
+            BetError::LinesNotSelectable => "lines_not_selectable",
+            BetError::InvalidLineCount { .. } => "invalid_line_count",
+        }
+    }
+}
+
+impl std::fmt::Display for BetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BetError::MissingBet => write!(
+                f,
+                "Specify bet_per_line, coin_value and coins_per_line, or total_bet"
+            ),
+            BetError::AmbiguousBet => write!(
+                f,
+                "Specify only one of bet_per_line, coin_value and coins_per_line, or total_bet"
+            ),
+            BetError::ZeroBet => write!(f, "Bet must be greater than zero"),
+            BetError::BetOverflow => write!(f, "Bet amount is too large"),
+            BetError::BelowMinimum { bet, min } => {
+                write!(f, "Bet {bet} is below the minimum bet {min}")
+            }
+            BetError::AboveMaximum { bet, max } => {
+                write!(f, "Bet {bet} is above the maximum bet {max}")
+            }
+            BetError::TotalBetNotSupported => write!(
+                f,
+                "total_bet is only accepted by ways and cluster games, use bet_per_line"
+            ),
+            BetError::LinesNotSelectable => {
+                write!(f, "Ways and cluster games do not have selectable lines")
+            }
+            BetError::InvalidLineCount { lines, max } => {
+                write!(f, "Line count {lines} must be between 1 and {max}")
+            }
+        }
+    }
+}
+
+// 检查投注是否在限额内，未配置老虎机的旧接口同样使用
+pub fn check_bet_limits(bet: u64, min: u64, max: u64) -> Result<(), BetError> {
+    if bet == 0 {
+        Err(BetError::ZeroBet)
+    } else if bet < min {
+        Err(BetError::BelowMinimum { bet, min })
+    } else if bet > max {
+        Err(BetError::AboveMaximum { bet, max })
+    } else {
+        Ok(())
+    }
+}
+
+// 命名的卷轴组，抽样表在构建机器时计算
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReelSet {
+    pub reel_strips: Vec<ReelStrip>,
+    #[serde(skip)]
+    pub reel_samplers: Vec<ReelSampler>,
+}
+
+impl ReelSet {
+    pub fn new(reel_strips: Vec<ReelStrip>) -> Self {
+        let reel_samplers = reel_strips.iter().map(ReelSampler::new).collect();
+        Self {
+            reel_strips,
+            reel_samplers,
+        }
+    }
+}
+
+// Wild属性：未配置的Wild倍数为1，可以替代所有普通符号
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WildFeature {
+    pub symbol_id: i32,
+    pub multiplier: u32,             // 参与连线时的赢金倍数，多个Wild的倍数相乘
+    pub cannot_substitute: Vec<i32>, // 不能替代的符号ID
+    pub expanding: bool,             // 出现时扩展到整个卷轴
+    pub sticky: bool,                // 免费旋转期间保持在原位置
+    pub walking: bool,               // 免费旋转期间每次向左移动一个卷轴
+}
+
+// 免费旋转期间保留到下一次旋转的Wild位置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeldWild {
+    pub symbol_id: i32,
+    pub reel: usize,
+    pub row: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniversalSlotMachine {
     pub config: SlotConfig,
     pub symbols: Vec<SlotSymbol>,
     pub reel_strips: Vec<ReelStrip>,
     pub paylines: Vec<Payline>,
+    pub scatter_triggers: Vec<ScatterTrigger>,
+    pub bonus_game: Option<BonusGameConfig>,
+    pub wild_features: Vec<WildFeature>,
+    #[serde(default)]
+    pub hold_and_spin: Option<HoldAndSpinConfig>,
+    #[serde(default)]
+    pub reel_sets: HashMap<String, ReelSet>, // 基础卷轴之外的命名卷轴组
+    // 基础游戏每次旋转按权重选择的卷轴组（按名称排序，base 为基础卷轴），为空时总是使用基础卷轴
+    #[serde(default)]
+    pub base_reel_set_weights: Vec<(String, u32)>,
+    #[serde(default)]
+    pub ante_bet: Option<AnteBetConfig>,
+    #[serde(default)]
+    pub bonus_buy: Option<BonusBuyConfig>,
+    #[serde(skip)]
+    pub reel_samplers: Vec<ReelSampler>,
 }
 
 impl UniversalSlotMachine {
@@ -65,273 +512,996 @@ impl UniversalSlotMachine {
         reel_strips: Vec<ReelStrip>,
         paylines: Vec<Payline>,
     ) -> Self {
+        let reel_samplers = reel_strips.iter().map(ReelSampler::new).collect();
         Self {
             config,
             symbols,
             reel_strips,
+            reel_samplers,
             paylines,
+            scatter_triggers: default_scatter_triggers(),
+            bonus_game: None,
+            wild_features: Vec::new(),
+            hold_and_spin: None,
+            reel_sets: HashMap::new(),
+            base_reel_set_weights: Vec::new(),
+            ante_bet: None,
+            bonus_buy: None,
         }
     }
 
     pub fn spin(&self, bet_per_line: u32) -> UniversalSpinResult {
-        let mut rng = rand::rng();
+        self.spin_mode(bet_per_line, BetMode::Standard)
+    }
+
+    pub fn spin_mode(&self, bet_per_line: u32, mode: BetMode) -> UniversalSpinResult {
+        let seed = rand::rng().random();
+        self.spin_seeded_mode(bet_per_line, mode, seed)
+    }
+
+    // 使用种子生成确定性的随机数，相同的配置+种子+投注总是得到相同结果
+    pub fn spin_seeded(&self, bet_per_line: u32, seed: u64) -> UniversalSpinResult {
+        self.spin_seeded_mode(bet_per_line, BetMode::Standard, seed)
+    }
+
+    pub fn spin_seeded_mode(
+        &self,
+        bet_per_line: u32,
+        mode: BetMode,
+        seed: u64,
+    ) -> UniversalSpinResult {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let mut result = self.spin_with_rng_mode(bet_per_line, mode, &mut rng);
+        result.seed = Some(seed);
+        result
+    }
+
+    pub fn spin_with_rng<R: Rng + ?Sized>(
+        &self,
+        bet_per_line: u32,
+        rng: &mut R,
+    ) -> UniversalSpinResult {
+        self.spin_with_rng_mode(bet_per_line, BetMode::Standard, rng)
+    }
+
+    // 加注和购买免费旋转使用各自的卷轴组，购买时保证触发免费旋转
+    pub fn spin_with_rng_mode<R: Rng + ?Sized>(
+        &self,
+        bet_per_line: u32,
+        mode: BetMode,
+        rng: &mut R,
+    ) -> UniversalSpinResult {
+        self.spin_internal(bet_per_line, rng, None, mode)
+    }
+
+    // 免费旋转：先放置上一次保留的粘性/移动Wild，结果中返回需要保留到下一次的Wild
+    pub fn spin_free_seeded(
+        &self,
+        bet_per_line: u32,
+        seed: u64,
+        held_wilds: &[HeldWild],
+    ) -> UniversalSpinResult {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let mut result =
+            self.spin_internal(bet_per_line, &mut rng, Some(held_wilds), BetMode::Standard);
+        result.seed = Some(seed);
+        result
+    }
+
+    fn spin_internal<R: Rng + ?Sized>(
+        &self,
+        bet_per_line: u32,
+        rng: &mut R,
+        held_wilds: Option<&[HeldWild]>,
+        mode: BetMode,
+    ) -> UniversalSpinResult {
         let mut grid = Vec::new();
         let mut megaway_rows = Vec::new();
+        let mut reel_stops = Vec::new();
+        // 免费旋转使用 free_spins 卷轴组（未配置时与基础游戏相同），基础游戏在加权的卷轴组中抽取
+        let reel_set = match mode {
+            BetMode::Standard
+                if held_wilds.is_some() && self.reel_sets.contains_key(FREE_SPINS_REEL_SET) =>
+            {
+                Some(FREE_SPINS_REEL_SET)
+            }
+            BetMode::Standard => self.pick_base_reel_set(rng),
+            _ => mode.reel_set(),
+        };
+        let (reel_strips, reel_samplers) = self.reels_for(reel_set);
 
         // 生成每个卷轴的结果
-        for reel_strip in &self.reel_strips {
-            let reel_symbols = if self.config.is_megaway {
+        for (reel_strip, sampler) in reel_strips.iter().zip(reel_samplers) {
+            let rows = if self.config.is_megaway {
                 // Megaway模式：每个卷轴的行数随机
-                let rows =
-                    rng.random_range(self.config.min_megaway_rows..=self.config.max_megaway_rows);
+                let (min_rows, max_rows) =
+                    (self.config.min_megaway_rows, self.config.max_megaway_rows);
+                let rows = min_rows + roll(rng, (max_rows - min_rows + 1) as u64) as usize;
                 megaway_rows.push(rows);
-                self.spin_reel(reel_strip, rows)
+                rows
             } else {
                 // 标准模式：固定行数
-                self.spin_reel(reel_strip, self.config.rows)
+                self.config.rows
+            };
+            let reel_symbols = match self.config.reel_mode {
+                ReelMode::Weighted => self.spin_reel(reel_strip, sampler, rows, rng),
+                ReelMode::Stepper => {
+                    let stop = sampler.sample(rng);
+                    reel_stops.push(stop);
+                    self.reel_window(reel_strip, stop, rows)
+                }
             };
             grid.push(reel_symbols);
         }
 
+        // 放置保留的Wild，然后扩展Wild
+        if let Some(held_wilds) = held_wilds {
+            self.place_held_wilds(&mut grid, held_wilds);
+        }
+        let expanded_reels = self.expand_wilds(&mut grid);
+
         // 计算获胜线路
-        let winning_lines = if self.config.is_megaway {
-            self.check_megaway_wins(&grid)
-        } else {
-            self.check_standard_wins(&grid)
-        };
+        let mut winning_lines = self.check_wins(&grid);
+
+        // 连锁消除：每一步记录网格和赢奖，结果中的grid保持为初始网格
+        let initial_grid = grid.clone();
+        let mut cascades = Vec::new();
+        if self.config.cascading {
+            let mut wins = std::mem::take(&mut winning_lines);
+            let mut stops = reel_stops.clone();
+            loop {
+                let multiplier = self.cascade_multiplier(cascades.len());
+                for win in &mut wins {
+                    win.win_multiplier = win.win_multiplier.saturating_mul(multiplier);
+                }
+                cascades.push(CascadeStep {
+                    grid: grid.clone(),
+                    winning_lines: wins.clone(),
+                    multiplier,
+                    win: self.calculate_total_win(&wins, bet_per_line),
+                });
+                if wins.is_empty() {
+                    break;
+                }
+                // 达到上限时这一步的赢奖照常支付，只是不再消除
+                if cascades.len() >= MAX_CASCADES {
+                    winning_lines.append(&mut wins);
+                    break;
+                }
+                self.tumble(
+                    &mut grid,
+                    &wins,
+                    &mut stops,
+                    reel_strips,
+                    reel_samplers,
+                    rng,
+                );
+                winning_lines.append(&mut wins);
+                wins = self.check_wins(&grid);
+            }
+        }
+
+        // Scatter任意位置赔付，按总投注计算；连锁时使用最后一步的网格
+        let scatter_wins = self.check_scatter_wins(&grid, bet_per_line);
 
         // 计算总赢金
-        let total_win = self.calculate_total_win(&winning_lines, bet_per_line);
+        let total_win = capped(
+            self.calculate_total_win(&winning_lines, bet_per_line) as u64
+                + scatter_wins
+                    .iter()
+                    .map(|w| w.win_amount as u64)
+                    .sum::<u64>(),
+        );
 
-        // 检查免费旋转触发
-        let free_spins = if self.config.free_spins_enabled {
+        // 检查免费旋转触发，购买时至少奖励购买的次数
+        let mut free_spins = if self.config.free_spins_enabled {
             self.check_free_spins(&grid)
         } else {
             0
         };
+        if let (BetMode::BonusBuy, Some(bonus_buy)) = (mode, &self.bonus_buy) {
+            free_spins = free_spins.max(bonus_buy.free_spins);
+        }
+
+        // 免费旋转中只能再次触发免费旋转，选宝和Hold and Spin只由基础游戏触发
+        let is_free_spin = held_wilds.is_some();
+        let bonus_triggered = !is_free_spin && self.check_bonus_trigger(&grid);
+
+        let held_wilds = if is_free_spin {
+            self.next_held_wilds(&grid)
+        } else {
+            Vec::new()
+        };
+
+        // 钱币金额在网格确定后抽取，未配置Hold and Spin时不消耗随机数
+        let coins = self.draw_coins(&grid, rng);
+        let hold_and_spin_triggered = !is_free_spin && self.hold_and_spin_triggered_by(coins.len());
 
         UniversalSpinResult {
-            grid,
+            grid: initial_grid,
+            cascades,
             megaway_rows: if self.config.is_megaway {
                 Some(megaway_rows)
             } else {
                 None
             },
             winning_lines,
+            scatter_wins,
             total_win,
             free_spins,
+            bonus_triggered,
+            coins,
+            hold_and_spin_triggered,
+            expanded_reels,
+            held_wilds,
+            reel_stops: match self.config.reel_mode {
+                ReelMode::Weighted => None,
+                ReelMode::Stepper => Some(reel_stops),
+            },
             bet_per_line,
+            bet_mode: mode,
+            reel_set: reel_set
+                .filter(|name| self.reel_sets.contains_key(*name))
+                .map(str::to_string),
+            seed: None,
         }
     }
 
-    fn spin_reel(&self, reel_strip: &ReelStrip, rows: usize) -> Vec<SlotSymbol> {
-        let mut rng = rand::rng();
-        let mut symbols = Vec::new();
-
-        // 创建加权符号池
-        let mut weighted_symbols = Vec::new();
-        for (symbol, weight) in &reel_strip.symbols {
-            for _ in 0..*weight {
-                weighted_symbols.push(symbol.clone());
+    // 按权重抽取基础游戏的卷轴组，未配置权重时不消耗随机数
+    pub fn pick_base_reel_set<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&str> {
+        let total_weight: u64 = self
+            .base_reel_set_weights
+            .iter()
+            .map(|(_, w)| *w as u64)
+            .sum();
+        if total_weight == 0 {
+            return None;
+        }
+        let mut roll = roll(rng, total_weight);
+        for (name, weight) in &self.base_reel_set_weights {
+            if roll < *weight as u64 {
+                return Some(name);
             }
+            roll -= *weight as u64;
         }
+        None
+    }
 
-        // 随机选择符号
-        for _ in 0..rows {
-            let idx = rng.random_range(0..weighted_symbols.len());
-            symbols.push(weighted_symbols[idx].clone());
+    // 卷轴组的卷轴条和抽样表，未指定或未配置时使用基础卷轴
+    pub fn reels_for(&self, reel_set: Option<&str>) -> (&[ReelStrip], &[ReelSampler]) {
+        match reel_set.and_then(|name| self.reel_sets.get(name)) {
+            Some(set) => (&set.reel_strips, &set.reel_samplers),
+            None => (&self.reel_strips, &self.reel_samplers),
         }
+    }
 
-        symbols
+    // 按投注方式实际收取的金额，未配置的投注方式返回None
+    pub fn bet_cost(&self, bet_per_line: u32, mode: BetMode) -> Option<u64> {
+        let total_bet = self.total_bet(bet_per_line);
+        match mode {
+            BetMode::Standard => Some(total_bet),
+            BetMode::Ante => self
+                .ante_bet
+                .as_ref()
+                .map(|ante| (total_bet * (100 + ante.cost_percent as u64)).div_ceil(100)),
+            BetMode::BonusBuy => self
+                .bonus_buy
+                .as_ref()
+                .map(|buy| total_bet * buy.cost_multiplier as u64),
+        }
     }
 
-    fn check_standard_wins(&self, grid: &[Vec<SlotSymbol>]) -> Vec<WinningLine> {
-        let mut winning_lines = Vec::new();
+    // 本次旋转的总投注：Megaway和簇赢奖只收取一次；标准模式按激活的支付线计
+    pub fn total_bet(&self, bet_per_line: u32) -> u64 {
+        if self.is_ways_game() {
+            bet_per_line as u64
+        } else {
+            bet_per_line as u64 * self.active_line_count() as u64
+        }
+    }
 
-        for payline in &self.paylines {
-            if !payline.is_active {
-                continue;
+    // 不按线计的游戏：Megaway和簇赢奖
+    pub fn is_ways_game(&self) -> bool {
+        self.config.is_megaway || self.config.evaluation_mode == EvaluationMode::Cluster
+    }
+
+    pub fn active_line_count(&self) -> u32 {
+        self.paylines.iter().filter(|p| p.is_active).count() as u32
+    }
+
+    // 将客户端的投注换算为每线投注并按配置校验：min_bet / max_bet 限制每线投注
+    // （Megaway和簇赢奖即总投注），lines 只能在按线计的游戏中选择
+    pub fn validate_bet(&self, bet: &BetRequest) -> Result<ValidatedBet, BetError> {
+        let coins = match (bet.coin_value, bet.coins_per_line) {
+            (None, None) => None,
+            (Some(value), Some(count)) => {
+                Some(value.checked_mul(count).ok_or(BetError::BetOverflow)?)
             }
+            _ => return Err(BetError::MissingBet),
+        };
+        let bet_per_line = match (bet.bet_per_line, coins, bet.total_bet) {
+            (Some(bet_per_line), None, None) => bet_per_line,
+            (None, Some(bet_per_line), None) => bet_per_line,
+            (None, None, Some(total_bet)) if self.is_ways_game() => total_bet,
+            (None, None, Some(_)) => return Err(BetError::TotalBetNotSupported),
+            (None, None, None) => return Err(BetError::MissingBet),
+            _ => return Err(BetError::AmbiguousBet),
+        };
+        check_bet_limits(
+            bet_per_line as u64,
+            self.config.min_bet as u64,
+            self.config.max_bet as u64,
+        )?;
 
-            let mut line_symbols = Vec::new();
-            for &(reel_idx, row_idx) in &payline.pattern {
-                if reel_idx < grid.len() && row_idx < grid[reel_idx].len() {
-                    line_symbols.push(grid[reel_idx][row_idx].clone());
-                }
+        let lines = match bet.lines {
+            Some(_) if self.is_ways_game() => return Err(BetError::LinesNotSelectable),
+            Some(lines) if lines == 0 || lines > self.active_line_count() => {
+                return Err(BetError::InvalidLineCount {
+                    lines,
+                    max: self.active_line_count(),
+                })
             }
+            lines => lines,
+        };
+        let total_bet = self.with_active_lines(lines).total_bet(bet_per_line);
+        Ok(ValidatedBet {
+            bet_per_line,
+            lines,
+            total_bet,
+        })
+    }
 
-            if let Some(win) = self.check_line_win(&line_symbols) {
-                winning_lines.push(WinningLine {
-                    payline_number: payline.line_number,
-                    symbols: line_symbols,
-                    win_multiplier: win,
-                });
+    // 只保留前 lines 条激活的支付线，总投注、赢奖判断都只计这些支付线；未指定时不复制机器
+    pub fn with_active_lines(&self, lines: Option<u32>) -> std::borrow::Cow<'_, Self> {
+        match lines {
+            Some(lines) if lines < self.active_line_count() => {
+                let mut machine = self.clone();
+                for (kept, payline) in machine
+                    .paylines
+                    .iter_mut()
+                    .filter(|p| p.is_active)
+                    .enumerate()
+                {
+                    payline.is_active = (kept as u32) < lines;
+                }
+                std::borrow::Cow::Owned(machine)
             }
+            _ => std::borrow::Cow::Borrowed(self),
         }
-
-        winning_lines
     }
 
-    fn check_megaway_wins(&self, grid: &Vec<Vec<SlotSymbol>>) -> Vec<WinningLine> {
-        let mut winning_lines = Vec::new();
+    // 按配置的计算方式查找当前网格的赢奖
+    fn check_wins(&self, grid: &[Vec<SlotSymbol>]) -> Vec<WinningLine> {
+        if self.config.evaluation_mode == EvaluationMode::Cluster {
+            self.check_cluster_wins(grid)
+        } else if self.config.is_megaway {
+            self.check_megaway_wins(grid)
+        } else {
+            self.check_standard_wins(grid)
+        }
+    }
 
-        // Megaway使用从左到右的连续符号计算
-        // 计算所有可能的路径
-        let paths = self.calculate_megaway_paths(grid);
+    pub fn cascade_multiplier(&self, cascade: usize) -> u32 {
+        let ladder = &self.config.cascade_multipliers;
+        ladder
+            .get(cascade)
+            .or(ladder.last())
+            .map_or(1, |&multiplier| multiplier.max(1))
+    }
 
-        for (path_idx, path) in paths.iter().enumerate() {
-            let mut line_symbols = Vec::new();
-            for &(reel_idx, row_idx) in path {
-                line_symbols.push(grid[reel_idx][row_idx].clone());
+    // 消除赢奖位置的符号，同一卷轴上方的符号下落，顶部补充新符号：
+    // weighted模式按权重抽取，stepper模式继续读取停止位置之前的卷轴条
+    fn tumble<R: Rng + ?Sized>(
+        &self,
+        grid: &mut [Vec<SlotSymbol>],
+        wins: &[WinningLine],
+        stops: &mut [usize],
+        reel_strips: &[ReelStrip],
+        reel_samplers: &[ReelSampler],
+        rng: &mut R,
+    ) {
+        for (reel_idx, reel) in grid.iter_mut().enumerate() {
+            let removed: Vec<usize> = (0..reel.len())
+                .filter(|&row| {
+                    wins.iter()
+                        .any(|win| win.positions.contains(&(reel_idx, row)))
+                })
+                .collect();
+            if removed.is_empty() {
+                continue;
             }
+            let (Some(reel_strip), Some(sampler)) =
+                (reel_strips.get(reel_idx), reel_samplers.get(reel_idx))
+            else {
+                continue;
+            };
 
-            if let Some(win) = self.check_line_win(&line_symbols) {
-                winning_lines.push(WinningLine {
-                    payline_number: path_idx,
-                    symbols: line_symbols,
-                    win_multiplier: win,
-                });
-            }
+            let mut next = match self.config.reel_mode {
+                ReelMode::Weighted => self.spin_reel(reel_strip, sampler, removed.len(), rng),
+                ReelMode::Stepper => {
+                    let len = reel_strip.symbols.len();
+                    let stop = &mut stops[reel_idx];
+                    *stop = (*stop + len - removed.len() % len) % len;
+                    self.reel_window(reel_strip, *stop, removed.len())
+                }
+            };
+            next.extend(
+                reel.iter()
+                    .enumerate()
+                    .filter(|(row, _)| !removed.contains(row))
+                    .map(|(_, symbol)| symbol.clone()),
+            );
+            *reel = next;
         }
+    }
 
-        winning_lines
+    fn spin_reel<R: Rng + ?Sized>(
+        &self,
+        reel_strip: &ReelStrip,
+        sampler: &ReelSampler,
+        rows: usize,
+        rng: &mut R,
+    ) -> Vec<SlotSymbol> {
+        // 按累积权重随机选择符号
+        (0..rows)
+            .map(|_| reel_strip.symbols[sampler.sample(rng)].0.clone())
+            .collect()
     }
 
-    fn calculate_megaway_paths(&self, grid: &Vec<Vec<SlotSymbol>>) -> Vec<Vec<(usize, usize)>> {
-        let mut paths = Vec::new();
+    pub fn is_wild(&self, symbol: &SlotSymbol) -> bool {
+        symbol.symbol_type == SymbolType::Wild && self.config.wild_enabled
+    }
+
+    fn wild_feature(&self, symbol_id: i32) -> Option<&WildFeature> {
+        self.wild_features.iter().find(|w| w.symbol_id == symbol_id)
+    }
 
-        // 从第一个卷轴的每个符号开始
-        for start_row in 0..grid[0].len() {
-            self.find_paths_recursive(grid, 0, start_row, vec![(0, start_row)], &mut paths);
+    // Wild可以替代普通符号和其他Wild，不能替代Scatter、Bonus以及配置中排除的符号
+    pub fn substitutes(&self, wild: &SlotSymbol, target: &SlotSymbol) -> bool {
+        if !self.is_wild(wild)
+            || !matches!(target.symbol_type, SymbolType::Normal | SymbolType::Wild)
+        {
+            return false;
         }
+        self.wild_feature(wild.id)
+            .is_none_or(|w| !w.cannot_substitute.contains(&target.id))
+    }
 
-        paths
+    pub fn wild_multiplier(&self, symbol_id: i32) -> u32 {
+        self.wild_feature(symbol_id)
+            .map_or(1, |w| w.multiplier.max(1))
     }
 
-    #[allow(clippy::only_used_in_recursion)]
-    fn find_paths_recursive(
-        &self,
-        grid: &Vec<Vec<SlotSymbol>>,
-        reel_idx: usize,
-        _row_idx: usize,
-        current_path: Vec<(usize, usize)>,
-        paths: &mut Vec<Vec<(usize, usize)>>,
-    ) {
-        // 如果到达最后一个卷轴，保存路径
-        if reel_idx == grid.len() - 1 {
-            paths.push(current_path);
-            return;
+    // 路数计算中一个位置对基础符号的贡献：不匹配为0，相同符号为1，Wild为其倍数
+    pub fn ways_score(&self, symbol: &SlotSymbol, base_symbol: &SlotSymbol) -> u32 {
+        if symbol.id == base_symbol.id {
+            1
+        } else if self.substitutes(symbol, base_symbol) {
+            self.wild_multiplier(symbol.id)
+        } else {
+            0
         }
+    }
 
-        // 连接到下一个卷轴的所有符号
-        let next_reel_idx = reel_idx + 1;
-        if next_reel_idx < grid.len() {
-            for next_row in 0..grid[next_reel_idx].len() {
-                let mut new_path = current_path.clone();
-                new_path.push((next_reel_idx, next_row));
-                self.find_paths_recursive(grid, next_reel_idx, next_row, new_path, paths);
+    fn place_held_wilds(&self, grid: &mut [Vec<SlotSymbol>], held_wilds: &[HeldWild]) {
+        for held in held_wilds {
+            let Some(symbol) = self.symbols.iter().find(|s| s.id == held.symbol_id) else {
+                continue;
+            };
+            if let Some(cell) = grid
+                .get_mut(held.reel)
+                .and_then(|reel| reel.get_mut(held.row))
+            {
+                *cell = symbol.clone();
             }
         }
     }
 
-    fn check_line_win(&self, symbols: &[SlotSymbol]) -> Option<u32> {
-        if symbols.is_empty() {
-            return None;
-        }
-
-        let first_symbol = &symbols[0];
-        let mut consecutive_count = 1;
-        let mut has_wild = false;
-
-        // 检查从左到右的连续符号
-        for item in symbols.iter().skip(1) {
-            let current = &item;
+    // 扩展Wild覆盖所在的整个卷轴，返回被扩展的卷轴索引
+    pub fn expand_wilds(&self, grid: &mut [Vec<SlotSymbol>]) -> Vec<usize> {
+        let mut expanded_reels = Vec::new();
 
-            if current.symbol_type == SymbolType::Wild && self.config.wild_enabled {
-                has_wild = true;
-                consecutive_count += 1;
-            } else if current.name == first_symbol.name
-                || (first_symbol.symbol_type == SymbolType::Wild && self.config.wild_enabled)
-            {
-                consecutive_count += 1;
-            } else {
-                break;
+        for (reel_idx, reel) in grid.iter_mut().enumerate() {
+            let expanding = reel.iter().find(|symbol| {
+                self.is_wild(symbol) && self.wild_feature(symbol.id).is_some_and(|w| w.expanding)
+            });
+            if let Some(wild) = expanding.cloned() {
+                reel.fill(wild);
+                expanded_reels.push(reel_idx);
             }
         }
 
-        // 至少需要3个连续符号才算赢
-        if consecutive_count >= 3 {
-            // 获取赔付倍数
-            let base_symbol = if first_symbol.symbol_type == SymbolType::Wild {
-                // 如果第一个是Wild，找第一个非Wild符号
-                symbols.iter().find(|s| s.symbol_type != SymbolType::Wild)?
-            } else {
-                first_symbol
-            };
+        expanded_reels
+    }
+
+    // 粘性Wild保持原位置，移动Wild向左移动一个卷轴，移出第一个卷轴后消失
+    fn next_held_wilds(&self, grid: &[Vec<SlotSymbol>]) -> Vec<HeldWild> {
+        let mut held_wilds = Vec::new();
 
-            if let Some(&payout) = base_symbol.payouts.get(&consecutive_count) {
-                // 如果有Wild，可能增加赔付
-                let multiplier = if has_wild && !self.config.is_megaway {
-                    2
+        for (reel_idx, reel) in grid.iter().enumerate() {
+            for (row_idx, symbol) in reel.iter().enumerate() {
+                if !self.is_wild(symbol) {
+                    continue;
+                }
+                let Some(feature) = self.wild_feature(symbol.id) else {
+                    continue;
+                };
+                let reel = if feature.walking {
+                    match reel_idx.checked_sub(1) {
+                        Some(reel) => reel,
+                        None => continue,
+                    }
+                } else if feature.sticky {
+                    reel_idx
                 } else {
-                    1
+                    continue;
                 };
-                return Some(payout * multiplier);
+                held_wilds.push(HeldWild {
+                    symbol_id: symbol.id,
+                    reel,
+                    row: row_idx,
+                });
             }
         }
 
-        None
+        held_wilds
+    }
+
+    // 从停止位置开始按顺序读取可见窗口，超出卷轴条末尾时回到开头
+    fn reel_window(&self, reel_strip: &ReelStrip, stop: usize, rows: usize) -> Vec<SlotSymbol> {
+        (0..rows)
+            .map(|row| {
+                reel_strip.symbols[(stop + row) % reel_strip.symbols.len()]
+                    .0
+                    .clone()
+            })
+            .collect()
+    }
+
+    fn check_standard_wins(&self, grid: &[Vec<SlotSymbol>]) -> Vec<WinningLine> {
+        let mut winning_lines = Vec::new();
+
+        for payline in &self.paylines {
+            if !payline.is_active {
+                continue;
+            }
+
+            let mut line_symbols = Vec::new();
+            let mut cells = Vec::new();
+            for &(reel_idx, row_idx) in &payline.pattern {
+                if reel_idx < grid.len() && row_idx < grid[reel_idx].len() {
+                    line_symbols.push(grid[reel_idx][row_idx].clone());
+                    cells.push((reel_idx, row_idx));
+                }
+            }
+
+            if let Some((base_symbol, win)) = self.line_win(&line_symbols) {
+                // 赢奖位置为从左开始连续匹配的部分
+                let count = line_symbols
+                    .iter()
+                    .take_while(|s| s.id == base_symbol.id || self.substitutes(s, base_symbol))
+                    .count();
+                cells.truncate(count);
+                winning_lines.push(WinningLine {
+                    payline_number: payline.line_number,
+                    symbols: line_symbols,
+                    win_multiplier: win,
+                    ways: None,
+                    positions: cells,
+                });
+            }
+        }
+
+        winning_lines
+    }
+
+    // Megaway按路数计算：每种符号统计各卷轴上匹配的数量（含可替代的Wild），
+    // 从左到右相乘得到路数，每种符号只产生一个赢奖
+    fn check_megaway_wins(&self, grid: &[Vec<SlotSymbol>]) -> Vec<WinningLine> {
+        let mut winning_lines = Vec::new();
+
+        for base_symbol in self
+            .symbols
+            .iter()
+            .filter(|s| matches!(s.symbol_type, SymbolType::Normal | SymbolType::Wild))
+            .filter(|s| !s.payouts.is_empty())
+        {
+            let mut reel_count = 0;
+            let mut ways = 1u32;
+            let mut weighted_ways = 1u64;
+
+            for reel in grid {
+                let mut matches = 0;
+                let mut weighted = 0u64;
+                for symbol in reel {
+                    let score = self.ways_score(symbol, base_symbol);
+                    if score > 0 {
+                        matches += 1;
+                        weighted += score as u64;
+                    }
+                }
+                if matches == 0 {
+                    break;
+                }
+                reel_count += 1;
+                ways = ways.saturating_mul(matches);
+                weighted_ways = weighted_ways.saturating_mul(weighted);
+            }
+
+            // 至少需要3个连续卷轴才算赢
+            if reel_count < 3 {
+                continue;
+            }
+
+            if let Some(&payout) = base_symbol.payouts.get(&reel_count) {
+                // 前 reel_count 个卷轴上所有匹配的位置
+                let positions = grid[..reel_count]
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(reel_idx, reel)| {
+                        reel.iter()
+                            .enumerate()
+                            .filter(|(_, symbol)| self.ways_score(symbol, base_symbol) > 0)
+                            .map(move |(row_idx, _)| (reel_idx, row_idx))
+                    })
+                    .collect();
+                winning_lines.push(WinningLine {
+                    payline_number: 0,
+                    symbols: vec![base_symbol.clone(); reel_count],
+                    win_multiplier: capped((payout as u64).saturating_mul(weighted_ways)),
+                    ways: Some(ways),
+                    positions,
+                });
+            }
+        }
+
+        winning_lines
+    }
+
+    // 簇赢奖：每种普通符号从自身所在位置出发做洪水填充，上下左右相邻的相同符号和
+    // 可替代它的Wild组成一个簇。Wild可以同时加入不同符号的簇，但不能单独成簇。
+    // 赔付使用不超过簇大小的最大赔付档，簇中Wild的倍数相乘
+    fn check_cluster_wins(&self, grid: &[Vec<SlotSymbol>]) -> Vec<WinningLine> {
+        let mut winning_lines = Vec::new();
+
+        for base_symbol in self
+            .symbols
+            .iter()
+            .filter(|s| s.symbol_type == SymbolType::Normal)
+            .filter(|s| !s.payouts.is_empty())
+        {
+            // 每种符号单独标记，Wild因此可以加入其他符号的簇
+            let mut visited: Vec<Vec<bool>> =
+                grid.iter().map(|reel| vec![false; reel.len()]).collect();
+
+            for (reel_idx, reel) in grid.iter().enumerate() {
+                for (row_idx, symbol) in reel.iter().enumerate() {
+                    if symbol.id != base_symbol.id || visited[reel_idx][row_idx] {
+                        continue;
+                    }
+
+                    let mut positions = Vec::new();
+                    let mut multiplier = 1u32;
+                    let mut stack = vec![(reel_idx, row_idx)];
+                    visited[reel_idx][row_idx] = true;
+                    while let Some((reel, row)) = stack.pop() {
+                        positions.push((reel, row));
+                        let symbol = &grid[reel][row];
+                        if symbol.id != base_symbol.id {
+                            multiplier = multiplier.saturating_mul(self.wild_multiplier(symbol.id));
+                        }
+
+                        // Megaway的卷轴高度不同，只连接实际存在的位置
+                        let neighbours = [
+                            reel.checked_sub(1).map(|r| (r, row)),
+                            Some((reel + 1, row)),
+                            row.checked_sub(1).map(|r| (reel, r)),
+                            Some((reel, row + 1)),
+                        ];
+                        for (r, c) in neighbours.into_iter().flatten() {
+                            let Some(neighbour) = grid.get(r).and_then(|reel| reel.get(c)) else {
+                                continue;
+                            };
+                            if !visited[r][c] && self.ways_score(neighbour, base_symbol) > 0 {
+                                visited[r][c] = true;
+                                stack.push((r, c));
+                            }
+                        }
+                    }
+
+                    if let Some(payout) = Self::scatter_payout(base_symbol, positions.len()) {
+                        positions.sort_unstable();
+                        winning_lines.push(WinningLine {
+                            payline_number: 0,
+                            symbols: positions
+                                .iter()
+                                .map(|&(reel, row)| grid[reel][row].clone())
+                                .collect(),
+                            win_multiplier: payout.saturating_mul(multiplier),
+                            ways: None,
+                            positions,
+                        });
+                    }
+                }
+            }
+        }
+
+        winning_lines
     }
 
-    fn check_free_spins(&self, grid: &Vec<Vec<SlotSymbol>>) -> u32 {
-        let mut scatter_count = 0;
+    // 计算一条线的赢奖，返回基础符号和赔付倍数（含Wild倍数）
+    pub fn line_win<'a>(&self, symbols: &'a [SlotSymbol]) -> Option<(&'a SlotSymbol, u32)> {
+        // 基础符号为第一个非Wild的普通符号，全部是Wild时按Wild自身的赔付计算；
+        // Scatter、Bonus和钱币符号不参与连线，Scatter已在任意位置赔付，不能重复计算
+        let base_symbol = symbols
+            .iter()
+            .filter(|s| matches!(s.symbol_type, SymbolType::Normal | SymbolType::Wild))
+            .find(|s| !self.is_wild(s))
+            .or(symbols.first().filter(|s| self.is_wild(s)))?;
+
+        let mut consecutive_count = 0;
+        let mut multiplier = 1u32;
+
+        // 检查从左到右的连续符号
+        for symbol in symbols {
+            if symbol.id == base_symbol.id {
+                consecutive_count += 1;
+            } else if self.substitutes(symbol, base_symbol) {
+                consecutive_count += 1;
+                multiplier = multiplier.saturating_mul(self.wild_multiplier(symbol.id));
+            } else {
+                break;
+            }
+        }
+
+        // 至少需要3个连续符号才算赢
+        if consecutive_count < 3 {
+            return None;
+        }
 
+        base_symbol
+            .payouts
+            .get(&consecutive_count)
+            .map(|&payout| (base_symbol, payout.saturating_mul(multiplier)))
+    }
+
+    fn check_scatter_wins(&self, grid: &[Vec<SlotSymbol>], bet_per_line: u32) -> Vec<ScatterWin> {
+        let total_bet = self.total_bet(bet_per_line);
+        let mut scatter_wins = Vec::new();
+
+        // 每种Scatter符号分别计数，使用不超过该数量的最大赔付档
+        for symbol in self
+            .symbols
+            .iter()
+            .filter(|s| s.symbol_type == SymbolType::Scatter)
+        {
+            let positions: Vec<(usize, usize)> = grid
+                .iter()
+                .enumerate()
+                .flat_map(|(reel_idx, reel)| {
+                    reel.iter()
+                        .enumerate()
+                        .filter(|(_, s)| s.id == symbol.id)
+                        .map(move |(row_idx, _)| (reel_idx, row_idx))
+                })
+                .collect();
+
+            if let Some(payout) = Self::scatter_payout(symbol, positions.len()) {
+                scatter_wins.push(ScatterWin {
+                    symbol_id: symbol.id,
+                    count: positions.len(),
+                    positions,
+                    win_multiplier: payout,
+                    win_amount: capped((payout as u64).saturating_mul(total_bet)),
+                });
+            }
+        }
+
+        scatter_wins
+    }
+
+    // Scatter和簇赢奖的赔付使用不超过出现数量的最大赔付档
+    pub fn scatter_payout(symbol: &SlotSymbol, count: usize) -> Option<u32> {
+        symbol
+            .payouts
+            .iter()
+            .filter(|(&c, &payout)| c <= count && payout > 0)
+            .max_by_key(|(&c, _)| c)
+            .map(|(_, &payout)| payout)
+    }
+
+    fn check_free_spins(&self, grid: &[Vec<SlotSymbol>]) -> u32 {
         // 计算Scatter符号数量
-        for reel in grid {
-            for symbol in reel {
-                if symbol.symbol_type == SymbolType::Scatter {
-                    scatter_count += 1;
+        let scatter_count = grid
+            .iter()
+            .flatten()
+            .filter(|symbol| symbol.symbol_type == SymbolType::Scatter)
+            .count();
+
+        self.free_spins_for(scatter_count)
+    }
+
+    pub fn free_spins_for(&self, scatter_count: usize) -> u32 {
+        // 取满足数量要求的最高档触发
+        self.scatter_triggers
+            .iter()
+            .filter(|t| t.scatter_count <= scatter_count)
+            .max_by_key(|t| t.scatter_count)
+            .map_or(0, |t| t.free_spins)
+    }
+
+    fn check_bonus_trigger(&self, grid: &[Vec<SlotSymbol>]) -> bool {
+        let bonus_count = grid
+            .iter()
+            .flatten()
+            .filter(|symbol| symbol.symbol_type == SymbolType::Bonus)
+            .count();
+
+        self.bonus_triggered_by(bonus_count)
+    }
+
+    pub fn bonus_triggered_by(&self, bonus_count: usize) -> bool {
+        self.bonus_game
+            .as_ref()
+            .is_some_and(|game| game.trigger_count > 0 && bonus_count >= game.trigger_count)
+    }
+
+    fn draw_coins<R: Rng + ?Sized>(&self, grid: &[Vec<SlotSymbol>], rng: &mut R) -> Vec<Coin> {
+        let Some(hold_and_spin) = &self.hold_and_spin else {
+            return Vec::new();
+        };
+
+        let mut coins = Vec::new();
+        for (reel_idx, reel) in grid.iter().enumerate() {
+            for (row_idx, symbol) in reel.iter().enumerate() {
+                if symbol.symbol_type == SymbolType::Money {
+                    coins.push(hold_and_spin.draw_coin(reel_idx, row_idx, rng));
                 }
             }
         }
+        coins
+    }
+
+    pub fn hold_and_spin_triggered_by(&self, coin_count: usize) -> bool {
+        self.hold_and_spin
+            .as_ref()
+            .is_some_and(|h| h.trigger_count > 0 && coin_count >= h.trigger_count)
+    }
 
-        // 通常3个或更多Scatter触发免费旋转
-        match scatter_count {
-            3 => 10,
-            4 => 15,
-            5 => 20,
-            6.. => 25,
-            _ => 0,
+    // 触发旋转的钱币全部锁定，开始Hold and Spin
+    pub fn start_hold_and_spin(&self, result: &UniversalSpinResult) -> Option<HoldAndSpinState> {
+        let hold_and_spin = self.hold_and_spin.as_ref()?;
+        if !result.hold_and_spin_triggered {
+            return None;
         }
+        Some(HoldAndSpinState::new(
+            result.grid.iter().map(Vec::len).collect(),
+            result.coins.clone(),
+            hold_and_spin.respins,
+        ))
     }
 
-    fn calculate_total_win(&self, winning_lines: &[WinningLine], bet_per_line: u32) -> u32 {
-        let mut total = 0;
+    pub fn respin_seeded(&self, state: &mut HoldAndSpinState, seed: u64) -> Vec<Coin> {
+        self.respin(state, &mut ChaCha20Rng::seed_from_u64(seed))
+    }
 
-        if self.config.is_megaway {
-            // Megaway模式：所有赢线的赢金相加
-            for line in winning_lines {
-                total += line.win_multiplier * bet_per_line;
+    // 进行一次重转：未锁定的位置按各卷轴的权重独立抽取符号，出现的钱币锁定并重置剩余次数，
+    // 没有新钱币时剩余次数减一，网格填满时立即结束。返回本次新出现的钱币
+    pub fn respin<R: Rng + ?Sized>(&self, state: &mut HoldAndSpinState, rng: &mut R) -> Vec<Coin> {
+        let Some(hold_and_spin) = &self.hold_and_spin else {
+            return Vec::new();
+        };
+        if state.is_complete() {
+            return Vec::new();
+        }
+
+        let mut landed = Vec::new();
+        for (reel_idx, &rows) in state.reel_rows.iter().enumerate() {
+            let (Some(reel_strip), Some(sampler)) = (
+                self.reel_strips.get(reel_idx),
+                self.reel_samplers.get(reel_idx),
+            ) else {
+                continue;
+            };
+            for row_idx in 0..rows {
+                let locked = state
+                    .coins
+                    .iter()
+                    .any(|coin| coin.reel == reel_idx && coin.row == row_idx);
+                if locked {
+                    continue;
+                }
+                let symbol = &reel_strip.symbols[sampler.sample(rng)].0;
+                if symbol.symbol_type == SymbolType::Money {
+                    landed.push(hold_and_spin.draw_coin(reel_idx, row_idx, rng));
+                }
             }
+        }
+
+        state.respins_played += 1;
+        state.coins.extend(landed.iter().cloned());
+        state.respins_remaining = if state.is_full() {
+            0
+        } else if landed.is_empty() {
+            state.respins_remaining - 1
         } else {
-            // 标准模式：每条支付线独立计算
-            for line in winning_lines {
-                total += line.win_multiplier * bet_per_line;
-            }
+            hold_and_spin.respins
+        };
+        landed
+    }
+
+    // 从触发旋转开始完整进行Hold and Spin，第n次重转使用 respin_rng(seed, n)，用于重放
+    pub fn play_hold_and_spin(
+        &self,
+        result: &UniversalSpinResult,
+        seed: u64,
+    ) -> Option<HoldAndSpinState> {
+        self.play_hold_and_spin_with(result, |respin| HoldAndSpinState::respin_rng(seed, respin))
+    }
+
+    // 同上，第n次重转的随机数由 respin_rng(n) 提供（可验证公平旋转使用 HMAC 随机数流）
+    pub fn play_hold_and_spin_with<R: Rng>(
+        &self,
+        result: &UniversalSpinResult,
+        mut respin_rng: impl FnMut(u32) -> R,
+    ) -> Option<HoldAndSpinState> {
+        let mut state = self.start_hold_and_spin(result)?;
+        while !state.is_complete() {
+            let mut rng = respin_rng(state.respins_played + 1);
+            self.respin(&mut state, &mut rng);
         }
+        Some(state)
+    }
 
-        total
+    // 每条支付线（Megaway为每种符号的路数赢奖）的赢金相加
+    fn calculate_total_win(&self, winning_lines: &[WinningLine], bet_per_line: u32) -> u32 {
+        let total = winning_lines.iter().fold(0u64, |total, line| {
+            total.saturating_add(line.win_multiplier as u64 * bet_per_line as u64)
+        });
+        capped(total)
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniversalSpinResult {
     pub grid: Vec<Vec<SlotSymbol>>,
+    #[serde(default)]
+    pub cascades: Vec<CascadeStep>, // 连锁消除的每一步，未开启时为空
     pub megaway_rows: Option<Vec<usize>>,
     pub winning_lines: Vec<WinningLine>,
+    #[serde(default)]
+    pub scatter_wins: Vec<ScatterWin>,
     pub total_win: u32,
     pub free_spins: u32,
+    #[serde(default)]
+    pub bonus_triggered: bool,
+    #[serde(default)]
+    pub coins: Vec<Coin>, // 钱币符号的位置和金额
+    #[serde(default)]
+    pub hold_and_spin_triggered: bool,
+    #[serde(default)]
+    pub expanded_reels: Vec<usize>,
+    #[serde(default)]
+    pub held_wilds: Vec<HeldWild>,
+    #[serde(default)]
+    pub reel_stops: Option<Vec<usize>>, // stepper模式下每个卷轴的停止位置
     pub bet_per_line: u32,
+    #[serde(default)]
+    pub bet_mode: BetMode,
+    #[serde(default)]
+    pub reel_set: Option<String>, // 本次旋转使用的卷轴组，使用基础卷轴时为空
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+// Scatter赔付：与位置无关，赔付倍数乘以总投注
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScatterWin {
+    pub symbol_id: i32,
+    pub count: usize,
+    pub positions: Vec<(usize, usize)>,
+    pub win_multiplier: u32,
+    pub win_amount: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -339,6 +1509,19 @@ pub struct WinningLine {
     pub payline_number: usize,
     pub symbols: Vec<SlotSymbol>,
     pub win_multiplier: u32,
+    #[serde(default)]
+    pub ways: Option<u32>, // Megaway赢奖的路数
+    #[serde(default)]
+    pub positions: Vec<(usize, usize)>, // 参与赢奖的位置 (卷轴索引, 行索引)
+}
+
+// 连锁消除的一步：在 grid 上计算出的赢奖，最后一步没有赢奖
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CascadeStep {
+    pub grid: Vec<Vec<SlotSymbol>>,
+    pub winning_lines: Vec<WinningLine>, // win_multiplier 已包含本步的连锁倍数
+    pub multiplier: u32,
+    pub win: u32,
 }
 
 // 用于从数据库构建SlotMachine的辅助结构
@@ -348,15 +1531,31 @@ pub struct SlotConfigBuilder {
     pub symbols: Vec<SlotSymbol>,
     pub reel_compositions: HashMap<usize, Vec<(i32, u32)>>, // reel_number -> [(symbol_id, weight)]
     pub paylines: Vec<Payline>,
+    #[serde(default)]
+    pub scatter_triggers: Vec<ScatterTrigger>, // 为空时使用默认触发表
+    #[serde(default)]
+    pub bonus_game: Option<BonusGameConfig>,
+    #[serde(default)]
+    pub wild_features: Vec<WildFeature>,
+    #[serde(default)]
+    pub hold_and_spin: Option<HoldAndSpinConfig>,
+    #[serde(default)]
+    pub reel_sets: HashMap<String, HashMap<usize, Vec<(i32, u32)>>>, // 卷轴组名称 -> 卷轴组成
+    #[serde(default)]
+    pub base_reel_set_weights: HashMap<String, u32>, // 基础游戏按权重选择的卷轴组，base 为基础卷轴
+    #[serde(default)]
+    pub ante_bet: Option<AnteBetConfig>,
+    #[serde(default)]
+    pub bonus_buy: Option<BonusBuyConfig>,
 }
 
 impl SlotConfigBuilder {
-    pub fn build(self) -> UniversalSlotMachine {
+    // 按卷轴组成构建卷轴条
+    fn reel_strips(&self, reel_compositions: &HashMap<usize, Vec<(i32, u32)>>) -> Vec<ReelStrip> {
         let mut reel_strips = Vec::new();
 
-        // 构建卷轴条
         for i in 0..self.config.reels {
-            if let Some(compositions) = self.reel_compositions.get(&i) {
+            if let Some(compositions) = reel_compositions.get(&i) {
                 let mut symbols_with_weights = Vec::new();
 
                 for (symbol_id, weight) in compositions {
@@ -371,8 +1570,35 @@ impl SlotConfigBuilder {
                 });
             }
         }
+        reel_strips
+    }
 
-        UniversalSlotMachine::new(self.config, self.symbols, reel_strips, self.paylines)
+    pub fn build(self) -> UniversalSlotMachine {
+        let reel_strips = self.reel_strips(&self.reel_compositions);
+        let reel_sets = self
+            .reel_sets
+            .iter()
+            .map(|(name, compositions)| {
+                (name.clone(), ReelSet::new(self.reel_strips(compositions)))
+            })
+            .collect();
+
+        let mut machine =
+            UniversalSlotMachine::new(self.config, self.symbols, reel_strips, self.paylines);
+        machine.reel_sets = reel_sets;
+        // 排序保证相同种子抽到相同的卷轴组
+        let mut base_reel_set_weights: Vec<_> = self.base_reel_set_weights.into_iter().collect();
+        base_reel_set_weights.sort();
+        machine.base_reel_set_weights = base_reel_set_weights;
+        machine.ante_bet = self.ante_bet;
+        machine.bonus_buy = self.bonus_buy;
+        if !self.scatter_triggers.is_empty() {
+            machine.scatter_triggers = self.scatter_triggers;
+        }
+        machine.bonus_game = self.bonus_game;
+        machine.wild_features = self.wild_features;
+        machine.hold_and_spin = self.hold_and_spin;
+        machine
     }
 }
 
@@ -395,6 +1621,11 @@ mod tests {
             max_bet: 100,
             wild_enabled: true,
             free_spins_enabled: true,
+            free_spins_multiplier: 1,
+            reel_mode: ReelMode::Weighted,
+            evaluation_mode: EvaluationMode::Lines,
+            cascading: false,
+            cascade_multipliers: Vec::new(),
             rtp_percentage: 96.0,
         };
 
@@ -429,4 +1660,972 @@ mod tests {
         assert_eq!(machine.config.reels, 5);
         assert_eq!(machine.config.rows, 3);
     }
+
+    fn test_config(is_megaway: bool) -> SlotConfig {
+        SlotConfig {
+            id: 1,
+            name: "Test Slot".to_string(),
+            rows: 3,
+            reels: 5,
+            is_megaway,
+            min_megaway_rows: 2,
+            max_megaway_rows: 7,
+            default_bet: 1,
+            min_bet: 1,
+            max_bet: 100,
+            wild_enabled: true,
+            free_spins_enabled: true,
+            free_spins_multiplier: 1,
+            reel_mode: ReelMode::Weighted,
+            evaluation_mode: EvaluationMode::Lines,
+            cascading: false,
+            cascade_multipliers: Vec::new(),
+            rtp_percentage: 96.0,
+        }
+    }
+
+    fn test_symbol(
+        id: i32,
+        name: &str,
+        symbol_type: SymbolType,
+        payouts: &[(usize, u32)],
+    ) -> SlotSymbol {
+        SlotSymbol {
+            id,
+            name: name.to_string(),
+            symbol_type,
+            value: id as u32,
+            image_url: None,
+            payouts: payouts.iter().copied().collect(),
+        }
+    }
+
+    fn test_machine(is_megaway: bool) -> UniversalSlotMachine {
+        let symbols = vec![
+            test_symbol(1, "Cherry", SymbolType::Normal, &[(3, 5), (4, 10), (5, 20)]),
+            test_symbol(2, "Bell", SymbolType::Normal, &[(3, 10), (4, 20), (5, 50)]),
+            test_symbol(3, "Wild", SymbolType::Wild, &[]),
+            test_symbol(4, "Scatter", SymbolType::Scatter, &[]),
+        ];
+        let reel_strips = (0..5)
+            .map(|reel_number| ReelStrip {
+                reel_number,
+                symbols: vec![
+                    (symbols[0].clone(), 5),
+                    (symbols[1].clone(), 3),
+                    (symbols[2].clone(), 1),
+                    (symbols[3].clone(), 1),
+                ],
+            })
+            .collect();
+        let paylines = (0..3)
+            .map(|row| Payline {
+                line_number: row + 1,
+                pattern: (0..5).map(|reel| (reel, row)).collect(),
+                is_active: true,
+            })
+            .collect();
+
+        UniversalSlotMachine::new(test_config(is_megaway), symbols, reel_strips, paylines)
+    }
+
+    fn grid_of(machine: &UniversalSlotMachine, names: &[&[&str]]) -> Vec<Vec<SlotSymbol>> {
+        names
+            .iter()
+            .map(|reel| {
+                reel.iter()
+                    .map(|name| {
+                        machine
+                            .symbols
+                            .iter()
+                            .find(|s| s.name == *name)
+                            .unwrap()
+                            .clone()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scatter_pays_anywhere_and_uses_trigger_table() {
+        let mut machine = test_machine(false);
+        machine.symbols[3].payouts = [(3, 2), (5, 10)].into_iter().collect();
+        machine.scatter_triggers = vec![ScatterTrigger {
+            scatter_count: 3,
+            free_spins: 8,
+        }];
+
+        let grid = grid_of(
+            &machine,
+            &[
+                &["Scatter", "Cherry", "Bell"],
+                &["Cherry", "Bell", "Cherry"],
+                &["Bell", "Cherry", "Scatter"],
+                &["Cherry", "Scatter", "Bell"],
+                &["Scatter", "Bell", "Cherry"],
+            ],
+        );
+
+        // 4个Scatter：使用3个的赔付档，3条支付线 x 投注2 = 总投注6
+        let wins = machine.check_scatter_wins(&grid, 2);
+        assert_eq!(wins.len(), 1);
+        assert_eq!(wins[0].count, 4);
+        assert_eq!(wins[0].win_amount, 12);
+        assert_eq!(machine.check_free_spins(&grid), 8);
+    }
+
+    #[test]
+    fn test_scatters_on_a_payline_pay_only_as_scatters() {
+        let mut machine = test_machine(false);
+        machine.symbols[3].payouts = [(3, 2), (5, 10)].into_iter().collect();
+
+        // 第2条支付线上5个Scatter，只按Scatter赔付：5个的赔付档 x 总投注3
+        let grid = grid_of(
+            &machine,
+            &[
+                &["Cherry", "Scatter", "Bell"],
+                &["Bell", "Scatter", "Cherry"],
+                &["Cherry", "Scatter", "Bell"],
+                &["Bell", "Scatter", "Cherry"],
+                &["Cherry", "Scatter", "Bell"],
+            ],
+        );
+        let lines = machine.check_wins(&grid);
+        assert!(lines.is_empty());
+        let scatters = machine.check_scatter_wins(&grid, 1);
+        let total = machine.calculate_total_win(&lines, 1) as u64
+            + scatters.iter().map(|w| w.win_amount as u64).sum::<u64>();
+        assert_eq!(total, 30);
+    }
+
+    #[test]
+    fn test_cluster_wins_flood_fill_with_shared_wilds() {
+        let mut machine = test_machine(false);
+        machine.config.evaluation_mode = EvaluationMode::Cluster;
+        machine.symbols[0].payouts = [(5, 2), (7, 5)].into_iter().collect();
+        machine.symbols[1].payouts = [(5, 4)].into_iter().collect();
+        machine.wild_features = vec![WildFeature {
+            symbol_id: 3,
+            multiplier: 2,
+            cannot_substitute: Vec::new(),
+            expanding: false,
+            sticky: false,
+            walking: false,
+        }];
+
+        let grid = grid_of(
+            &machine,
+            &[
+                &["Cherry", "Cherry", "Bell"],
+                &["Cherry", "Wild", "Bell"],
+                &["Cherry", "Cherry", "Bell"],
+                &["Cherry", "Scatter", "Bell"],
+                &["Scatter", "Cherry", "Scatter"],
+            ],
+        );
+        let wins = machine.check_cluster_wins(&grid);
+
+        // Cherry簇：6个Cherry + Wild = 7，使用7的赔付档；最后一个卷轴的Cherry不相连
+        let cherry = wins
+            .iter()
+            .find(|w| w.symbols.iter().any(|s| s.id == 1))
+            .unwrap();
+        assert_eq!(cherry.positions.len(), 7);
+        assert!(cherry.positions.contains(&(1, 1)));
+        assert!(!cherry.positions.contains(&(4, 1)));
+        assert_eq!(cherry.win_multiplier, 10);
+
+        // Bell簇：4个Bell + 同一个Wild = 5
+        let bell = wins
+            .iter()
+            .find(|w| w.symbols.iter().any(|s| s.id == 2))
+            .unwrap();
+        assert_eq!(bell.positions, vec![(0, 2), (1, 1), (1, 2), (2, 2), (3, 2)]);
+        assert_eq!(bell.win_multiplier, 8);
+        assert_eq!(wins.len(), 2);
+
+        // 簇赢奖只收取一次投注
+        assert_eq!(machine.total_bet(2), 2);
+    }
+
+    #[test]
+    fn test_cascade_removes_wins_and_refills_from_strip() {
+        let mut machine = test_machine(false);
+        machine.config.reel_mode = ReelMode::Stepper;
+        machine.config.cascading = true;
+        machine.config.cascade_multipliers = vec![1, 2, 3];
+
+        let mut grid = grid_of(
+            &machine,
+            &[
+                &["Bell", "Cherry", "Bell"],
+                &["Bell", "Cherry", "Scatter"],
+                &["Scatter", "Cherry", "Bell"],
+                &["Scatter", "Bell", "Bell"],
+                &["Bell", "Bell", "Scatter"],
+            ],
+        );
+        let wins = machine.check_wins(&grid);
+        assert_eq!(wins.len(), 1);
+        assert_eq!(wins[0].positions, vec![(0, 1), (1, 1), (2, 1)]);
+
+        // 上方符号下落，顶部补充停止位置之前的卷轴条符号（Cherry, Bell, Wild, Scatter）
+        let mut stops = vec![0; 5];
+        let (reel_strips, reel_samplers) = machine.reels_for(None);
+        machine.tumble(
+            &mut grid,
+            &wins,
+            &mut stops,
+            reel_strips,
+            reel_samplers,
+            &mut ChaCha20Rng::seed_from_u64(1),
+        );
+        let names = |reel: &[SlotSymbol]| reel.iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&grid[0]), ["Scatter", "Bell", "Bell"]);
+        assert_eq!(names(&grid[2]), ["Scatter", "Scatter", "Bell"]);
+        assert_eq!(names(&grid[3]), ["Scatter", "Bell", "Bell"]);
+        assert_eq!(stops, [3, 3, 3, 0, 0]);
+
+        // 每一步的赢金之和即为总赢金，最后一步没有赢奖
+        let mut chained = false;
+        for seed in 0..200 {
+            let result = machine.spin_seeded(1, seed);
+            let last = result.cascades.last().unwrap();
+            assert!(last.winning_lines.is_empty());
+            assert_eq!(
+                serde_json::to_value(&result.cascades[0].grid).unwrap(),
+                serde_json::to_value(&result.grid).unwrap()
+            );
+            for (step, cascade) in result.cascades.iter().enumerate() {
+                assert_eq!(cascade.multiplier, [1, 2, 3][step.min(2)]);
+            }
+            let scatter: u32 = result.scatter_wins.iter().map(|w| w.win_amount).sum();
+            let cascaded: u32 = result.cascades.iter().map(|c| c.win).sum();
+            assert_eq!(result.total_win, cascaded + scatter);
+            chained |= result.cascades.len() > 2;
+        }
+        assert!(chained);
+    }
+
+    #[test]
+    fn test_cascade_cap_still_pays_the_last_step() {
+        let mut config = test_config(false);
+        config.cascading = true;
+        let cherry = test_symbol(1, "Cherry", SymbolType::Normal, &[(3, 5), (4, 10), (5, 20)]);
+        let reel_strips = (0..5)
+            .map(|reel_number| ReelStrip {
+                reel_number,
+                symbols: vec![(cherry.clone(), 1)],
+            })
+            .collect();
+        let paylines = vec![Payline {
+            line_number: 1,
+            pattern: (0..5).map(|reel| (reel, 1)).collect(),
+            is_active: true,
+        }];
+        let machine = UniversalSlotMachine::new(config, vec![cherry], reel_strips, paylines);
+
+        // 补充的符号永远是Cherry，每一步都赢，直到达到上限
+        let result = machine.spin_seeded(1, 0);
+        assert_eq!(result.cascades.len(), MAX_CASCADES);
+        assert_eq!(result.cascades.last().unwrap().win, 20);
+        assert_eq!(result.winning_lines.len(), MAX_CASCADES);
+        let cascaded: u32 = result.cascades.iter().map(|c| c.win).sum();
+        assert_eq!(result.total_win, cascaded);
+    }
+
+    #[test]
+    fn test_hold_and_spin_locks_coins_and_resets_respins() {
+        let mut machine = test_machine(false);
+        machine.symbols[3].symbol_type = SymbolType::Money;
+        for strip in &mut machine.reel_strips {
+            strip.symbols[3].0.symbol_type = SymbolType::Money;
+        }
+        machine.hold_and_spin = Some(HoldAndSpinConfig {
+            trigger_count: 6,
+            respins: 3,
+            values: vec![
+                MoneyValue {
+                    multiplier: 2,
+                    jackpot: None,
+                    weight: 3,
+                },
+                MoneyValue {
+                    multiplier: 500,
+                    jackpot: Some(JackpotTier::Grand),
+                    weight: 1,
+                },
+            ],
+        });
+
+        let grid = grid_of(
+            &machine,
+            &[
+                &["Scatter", "Cherry", "Scatter"],
+                &["Cherry", "Scatter", "Cherry"],
+                &["Scatter", "Cherry", "Bell"],
+                &["Bell", "Scatter", "Bell"],
+                &["Scatter", "Bell", "Cherry"],
+            ],
+        );
+        let coins = machine.draw_coins(&grid, &mut ChaCha20Rng::seed_from_u64(7));
+        assert_eq!(coins.len(), 6);
+        assert!(machine.hold_and_spin_triggered_by(coins.len()));
+        assert!(!machine.hold_and_spin_triggered_by(5));
+        for coin in &coins {
+            assert_eq!(grid[coin.reel][coin.row].symbol_type, SymbolType::Money);
+            assert_eq!(coin.jackpot.is_some(), coin.multiplier == 500);
+        }
+
+        // 出现新钱币时重置为3次，否则减一，钱币只会增加且不会覆盖已锁定的位置
+        let mut state = HoldAndSpinState::new(vec![3; 5], coins.clone(), 3);
+        let mut seed = 0;
+        while !state.is_complete() {
+            let before = state.clone();
+            let landed = machine.respin_seeded(&mut state, seed);
+            seed += 1;
+            assert_eq!(state.coins.len(), before.coins.len() + landed.len());
+            assert!(landed.iter().all(|c| !before
+                .coins
+                .iter()
+                .any(|l| (l.reel, l.row) == (c.reel, c.row))));
+            let expected = match (state.is_full(), landed.is_empty()) {
+                (true, _) => 0,
+                (false, true) => before.respins_remaining - 1,
+                (false, false) => 3,
+            };
+            assert_eq!(state.respins_remaining, expected);
+        }
+        assert_eq!(
+            state.total_multiplier(),
+            state.coins.iter().map(|c| c.multiplier as u64).sum::<u64>()
+        );
+
+        // 填满网格立即结束
+        let full = HoldAndSpinState::new(vec![2, 1], coins[..3].to_vec(), 3);
+        assert!(full.is_complete());
+    }
+
+    #[test]
+    fn test_seeded_spin_is_reproducible() {
+        for is_megaway in [false, true] {
+            let machine = test_machine(is_megaway);
+            let first = machine.spin_seeded(2, 12345);
+            let second = machine.spin_seeded(2, 12345);
+
+            assert_eq!(first.seed, Some(12345));
+            assert_eq!(
+                serde_json::to_value(&first).unwrap(),
+                serde_json::to_value(&second).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_free_spins_only_retrigger_free_spins() {
+        // 所有位置都是钱币：基础游戏必定触发Hold and Spin，免费旋转不触发
+        let mut machine = test_machine(false);
+        for strip in &mut machine.reel_strips {
+            for (symbol, _) in &mut strip.symbols {
+                symbol.symbol_type = SymbolType::Money;
+            }
+        }
+        machine.hold_and_spin = Some(HoldAndSpinConfig {
+            trigger_count: 6,
+            respins: 3,
+            values: vec![MoneyValue {
+                multiplier: 1,
+                jackpot: None,
+                weight: 1,
+            }],
+        });
+        assert!(machine.spin_seeded(1, 5).hold_and_spin_triggered);
+        assert!(!machine.spin_free_seeded(1, 5, &[]).hold_and_spin_triggered);
+
+        // 所有位置都是Bonus：同样只有基础游戏触发选宝
+        for strip in &mut machine.reel_strips {
+            for (symbol, _) in &mut strip.symbols {
+                symbol.symbol_type = SymbolType::Bonus;
+            }
+        }
+        machine.bonus_game = Some(BonusGameConfig {
+            trigger_count: 3,
+            picks: 1,
+            board_size: 3,
+            prizes: vec![BonusPrize {
+                multiplier: 1,
+                weight: 1,
+            }],
+        });
+        assert!(machine.spin_seeded(1, 5).bonus_triggered);
+        assert!(!machine.spin_free_seeded(1, 5, &[]).bonus_triggered);
+    }
+
+    #[test]
+    fn test_bonus_trigger_and_seeded_board() {
+        let mut machine = test_machine(false);
+        machine.symbols[3].symbol_type = SymbolType::Bonus;
+        machine.bonus_game = Some(BonusGameConfig {
+            trigger_count: 3,
+            picks: 3,
+            board_size: 12,
+            prizes: vec![
+                BonusPrize {
+                    multiplier: 2,
+                    weight: 5,
+                },
+                BonusPrize {
+                    multiplier: 50,
+                    weight: 1,
+                },
+            ],
+        });
+
+        let grid = grid_of(
+            &machine,
+            &[
+                &["Scatter", "Cherry", "Bell"],
+                &["Cherry", "Scatter", "Cherry"],
+                &["Bell", "Cherry", "Cherry"],
+                &["Cherry", "Bell", "Scatter"],
+                &["Bell", "Bell", "Cherry"],
+            ],
+        );
+        assert!(machine.check_bonus_trigger(&grid));
+        assert!(!machine.check_bonus_trigger(&grid[..2]));
+
+        let bonus_game = machine.bonus_game.as_ref().unwrap();
+        let board = bonus_game.generate_board(42);
+        assert_eq!(board.len(), 12);
+        assert!(board.iter().all(|m| *m == 2 || *m == 50));
+        assert_eq!(board, bonus_game.generate_board(42));
+    }
+
+    #[test]
+    fn test_bet_modes_use_their_reel_sets_and_prices() {
+        let mut machine = test_machine(false);
+        let scatter = machine.symbols[3].clone();
+        machine.reel_sets.insert(
+            ANTE_REEL_SET.to_string(),
+            ReelSet::new(
+                (0..5)
+                    .map(|reel_number| ReelStrip {
+                        reel_number,
+                        symbols: vec![(scatter.clone(), 1)],
+                    })
+                    .collect(),
+            ),
+        );
+        machine.ante_bet = Some(AnteBetConfig { cost_percent: 25 });
+        machine.bonus_buy = Some(BonusBuyConfig {
+            cost_multiplier: 100,
+            free_spins: 8,
+        });
+
+        // 3条支付线，加注多付25%并向上取整
+        assert_eq!(machine.bet_cost(1, BetMode::Standard), Some(3));
+        assert_eq!(machine.bet_cost(1, BetMode::Ante), Some(4));
+        assert_eq!(machine.bet_cost(2, BetMode::BonusBuy), Some(600));
+
+        let ante = machine.spin_seeded_mode(1, BetMode::Ante, 7);
+        assert!(ante.grid.iter().flatten().all(|s| s.id == scatter.id));
+        assert_eq!(ante.free_spins, 25);
+        assert_eq!(ante.bet_mode, BetMode::Ante);
+
+        // 没有bonus_buy卷轴组时使用基础卷轴，免费旋转次数不少于购买的次数
+        for seed in 0..50 {
+            let bought = machine.spin_seeded_mode(1, BetMode::BonusBuy, seed);
+            assert!(bought.free_spins >= 8);
+            let standard = machine.spin_seeded(1, seed);
+            assert_eq!(bought.grid.len(), standard.grid.len());
+            assert_eq!(
+                bought
+                    .grid
+                    .iter()
+                    .flatten()
+                    .map(|s| s.id)
+                    .collect::<Vec<_>>(),
+                standard
+                    .grid
+                    .iter()
+                    .flatten()
+                    .map(|s| s.id)
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        machine.ante_bet = None;
+        assert_eq!(machine.bet_cost(1, BetMode::Ante), None);
+    }
+
+    #[test]
+    fn test_bet_validation_and_line_selection() {
+        let machine = test_machine(false);
+        let bet = |bet_per_line, coin_value, coins_per_line, total_bet, lines| BetRequest {
+            bet_per_line,
+            coin_value,
+            coins_per_line,
+            total_bet,
+            lines,
+        };
+
+        // 硬币面值 × 每线硬币数 × 激活的支付线数
+        let validated = machine
+            .validate_bet(&bet(None, Some(5), Some(2), None, Some(2)))
+            .unwrap();
+        assert_eq!(validated.bet_per_line, 10);
+        assert_eq!(validated.total_bet, 20);
+        assert_eq!(
+            machine.validate_bet(&bet(Some(4), None, None, None, None)),
+            Ok(ValidatedBet {
+                bet_per_line: 4,
+                lines: None,
+                total_bet: 12,
+            })
+        );
+
+        let error = |request: BetRequest| machine.validate_bet(&request).unwrap_err().code();
+        assert_eq!(error(bet(None, None, None, None, None)), "missing_bet");
+        assert_eq!(error(bet(None, Some(5), None, None, None)), "missing_bet");
+        assert_eq!(
+            error(bet(Some(1), Some(1), Some(1), None, None)),
+            "ambiguous_bet"
+        );
+        assert_eq!(error(bet(Some(0), None, None, None, None)), "zero_bet");
+        assert_eq!(
+            error(bet(Some(101), None, None, None, None)),
+            "bet_above_maximum"
+        );
+        assert_eq!(
+            error(bet(None, Some(u32::MAX), Some(2), None, None)),
+            "bet_overflow"
+        );
+        assert_eq!(
+            error(bet(None, None, None, Some(3), None)),
+            "total_bet_not_supported"
+        );
+        assert_eq!(
+            error(bet(Some(1), None, None, None, Some(0))),
+            "invalid_line_count"
+        );
+        assert_eq!(
+            error(bet(Some(1), None, None, None, Some(4))),
+            "invalid_line_count"
+        );
+
+        let mut below_minimum = machine.clone();
+        below_minimum.config.min_bet = 5;
+        assert_eq!(
+            below_minimum.validate_bet(&bet(Some(4), None, None, None, None)),
+            Err(BetError::BelowMinimum { bet: 4, min: 5 })
+        );
+
+        // 不按线计的游戏使用总投注，不能选择支付线
+        let megaway = test_machine(true);
+        assert_eq!(
+            megaway
+                .validate_bet(&bet(None, None, None, Some(50), None))
+                .unwrap()
+                .total_bet,
+            50
+        );
+        assert_eq!(
+            megaway
+                .validate_bet(&bet(Some(1), None, None, None, Some(1)))
+                .unwrap_err()
+                .code(),
+            "lines_not_selectable"
+        );
+
+        // 只选择1条支付线时，其余支付线上的赢奖不计
+        let grid = grid_of(
+            &machine,
+            &[&["Cherry", "Cherry", "Cherry"]; 5].map(|reel| reel.as_slice()),
+        );
+        assert_eq!(machine.check_wins(&grid).len(), 3);
+        let one_line = machine.with_active_lines(Some(1));
+        assert_eq!(one_line.active_line_count(), 1);
+        assert_eq!(one_line.total_bet(2), 2);
+        let wins = one_line.check_wins(&grid);
+        assert_eq!(wins.len(), 1);
+        assert_eq!(wins[0].payline_number, 1);
+        assert!(matches!(
+            machine.with_active_lines(Some(3)),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_base_game_draws_weighted_reel_sets_and_free_spins_use_their_own() {
+        let mut machine = test_machine(false);
+        let uniform = |symbol: &SlotSymbol| {
+            ReelSet::new(
+                (0..5)
+                    .map(|reel_number| ReelStrip {
+                        reel_number,
+                        symbols: vec![(symbol.clone(), 1)],
+                    })
+                    .collect(),
+            )
+        };
+        let scatter = machine.symbols[3].clone();
+        let cherry = machine.symbols[0].clone();
+        machine
+            .reel_sets
+            .insert("scatters".to_string(), uniform(&scatter));
+        machine
+            .reel_sets
+            .insert(FREE_SPINS_REEL_SET.to_string(), uniform(&cherry));
+        machine.base_reel_set_weights =
+            vec![(BASE_REEL_SET.to_string(), 3), ("scatters".to_string(), 1)];
+
+        let mut drawn = 0;
+        for seed in 0..400 {
+            let result = machine.spin_seeded(1, seed);
+            match result.reel_set.as_deref() {
+                Some("scatters") => {
+                    drawn += 1;
+                    assert!(result.grid.iter().flatten().all(|s| s.id == scatter.id));
+                }
+                None => {}
+                other => panic!("unexpected reel set {other:?}"),
+            }
+            // 同一种子总是抽到同一个卷轴组
+            assert_eq!(machine.spin_seeded(1, seed).reel_set, result.reel_set);
+        }
+        assert!((60..140).contains(&drawn), "drew scatters {drawn} times");
+
+        let free = machine.spin_free_seeded(1, 3, &[]);
+        assert_eq!(free.reel_set.as_deref(), Some(FREE_SPINS_REEL_SET));
+        assert!(free.grid.iter().flatten().all(|s| s.id == cherry.id));
+    }
+
+    #[test]
+    fn test_wild_multiplier_and_substitution_rules() {
+        let mut machine = test_machine(false);
+        machine.wild_features = vec![WildFeature {
+            symbol_id: 3,
+            multiplier: 3,
+            cannot_substitute: vec![2],
+            expanding: false,
+            sticky: false,
+            walking: false,
+        }];
+        // 单个"卷轴"中的符号依次作为一条线
+        let line = |names: &[&str]| {
+            machine
+                .line_win(&grid_of(&machine, &[names])[0])
+                .map(|(_, win)| win)
+        };
+
+        assert_eq!(
+            line(&["Cherry", "Cherry", "Cherry", "Bell", "Bell"]),
+            Some(5)
+        );
+        assert_eq!(
+            line(&["Wild", "Cherry", "Wild", "Bell", "Bell"]),
+            Some(5 * 9)
+        );
+        assert_eq!(line(&["Bell", "Wild", "Bell", "Bell", "Cherry"]), None);
+        assert_eq!(line(&["Wild", "Scatter", "Scatter", "Bell", "Bell"]), None);
+    }
+
+    #[test]
+    fn test_expanding_sticky_and_walking_wilds() {
+        let mut machine = test_machine(false);
+        machine
+            .symbols
+            .push(test_symbol(5, "Walker", SymbolType::Wild, &[]));
+        machine.wild_features = vec![
+            WildFeature {
+                symbol_id: 3,
+                multiplier: 1,
+                cannot_substitute: Vec::new(),
+                expanding: true,
+                sticky: true,
+                walking: false,
+            },
+            WildFeature {
+                symbol_id: 5,
+                multiplier: 1,
+                cannot_substitute: Vec::new(),
+                expanding: false,
+                sticky: false,
+                walking: true,
+            },
+        ];
+
+        let mut grid = grid_of(
+            &machine,
+            &[
+                &["Walker", "Cherry", "Bell"],
+                &["Cherry", "Bell", "Cherry"],
+                &["Bell", "Wild", "Cherry"],
+                &["Cherry", "Walker", "Bell"],
+                &["Bell", "Bell", "Cherry"],
+            ],
+        );
+
+        assert_eq!(machine.expand_wilds(&mut grid), vec![2]);
+        assert!(grid[2].iter().all(|s| s.name == "Wild"));
+
+        // 第一个卷轴上的移动Wild移出画面，粘性扩展Wild保留整个卷轴
+        let held = machine.next_held_wilds(&grid);
+        assert_eq!(held.len(), 4);
+        assert!(held.contains(&HeldWild {
+            symbol_id: 5,
+            reel: 2,
+            row: 1
+        }));
+        assert_eq!(held.iter().filter(|h| h.symbol_id == 3).count(), 3);
+
+        let result = machine.spin_free_seeded(1, 7, &held);
+        assert!(result.grid[2]
+            .iter()
+            .all(|s| s.symbol_type == SymbolType::Wild));
+    }
+
+    #[test]
+    fn test_large_wins_are_capped_instead_of_overflowing() {
+        let mut machine = test_machine(true);
+        machine.wild_features = vec![WildFeature {
+            symbol_id: 3,
+            multiplier: 1000,
+            cannot_substitute: Vec::new(),
+            expanding: false,
+            sticky: false,
+            walking: false,
+        }];
+
+        let grid = grid_of(
+            &machine,
+            &[
+                &["Wild", "Wild", "Wild"],
+                &["Wild", "Wild", "Wild"],
+                &["Wild", "Wild", "Wild"],
+                &["Wild", "Wild", "Wild"],
+                &["Wild", "Wild", "Wild"],
+            ],
+        );
+        let wins = machine.check_megaway_wins(&grid);
+        assert!(wins.iter().all(|w| w.win_multiplier == u32::MAX));
+        assert_eq!(machine.calculate_total_win(&wins, u32::MAX), u32::MAX);
+
+        let scatter = machine.symbols.iter_mut().find(|s| s.name == "Scatter");
+        scatter.unwrap().payouts = HashMap::from([(3, 100)]);
+        let scatters = grid_of(
+            &machine,
+            &[
+                &["Scatter"],
+                &["Scatter"],
+                &["Scatter"],
+                &["Scatter"],
+                &["Scatter"],
+            ],
+        );
+        let scatter_wins = machine.check_scatter_wins(&scatters, u32::MAX);
+        assert!(!scatter_wins.is_empty());
+        assert!(scatter_wins.iter().all(|w| w.win_amount == u32::MAX));
+    }
+
+    #[test]
+    fn test_megaway_ways_counting() {
+        let mut machine = test_machine(true);
+        machine.wild_features = vec![WildFeature {
+            symbol_id: 3,
+            multiplier: 2,
+            cannot_substitute: Vec::new(),
+            expanding: false,
+            sticky: false,
+            walking: false,
+        }];
+
+        let grid = grid_of(
+            &machine,
+            &[
+                &["Cherry", "Cherry", "Bell"],
+                &["Wild", "Bell"],
+                &["Cherry", "Scatter", "Cherry", "Cherry"],
+                &["Bell", "Bell"],
+                &["Cherry", "Cherry"],
+            ],
+        );
+
+        let wins = machine.check_megaway_wins(&grid);
+        assert_eq!(wins.len(), 1);
+
+        // Cherry：2 x 1(Wild) x 3 = 6路，Wild倍数2，3个卷轴赔付5
+        assert_eq!(wins[0].symbols[0].name, "Cherry");
+        assert_eq!(wins[0].ways, Some(6));
+        assert_eq!(wins[0].win_multiplier, 5 * 2 * 2 * 3);
+    }
+
+    // 旧实现：每次旋转按权重展开符号池
+    fn expanded_pool_index<R: Rng + ?Sized>(reel_strip: &ReelStrip, rng: &mut R) -> usize {
+        let pool: Vec<usize> = reel_strip
+            .symbols
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (_, weight))| std::iter::repeat_n(i, *weight as usize))
+            .collect();
+        pool[roll(rng, pool.len() as u64) as usize]
+    }
+
+    #[test]
+    fn test_reel_sampler_sums_large_weights_without_overflow() {
+        let cherry = test_symbol(1, "Cherry", SymbolType::Normal, &[]);
+        let bell = test_symbol(2, "Bell", SymbolType::Normal, &[]);
+        let max = i32::MAX as u32;
+        let sampler = ReelSampler::new(&ReelStrip {
+            reel_number: 0,
+            symbols: vec![(cherry, max), (bell, max)],
+        });
+        assert_eq!(sampler.total_weight(), 2 * max as u64);
+
+        // 两个位置各占一半，分界在第一个位置的权重处
+        let first = max as u64;
+        assert_eq!(sampler.sample(&mut FixedRng(vec![first - 1])), 0);
+        assert_eq!(sampler.sample(&mut FixedRng(vec![first])), 1);
+    }
+
+    #[test]
+    fn test_reel_sampler_matches_expanded_pool() {
+        let machine = test_machine(false);
+        let reel_strip = &machine.reel_strips[0];
+        let sampler = &machine.reel_samplers[0];
+        assert_eq!(sampler.total_weight(), 10);
+
+        let mut a = ChaCha20Rng::seed_from_u64(99);
+        let mut b = ChaCha20Rng::seed_from_u64(99);
+        for _ in 0..1000 {
+            assert_eq!(
+                sampler.sample(&mut a),
+                expanded_pool_index(reel_strip, &mut b)
+            );
+        }
+    }
+
+    // cargo test --release reel_sampling_throughput -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn test_reel_sampling_throughput() {
+        let mut machine = test_machine(true);
+        for reel_strip in &mut machine.reel_strips {
+            for (_, weight) in &mut reel_strip.symbols {
+                *weight *= 1000;
+            }
+        }
+        let machine = UniversalSlotMachine::new(
+            machine.config,
+            machine.symbols,
+            machine.reel_strips,
+            machine.paylines,
+        );
+        let spins = 100_000;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let start = std::time::Instant::now();
+        for _ in 0..spins {
+            for sampler in &machine.reel_samplers {
+                std::hint::black_box(sampler.sample(&mut rng));
+            }
+        }
+        let sampled = start.elapsed();
+
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let start = std::time::Instant::now();
+        for _ in 0..spins / 100 {
+            for reel_strip in &machine.reel_strips {
+                std::hint::black_box(expanded_pool_index(reel_strip, &mut rng));
+            }
+        }
+        let expanded = start.elapsed() * 100;
+
+        let start = std::time::Instant::now();
+        for seed in 0..spins {
+            std::hint::black_box(machine.spin_seeded(1, seed));
+        }
+        let full_spins = start.elapsed();
+
+        println!("reel draws, cumulative table: {sampled:?} per {spins} spins");
+        println!("reel draws, expanded pool:    {expanded:?} per {spins} spins (extrapolated)");
+        println!(
+            "full Megaway spins: {:.0} spins/s",
+            spins as f64 / full_spins.as_secs_f64()
+        );
+        assert!(sampled < expanded);
+    }
+
+    #[test]
+    fn test_stepper_mode_reads_contiguous_window() {
+        let mut machine = test_machine(false);
+        machine.config.reel_mode = ReelMode::Stepper;
+
+        let result = machine.spin_seeded(1, 2024);
+        let stops = result.reel_stops.clone().unwrap();
+        assert_eq!(stops.len(), 5);
+
+        for (reel_idx, reel) in result.grid.iter().enumerate() {
+            let strip = &machine.reel_strips[reel_idx].symbols;
+            for (row, symbol) in reel.iter().enumerate() {
+                assert_eq!(symbol.id, strip[(stops[reel_idx] + row) % strip.len()].0.id);
+            }
+        }
+
+        // 停在最后一个位置时窗口回到卷轴条开头
+        let window = machine.reel_window(&machine.reel_strips[0], 3, 3);
+        let names: Vec<&str> = window.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Scatter", "Cherry", "Bell"]);
+    }
+
+    // 依次返回给定的 u64，用于检查抽样映射
+    struct FixedRng(Vec<u64>);
+
+    impl rand::RngCore for FixedRng {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0.remove(0)
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            for chunk in dst.chunks_mut(8) {
+                let bytes = self.next_u64().to_le_bytes();
+                chunk.copy_from_slice(&bytes[..chunk.len()]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_respin_streams_do_not_overlap_between_rounds() {
+        let draws = |seed, respin| -> Vec<u64> {
+            let mut rng = HoldAndSpinState::respin_rng(seed, respin);
+            (0..4).map(|_| rng.random::<u64>()).collect()
+        };
+        assert_eq!(draws(10, 2), draws(10, 2));
+        // 相邻触发种子的相邻重转不再得到相同的随机数
+        assert_ne!(draws(10, 2), draws(11, 1));
+        assert_ne!(draws(10, 1), draws(10, 2));
+    }
+
+    #[test]
+    fn test_roll_maps_u64_to_range() {
+        // 2^64 对3取余为1，u64::MAX 落在尾部被丢弃，7 % 3 = 1
+        assert_eq!(roll(&mut FixedRng(vec![u64::MAX, 7]), 3), 1);
+        // 2的幂没有尾部，直接取余
+        assert_eq!(roll(&mut FixedRng(vec![u64::MAX]), 4), 3);
+
+        // 累计权重 [3, 5]：0..3 为第一个位置，3..5 为第二个位置
+        let cherry = test_symbol(1, "Cherry", SymbolType::Normal, &[]);
+        let bell = test_symbol(2, "Bell", SymbolType::Normal, &[]);
+        let sampler = ReelSampler::new(&ReelStrip {
+            reel_number: 0,
+            symbols: vec![(cherry, 3), (bell, 2)],
+        });
+        assert_eq!(sampler.sample(&mut FixedRng(vec![2])), 0);
+        assert_eq!(sampler.sample(&mut FixedRng(vec![8])), 1);
+    }
 }
@@ -0,0 +1,69 @@
+use sqlx::PgConnection;
+
+#[derive(Debug)]
+pub enum WalletError {
+    PlayerNotFound,
+    InsufficientBalance,
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for WalletError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WalletError::PlayerNotFound => write!(f, "Player not found"),
+            WalletError::InsufficientBalance => write!(f, "Insufficient balance"),
+            WalletError::Database(e) => write!(f, "database error: {e}"),
+        }
+    }
+}
+
+impl From<sqlx::Error> for WalletError {
+    fn from(e: sqlx::Error) -> Self {
+        WalletError::Database(e)
+    }
+}
+
+// 锁定玩家余额行（在事务内调用），确认余额足够支付投注
+pub async fn lock_balance(
+    conn: &mut PgConnection,
+    player_id: i32,
+    bet_amount: i64,
+) -> Result<i64, WalletError> {
+    let balance = sqlx::query_scalar!(
+        r#"SELECT COALESCE(balance, 0) as "balance!" FROM players WHERE id = $1 FOR UPDATE"#,
+        player_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(WalletError::PlayerNotFound)?;
+
+    if balance < bet_amount {
+        return Err(WalletError::InsufficientBalance);
+    }
+
+    Ok(balance)
+}
+
+// 扣除投注并加上赢金，返回新余额
+pub async fn settle(
+    conn: &mut PgConnection,
+    player_id: i32,
+    bet_amount: i64,
+    win_amount: i64,
+) -> Result<i64, WalletError> {
+    let balance = sqlx::query_scalar!(
+        r#"
+        UPDATE players SET balance = COALESCE(balance, 0) - $1 + $2
+        WHERE id = $3
+        RETURNING balance as "balance!"
+        "#,
+        bet_amount,
+        win_amount,
+        player_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(WalletError::PlayerNotFound)?;
+
+    Ok(balance)
+}